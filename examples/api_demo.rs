@@ -82,7 +82,9 @@ fn main() {
         ..Default::default()
     };
     println!("  ✓ MCP stdio server configured");
-    println!("  - Server: filesystem");
+    for name in mcp_options.mcp_servers.keys() {
+        println!("  - Server: {}", name);
+    }
     println!();
 
     // 5. Permission Callback
@@ -107,19 +109,13 @@ fn main() {
 
     // Test the callback
     let result = (permission_callback)("Bash".to_string(), json!({}));
-    match result {
-        PermissionResult::Deny { message, .. } => {
-            println!("  - Bash denied: {}", message);
-        }
-        _ => {}
+    if let PermissionResult::Deny { message, .. } = result {
+        println!("  - Bash denied: {}", message);
     }
 
     let result = (permission_callback)("Read".to_string(), json!({}));
-    match result {
-        PermissionResult::Allow { .. } => {
-            println!("  - Read allowed");
-        }
-        _ => {}
+    if let PermissionResult::Allow { .. } = result {
+        println!("  - Read allowed");
     }
     println!();
 
@@ -9,57 +9,36 @@
 //! 1. Blocking bash commands using PreToolUse hook
 //! 2. Adding context to user prompts using UserPromptSubmit hook
 
-use claude_agent_sdk::{ClaudeAgentOptions, ClaudeSDKClient, HookCallback, HookMatcher, Message};
+use claude_agent_sdk::types::{HookSpecificOutput, PreToolUseHookInput, UserPromptSubmitHookInput};
+use claude_agent_sdk::{ClaudeAgentOptions, ClaudeSDKClient, ContentBlock, HookJSONOutput, Message, PermissionBehavior};
 use futures::StreamExt;
-use serde_json::{json, Value};
-use std::sync::Arc;
 
 async fn example_pre_tool_use() {
     println!("=== PreToolUse Hook Example ===");
     println!("This example blocks bash commands using a PreToolUse hook\n");
 
-    // Create a PreToolUse hook that blocks bash commands
-    let pre_tool_use_hook = Arc::new(move |payload: Value| -> Option<Value> {
-        println!("🪝 PreToolUse hook triggered");
-
-        // Extract tool information
-        if let Some(input) = payload.get("input").and_then(|i| i.as_object()) {
-            if let Some(tool_name) = input.get("name").and_then(|n| n.as_str()) {
-                println!("  Tool: {}", tool_name);
-
-                if tool_name == "Bash" {
-                    println!("  ❌ Blocking bash command");
-                    // Return error to block the tool
-                    return Some(json!({
-                        "error": "Bash commands are not allowed in this session"
-                    }));
-                } else {
-                    println!("  ✓ Allowing tool");
-                }
-            }
-        }
-
-        // Return None to allow the tool to proceed
-        None
-    }) as Arc<dyn Fn(Value) -> Option<Value> + Send + Sync>;
-
-    let hook_callback = HookCallback {
-        name: "pre_tool_use".to_string(),
-        callback: pre_tool_use_hook,
-    };
-
-    let options = ClaudeAgentOptions {
-        hooks: Some(vec![(
-            HookMatcher {
-                name: "pre_tool_use".to_string(),
-                matchers: vec![],
-            },
-            vec![hook_callback],
-        )]),
+    let mut options = ClaudeAgentOptions {
         max_turns: Some(3),
         ..Default::default()
     };
 
+    // Register a PreToolUse hook for Bash; the input type selects the event
+    options.add_hook(Some("Bash"), |input: PreToolUseHookInput, _tool_use_id, _ctx| async move {
+        println!("🪝 PreToolUse hook triggered");
+        println!("  Tool: {}", input.tool_name);
+        println!("  Input: {}", input.tool_input);
+        println!("  ❌ Blocking bash command");
+
+        HookJSONOutput {
+            system_message: Some("Bash commands are not allowed in this session".to_string()),
+            hook_specific_output: Some(HookSpecificOutput::PreToolUse {
+                permission_decision: Some(PermissionBehavior::Deny),
+                permission_decision_reason: Some("Bash is disabled by a hook".to_string()),
+            }),
+            ..Default::default()
+        }
+    });
+
     let mut client = ClaudeSDKClient::new(options);
 
     match client.connect().await {
@@ -69,26 +48,30 @@ async fn example_pre_tool_use() {
             println!("Prompt: {}\n", prompt);
             println!("{}", "=".repeat(50));
 
-            if let Err(e) = client.query(prompt).await {
+            if let Err(e) = client.query(prompt.to_string()).await {
                 eprintln!("Query error: {}", e);
                 return;
             }
 
-            let mut stream = client.receive_response();
-            while let Some(result) = stream.next().await {
-                match result {
-                    Ok(message) => match message {
-                        Message::Assistant(msg) => {
-                            if !msg.content.is_empty() {
-                                println!("\nAssistant: {}", msg.content);
+            {
+                let mut stream = client.receive_response();
+                while let Some(result) = stream.next().await {
+                    match result {
+                        Ok(message) => match message {
+                            Message::Assistant { message, .. } => {
+                                for block in message.message.content {
+                                    if let ContentBlock::Text { text } = block {
+                                        println!("\nAssistant: {}", text);
+                                    }
+                                }
+                            }
+                            Message::Result { subtype, .. } => {
+                                println!("\n✓ Result: {}", subtype);
                             }
-                        }
-                        Message::Result(msg) => {
-                            println!("\n✓ Result: {:?}", msg.result_type);
-                        }
-                        _ => {}
-                    },
-                    Err(e) => eprintln!("Error: {}", e),
+                            _ => {}
+                        },
+                        Err(e) => eprintln!("Error: {}", e),
+                    }
                 }
             }
 
@@ -106,47 +89,24 @@ async fn example_user_prompt_submit() {
     println!("=== UserPromptSubmit Hook Example ===");
     println!("This example adds context to user prompts before submission\n");
 
-    // Create a UserPromptSubmit hook that adds context
-    let user_prompt_submit_hook = Arc::new(move |payload: Value| -> Option<Value> {
-        println!("🪝 UserPromptSubmit hook triggered");
-
-        if let Some(prompt) = payload.get("prompt").and_then(|p| p.as_str()) {
-            println!("  Original prompt: {}", prompt);
-
-            // Add additional context
-            let enhanced_prompt = format!(
-                "{}\n\nAdditional context: Please keep your response concise and to the point.",
-                prompt
-            );
-
-            println!("  Enhanced prompt: {}", enhanced_prompt);
-
-            // Return modified prompt
-            return Some(json!({
-                "prompt": enhanced_prompt
-            }));
-        }
-
-        None
-    }) as Arc<dyn Fn(Value) -> Option<Value> + Send + Sync>;
-
-    let hook_callback = HookCallback {
-        name: "user_prompt_submit".to_string(),
-        callback: user_prompt_submit_hook,
-    };
-
-    let options = ClaudeAgentOptions {
-        hooks: Some(vec![(
-            HookMatcher {
-                name: "user_prompt_submit".to_string(),
-                matchers: vec![],
-            },
-            vec![hook_callback],
-        )]),
+    let mut options = ClaudeAgentOptions {
         max_turns: Some(2),
         ..Default::default()
     };
 
+    // Add additional context for the model to every prompt
+    options.add_hook(None, |input: UserPromptSubmitHookInput, _tool_use_id, _ctx| async move {
+        println!("🪝 UserPromptSubmit hook triggered");
+        println!("  Original prompt: {}", input.prompt);
+
+        HookJSONOutput {
+            hook_specific_output: Some(HookSpecificOutput::UserPromptSubmit {
+                additional_context: Some("Please keep your response concise and to the point.".to_string()),
+            }),
+            ..Default::default()
+        }
+    });
+
     let mut client = ClaudeSDKClient::new(options);
 
     match client.connect().await {
@@ -155,26 +115,30 @@ async fn example_user_prompt_submit() {
             println!("Prompt: {}\n", prompt);
             println!("{}", "=".repeat(50));
 
-            if let Err(e) = client.query(prompt).await {
+            if let Err(e) = client.query(prompt.to_string()).await {
                 eprintln!("Query error: {}", e);
                 return;
             }
 
-            let mut stream = client.receive_response();
-            while let Some(result) = stream.next().await {
-                match result {
-                    Ok(message) => match message {
-                        Message::Assistant(msg) => {
-                            if !msg.content.is_empty() {
-                                println!("\nAssistant: {}", msg.content);
+            {
+                let mut stream = client.receive_response();
+                while let Some(result) = stream.next().await {
+                    match result {
+                        Ok(message) => match message {
+                            Message::Assistant { message, .. } => {
+                                for block in message.message.content {
+                                    if let ContentBlock::Text { text } = block {
+                                        println!("\nAssistant: {}", text);
+                                    }
+                                }
+                            }
+                            Message::Result { subtype, .. } => {
+                                println!("\n✓ Result: {}", subtype);
                             }
-                        }
-                        Message::Result(msg) => {
-                            println!("\n✓ Result: {:?}", msg.result_type);
-                        }
-                        _ => {}
-                    },
-                    Err(e) => eprintln!("Error: {}", e),
+                            _ => {}
+                        },
+                        Err(e) => eprintln!("Error: {}", e),
+                    }
                 }
             }
 
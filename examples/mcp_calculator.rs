@@ -240,10 +240,8 @@ fn display_message(msg: &Message) {
             // Show text content and tool usage from content blocks
             for block in &message.message.content {
                 match block {
-                    ContentBlock::Text { text } => {
-                        if !text.is_empty() {
-                            println!("Claude: {}", text);
-                        }
+                    ContentBlock::Text { text } if !text.is_empty() => {
+                        println!("Claude: {}", text);
                     }
                     ContentBlock::ToolUse { name, input, .. } => {
                         println!("Using tool: {}", name);
@@ -299,7 +297,7 @@ async fn main() {
     mcp_servers.insert(
         "calc".to_string(),
         McpServerConfig::SDK {
-            name: "calculator".to_string(),
+            name: calculator.name.clone(),
             instance: Some(()),  // Placeholder - actual server handling needs implementation
         },
     );
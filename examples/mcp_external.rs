@@ -78,10 +78,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                         Message::Assistant { message, .. } => {
                             for block in &message.message.content {
                                 match block {
-                                    ContentBlock::Text { text } => {
-                                        if !text.is_empty() {
-                                            println!("\nClaude: {}", text);
-                                        }
+                                    ContentBlock::Text { text } if !text.is_empty() => {
+                                        println!("\nClaude: {}", text);
                                     }
                                     ContentBlock::ToolUse { name, .. } => {
                                         println!("\n[Using MCP tool: {}]", name);
@@ -0,0 +1,145 @@
+#!/usr/bin/env cargo
+//! Example demonstrating model-related options.
+//!
+//! This example shows:
+//! 1. Extended thinking with a token budget (`max_thinking_tokens`)
+//! 2. A fallback model used when the primary model is overloaded (`fallback_model`)
+//! 3. Structured output matching a JSON schema (`json_schema`)
+
+use claude_agent_sdk::{ClaudeAgentOptions, ClaudeSDKClient, ContentBlock, Message};
+use futures::StreamExt;
+use serde_json::json;
+
+async fn example_extended_thinking() {
+    println!("=== Example 1: Extended Thinking ===");
+    println!("Allowing up to 4000 thinking tokens\n");
+
+    let options = ClaudeAgentOptions {
+        max_thinking_tokens: Some(4000),
+        max_turns: Some(1),
+        ..Default::default()
+    };
+
+    let mut client = ClaudeSDKClient::new(options);
+
+    if let Err(e) = run_query(&mut client, "Is 1001 a prime number?").await {
+        eprintln!("Error: {}", e);
+    }
+
+    println!();
+}
+
+async fn example_fallback_model() {
+    println!("=== Example 2: Fallback Model ===");
+    println!("Using claude-opus-4-1, falling back to claude-sonnet-4-5 when overloaded\n");
+
+    let options = ClaudeAgentOptions {
+        model: Some("claude-opus-4-1".to_string()),
+        fallback_model: Some("claude-sonnet-4-5".to_string()),
+        max_turns: Some(1),
+        ..Default::default()
+    };
+
+    let mut client = ClaudeSDKClient::new(options);
+
+    if let Err(e) = run_query(&mut client, "What is the capital of France?").await {
+        eprintln!("Error: {}", e);
+    }
+
+    println!();
+}
+
+async fn example_structured_output() {
+    println!("=== Example 3: Structured Output ===");
+    println!("Requiring the final answer to match a JSON schema\n");
+
+    let options = ClaudeAgentOptions {
+        json_schema: Some(json!({
+            "type": "object",
+            "properties": {
+                "city": {"type": "string"},
+                "population": {"type": "integer"}
+            },
+            "required": ["city", "population"]
+        })),
+        max_turns: Some(2),
+        ..Default::default()
+    };
+
+    let mut client = ClaudeSDKClient::new(options);
+
+    if let Err(e) = run_query(&mut client, "What is the largest city in Japan?").await {
+        eprintln!("Error: {}", e);
+    }
+
+    println!();
+}
+
+async fn run_query(client: &mut ClaudeSDKClient, prompt: &str) -> Result<(), Box<dyn std::error::Error>> {
+    client.connect().await?;
+
+    println!("Prompt: {}", prompt);
+    println!("{}", "-".repeat(50));
+
+    client.query(prompt.to_string()).await?;
+
+    {
+        let mut stream = client.receive_response();
+        while let Some(result) = stream.next().await {
+            match result? {
+                Message::Assistant { message, .. } => {
+                    for block in message.message.content {
+                        match block {
+                            ContentBlock::Thinking { thinking, .. } => println!("\n💭 Thinking: {}", thinking),
+                            ContentBlock::Text { text } => println!("\nAssistant: {}", text),
+                            _ => {}
+                        }
+                    }
+                }
+                Message::Result {
+                    subtype,
+                    structured_output,
+                    model_usage,
+                    ..
+                } => {
+                    if let Some(output) = structured_output {
+                        println!("\n📦 Structured output: {}", output);
+                    }
+                    if let Some(model_usage) = model_usage {
+                        let models: Vec<&String> = model_usage.keys().collect();
+                        println!("\nModels used: {:?}", models);
+                    }
+                    println!("\n✓ Result: {}", subtype);
+                    break;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    client.disconnect().await?;
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() {
+    println!("Model Option Examples");
+    println!("{}", "=".repeat(50));
+    println!();
+
+    example_extended_thinking().await;
+    println!("{}", "=".repeat(50));
+    println!();
+
+    example_fallback_model().await;
+    println!("{}", "=".repeat(50));
+    println!();
+
+    example_structured_output().await;
+    println!("{}", "=".repeat(50));
+
+    println!("\nKey takeaways:");
+    println!("- max_thinking_tokens: Budget for extended thinking");
+    println!("- fallback_model: Keeps requests going when the primary model is overloaded");
+    println!("- json_schema: The final answer arrives as JSON in the Result's structured_output");
+}
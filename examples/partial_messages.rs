@@ -11,9 +11,9 @@
 //! Note: Partial message streaming requires the CLI to support it, and the
 //! messages will include StreamEvent messages interspersed with regular messages.
 
-use claude_agent_sdk::{ClaudeAgentOptions, ClaudeSDKClient, Message};
+use claude_agent_sdk::types::ContentBlockDelta;
+use claude_agent_sdk::{ClaudeAgentOptions, ClaudeSDKClient, Message, MessageAccumulator, StreamEvent, SystemMessage};
 use futures::StreamExt;
-use std::collections::HashMap;
 
 #[tokio::main]
 async fn main() {
@@ -22,14 +22,11 @@ async fn main() {
     println!();
 
     // Enable partial message streaming with extended thinking
-    let mut env = HashMap::new();
-    env.insert("MAX_THINKING_TOKENS".to_string(), "8000".to_string());
-
     let options = ClaudeAgentOptions {
         include_partial_messages: true,
         model: Some("claude-sonnet-4-5".to_string()),
         max_turns: Some(2),
-        env,
+        max_thinking_tokens: Some(8000),
         ..Default::default()
     };
 
@@ -49,7 +46,7 @@ async fn main() {
 
             let mut message_count = 0;
             let mut stream_event_count = 0;
-            let mut partial_text = String::new();
+            let mut accumulator = MessageAccumulator::new();
 
             let mut stream = client.receive_response();
             while let Some(result) = stream.next().await {
@@ -60,27 +57,34 @@ async fn main() {
                         match &message {
                             Message::StreamEvent { event, .. } => {
                                 stream_event_count += 1;
-                                println!("\n📡 StreamEvent #{}", stream_event_count);
-
-                                // Show event type if available
-                                if let Some(event_type) = event.get("type").and_then(|t| t.as_str()) {
-                                    println!("   Type: {}", event_type);
-                                }
-
-                                // Show partial content if available
-                                if let Some(delta) = event.get("delta") {
-                                    if let Some(text) = delta.get("text").and_then(|t| t.as_str()) {
-                                        partial_text.push_str(text);
-                                        print!("{}", text);
+                                let before = accumulator.text().len();
+                                accumulator.push_message(&message);
+
+                                match event {
+                                    StreamEvent::ContentBlockDelta {
+                                        delta: ContentBlockDelta::TextDelta { .. },
+                                        ..
+                                    } => {
+                                        // Print only the newly arrived text
+                                        print!("{}", &accumulator.text()[before..]);
                                         std::io::Write::flush(&mut std::io::stdout()).ok();
                                     }
-                                }
-
-                                // Show thinking if available
-                                if let Some(thinking) = event.get("thinking").and_then(|t| t.as_str()) {
-                                    if !thinking.is_empty() {
+                                    StreamEvent::ContentBlockDelta {
+                                        delta: ContentBlockDelta::ThinkingDelta { thinking },
+                                        ..
+                                    } if !thinking.is_empty() => {
                                         println!("\n💭 Thinking: {}", thinking);
                                     }
+                                    StreamEvent::ContentBlockDelta {
+                                        delta: ContentBlockDelta::InputJsonDelta { .. },
+                                        index,
+                                    } => {
+                                        if let Some(block) = accumulator.blocks().get(*index) {
+                                            println!("\n🔧 Tool input so far: {:?}", block.partial_input());
+                                        }
+                                    }
+                                    StreamEvent::Unknown(raw) => println!("\n📡 Unknown stream event: {}", raw),
+                                    _ => {}
                                 }
                             }
                             Message::Assistant { message, .. } => {
@@ -89,16 +93,12 @@ async fn main() {
                                 // Show content blocks
                                 for block in &message.message.content {
                                     match block {
-                                        claude_agent_sdk::ContentBlock::Text { text } => {
-                                            if !text.is_empty() {
-                                                println!("   Text: {}", text);
-                                            }
+                                        claude_agent_sdk::ContentBlock::Text { text } if !text.is_empty() => {
+                                            println!("   Text: {}", text);
                                         }
-                                        claude_agent_sdk::ContentBlock::Thinking { thinking, .. } => {
-                                            if !thinking.is_empty() {
-                                                println!("\n💭 Complete Thinking:");
-                                                println!("   {}", thinking);
-                                            }
+                                        claude_agent_sdk::ContentBlock::Thinking { thinking, .. } if !thinking.is_empty() => {
+                                            println!("\n💭 Complete Thinking:");
+                                            println!("   {}", thinking);
                                         }
                                         _ => {}
                                     }
@@ -113,11 +113,18 @@ async fn main() {
                                     println!("\n👤 User: {:?}", content);
                                 }
                             }
-                            Message::System { subtype, data, .. } => {
-                                if subtype != "init" {
-                                    println!("\n⚙️  System [{}]: {:?}", subtype, data);
+                            Message::System(system) => {
+                                if !matches!(system, SystemMessage::Init(_)) {
+                                    println!("\n⚙️  System [{}]: {:?}", system.subtype(), system);
                                 }
                             }
+                            Message::Reconnected { attempt, .. } => {
+                                println!("\n🔄 Reconnected after CLI crash (attempt {})", attempt);
+                                break;
+                            }
+                            Message::Unknown { raw } => {
+                                println!("\n❓ Unknown message: {}", raw);
+                            }
                         }
                     }
                     Err(e) => {
@@ -131,7 +138,7 @@ async fn main() {
             println!("Statistics:");
             println!("  Total messages: {}", message_count);
             println!("  Stream events: {}", stream_event_count);
-            println!("  Partial text length: {} chars", partial_text.len());
+            println!("  Last streamed message: {} chars", accumulator.text().len());
 
             drop(stream);
 
@@ -28,6 +28,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                         ContentBlock::ToolResult { tool_use_id, .. } => {
                             println!("[Tool result for: {}]", tool_use_id);
                         }
+                        other => {
+                            println!("[Other block: {:?}]", other);
+                        }
                     }
                 }
             }
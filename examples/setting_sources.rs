@@ -19,24 +19,15 @@
 //! cargo run --example setting_sources all      # Run all examples
 //! cargo run --example setting_sources default  # Run specific example
 
-use claude_agent_sdk::{ClaudeAgentOptions, ClaudeSDKClient, Message};
+use claude_agent_sdk::{ClaudeAgentOptions, ClaudeSDKClient, Message, SettingSource, SystemMessage};
 use futures::StreamExt;
 use std::env;
 
 fn extract_slash_commands(msg: &Message) -> Vec<String> {
-    if let Message::System(system_msg) = msg {
-        if system_msg.subtype == "init" {
-            if let Some(commands) = system_msg.data.get("slash_commands") {
-                if let Some(arr) = commands.as_array() {
-                    return arr
-                        .iter()
-                        .filter_map(|v| v.as_str().map(|s| s.to_string()))
-                        .collect();
-                }
-            }
-        }
+    match msg {
+        Message::System(SystemMessage::Init(init)) => init.slash_commands.clone(),
+        _ => Vec::new(),
     }
-    Vec::new()
 }
 
 async fn example_default() {
@@ -57,7 +48,7 @@ async fn example_default() {
         return;
     }
 
-    if let Err(e) = client.query("What is 2 + 2?").await {
+    if let Err(e) = client.query("What is 2 + 2?".to_string()).await {
         eprintln!("Query error: {}", e);
         return;
     }
@@ -66,22 +57,21 @@ async fn example_default() {
     while let Some(result) = stream.next().await {
         match result {
             Ok(message) => {
-                if let Message::System(ref msg) = message {
-                    if msg.subtype == "init" {
-                        let commands = extract_slash_commands(&message);
-                        println!("Available slash commands: {:?}", commands);
-                        if commands.contains(&"commit".to_string()) {
-                            println!("❌ /commit is available (unexpected)");
-                        } else {
-                            println!("✓ /commit is NOT available (expected - no settings loaded)");
-                        }
-                        break;
+                if let Message::System(SystemMessage::Init(_)) = message {
+                    let commands = extract_slash_commands(&message);
+                    println!("Available slash commands: {:?}", commands);
+                    if commands.contains(&"commit".to_string()) {
+                        println!("❌ /commit is available (unexpected)");
+                    } else {
+                        println!("✓ /commit is NOT available (expected - no settings loaded)");
                     }
+                    break;
                 }
             }
             Err(e) => eprintln!("Error: {}", e),
         }
     }
+    drop(stream);
 
     let _ = client.disconnect().await;
     println!();
@@ -93,7 +83,7 @@ async fn example_user_only() {
     println!("Expected: Project slash commands (like /commit) will NOT be available\n");
 
     let options = ClaudeAgentOptions {
-        setting_sources: Some(vec!["user".to_string()]),
+        setting_sources: Some(vec![SettingSource::User]),
         max_turns: Some(1),
         ..Default::default()
     };
@@ -105,7 +95,7 @@ async fn example_user_only() {
         return;
     }
 
-    if let Err(e) = client.query("What is 2 + 2?").await {
+    if let Err(e) = client.query("What is 2 + 2?".to_string()).await {
         eprintln!("Query error: {}", e);
         return;
     }
@@ -114,22 +104,21 @@ async fn example_user_only() {
     while let Some(result) = stream.next().await {
         match result {
             Ok(message) => {
-                if let Message::System(ref msg) = message {
-                    if msg.subtype == "init" {
-                        let commands = extract_slash_commands(&message);
-                        println!("Available slash commands: {:?}", commands);
-                        if commands.contains(&"commit".to_string()) {
-                            println!("❌ /commit is available (unexpected)");
-                        } else {
-                            println!("✓ /commit is NOT available (expected)");
-                        }
-                        break;
+                if let Message::System(SystemMessage::Init(_)) = message {
+                    let commands = extract_slash_commands(&message);
+                    println!("Available slash commands: {:?}", commands);
+                    if commands.contains(&"commit".to_string()) {
+                        println!("❌ /commit is available (unexpected)");
+                    } else {
+                        println!("✓ /commit is NOT available (expected)");
                     }
+                    break;
                 }
             }
             Err(e) => eprintln!("Error: {}", e),
         }
     }
+    drop(stream);
 
     let _ = client.disconnect().await;
     println!();
@@ -141,7 +130,7 @@ async fn example_project_and_user() {
     println!("Expected: Project slash commands (like /commit) WILL be available\n");
 
     let options = ClaudeAgentOptions {
-        setting_sources: Some(vec!["user".to_string(), "project".to_string()]),
+        setting_sources: Some(vec![SettingSource::User, SettingSource::Project]),
         max_turns: Some(1),
         ..Default::default()
     };
@@ -153,7 +142,7 @@ async fn example_project_and_user() {
         return;
     }
 
-    if let Err(e) = client.query("What is 2 + 2?").await {
+    if let Err(e) = client.query("What is 2 + 2?".to_string()).await {
         eprintln!("Query error: {}", e);
         return;
     }
@@ -162,22 +151,21 @@ async fn example_project_and_user() {
     while let Some(result) = stream.next().await {
         match result {
             Ok(message) => {
-                if let Message::System(ref msg) = message {
-                    if msg.subtype == "init" {
-                        let commands = extract_slash_commands(&message);
-                        println!("Available slash commands: {:?}", commands);
-                        if commands.contains(&"commit".to_string()) {
-                            println!("✓ /commit is available (expected)");
-                        } else {
-                            println!("❌ /commit is NOT available (unexpected)");
-                        }
-                        break;
+                if let Message::System(SystemMessage::Init(_)) = message {
+                    let commands = extract_slash_commands(&message);
+                    println!("Available slash commands: {:?}", commands);
+                    if commands.contains(&"commit".to_string()) {
+                        println!("✓ /commit is available (expected)");
+                    } else {
+                        println!("❌ /commit is NOT available (unexpected)");
                     }
+                    break;
                 }
             }
             Err(e) => eprintln!("Error: {}", e),
         }
     }
+    drop(stream);
 
     let _ = client.disconnect().await;
     println!();
@@ -7,7 +7,7 @@
 //! - Monitoring internal operations
 //! - Filtering and logging specific events
 
-use claude_agent_sdk::{query, ClaudeAgentOptions};
+use claude_agent_sdk::{query, ClaudeAgentOptions, ContentBlock, Message};
 use futures::StreamExt;
 use std::sync::{Arc, Mutex};
 
@@ -47,7 +47,7 @@ async fn main() {
     extra_args.insert("debug-to-stderr".to_string(), None);
 
     let options = ClaudeAgentOptions {
-        stderr: Some(stderr_callback),
+        stderr_callback: Some(stderr_callback),
         extra_args,
         max_turns: Some(1),
         ..Default::default()
@@ -57,17 +57,18 @@ async fn main() {
     println!("{}", "-".repeat(50));
 
     // Run a simple query
-    match query("What is 2+2?", options).await {
+    match query("What is 2+2?".to_string(), options).await {
         Ok(mut stream) => {
             while let Some(result) = stream.next().await {
                 match result {
-                    Ok(message) => {
-                        if let Some(content) = message.get("content").and_then(|c| c.as_str()) {
-                            if !content.is_empty() {
-                                println!("\nResponse: {}", content);
+                    Ok(Message::Assistant { message, .. }) => {
+                        for block in message.message.content {
+                            if let ContentBlock::Text { text } = block {
+                                println!("\nResponse: {}", text);
                             }
                         }
                     }
+                    Ok(_) => {}
                     Err(e) => eprintln!("Error: {}", e),
                 }
             }
@@ -1,6 +1,6 @@
 //! Streaming mode example with ClaudeSDKClient
 
-use claude_agent_sdk::{ClaudeAgentOptions, ClaudeSDKClient, ContentBlock, Message};
+use claude_agent_sdk::{ClaudeAgentOptions, ClaudeSDKClient, ContentBlock, Message, PermissionMode};
 use futures::StreamExt;
 
 #[tokio::main]
@@ -8,7 +8,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Create options
     let options = ClaudeAgentOptions {
         allowed_tools: vec!["Read".to_string(), "Bash".to_string()],
-        permission_mode: Some("acceptEdits".to_string()),
+        permission_mode: Some(PermissionMode::AcceptEdits),
         ..Default::default()
     };
 
@@ -1,13 +1,14 @@
 #!/usr/bin/env cargo
 //! Example demonstrating different system prompt configurations.
 //!
-//! This example shows four ways to configure the system prompt:
+//! This example shows five ways to configure the system prompt:
 //! 1. No system prompt (default)
 //! 2. Custom string system prompt
 //! 3. Preset system prompt (uses Claude Code's built-in preset)
 //! 4. Preset + append (adds to the built-in preset)
+//! 5. Prompt file with `{var}` placeholders
 
-use claude_agent_sdk::{ClaudeAgentOptions, ClaudeSDKClient, Message, SystemPromptConfig};
+use claude_agent_sdk::{ClaudeAgentOptions, ClaudeSDKClient, ContentBlock, Message, SystemPrompt};
 use futures::StreamExt;
 use std::collections::HashMap;
 
@@ -34,7 +35,7 @@ async fn example_string_system_prompt() {
     println!("Custom system prompt as a string\n");
 
     let options = ClaudeAgentOptions {
-        system_prompt: Some(SystemPromptConfig::String(
+        system_prompt: Some(SystemPrompt::Text(
             "You are a helpful assistant that always responds in a very concise manner. \
              Keep all responses to one sentence or less."
                 .to_string(),
@@ -56,11 +57,11 @@ async fn example_preset_system_prompt() {
     println!("=== Example 3: Preset System Prompt ===");
     println!("Using Claude Code's built-in preset system prompt\n");
 
-    let mut config = HashMap::new();
-    config.insert("type".to_string(), "preset".to_string());
-
     let options = ClaudeAgentOptions {
-        system_prompt: Some(SystemPromptConfig::Preset(config)),
+        system_prompt: Some(SystemPrompt::Preset {
+            preset: "claude_code".to_string(),
+            append: None,
+        }),
         max_turns: Some(1),
         ..Default::default()
     };
@@ -78,15 +79,11 @@ async fn example_preset_with_append() {
     println!("=== Example 4: Preset + Append System Prompt ===");
     println!("Using preset and appending additional instructions\n");
 
-    let mut config = HashMap::new();
-    config.insert("type".to_string(), "preset".to_string());
-    config.insert(
-        "append".to_string(),
-        "Always end your responses with 'Hope this helps!'".to_string(),
-    );
-
     let options = ClaudeAgentOptions {
-        system_prompt: Some(SystemPromptConfig::Preset(config)),
+        system_prompt: Some(SystemPrompt::Preset {
+            preset: "claude_code".to_string(),
+            append: Some("Always end your responses with 'Hope this helps!'".to_string()),
+        }),
         max_turns: Some(1),
         ..Default::default()
     };
@@ -100,30 +97,61 @@ async fn example_preset_with_append() {
     println!();
 }
 
+async fn example_system_prompt_file() {
+    println!("=== Example 5: System Prompt File with Variables ===");
+    println!("Loading the prompt from disk and filling in {{persona}}\n");
+
+    let path = std::env::temp_dir().join("claude_sdk_system_prompt.md");
+    if let Err(e) = std::fs::write(&path, "You are {persona}. Answer in one short sentence.") {
+        eprintln!("Error writing prompt file: {}", e);
+        return;
+    }
+
+    let options = ClaudeAgentOptions {
+        system_prompt: Some(SystemPrompt::File(path.clone())),
+        system_prompt_vars: HashMap::from([("persona".to_string(), "a pirate".to_string())]),
+        max_turns: Some(1),
+        ..Default::default()
+    };
+
+    let mut client = ClaudeSDKClient::new(options);
+
+    if let Err(e) = run_query(&mut client, "What is the capital of France?").await {
+        eprintln!("Error: {}", e);
+    }
+
+    let _ = std::fs::remove_file(path);
+    println!();
+}
+
 async fn run_query(client: &mut ClaudeSDKClient, prompt: &str) -> Result<(), Box<dyn std::error::Error>> {
     client.connect().await?;
 
     println!("Prompt: {}", prompt);
     println!("{}", "-".repeat(50));
 
-    client.query(prompt).await?;
-
-    let mut stream = client.receive_response();
-    while let Some(result) = stream.next().await {
-        match result {
-            Ok(message) => match message {
-                Message::Assistant(msg) => {
-                    if !msg.content.is_empty() {
-                        println!("\nAssistant: {}", msg.content);
+    client.query(prompt.to_string()).await?;
+
+    {
+        let mut stream = client.receive_response();
+        while let Some(result) = stream.next().await {
+            match result {
+                Ok(message) => match message {
+                    Message::Assistant { message, .. } => {
+                        for block in message.message.content {
+                            if let ContentBlock::Text { text } = block {
+                                println!("\nAssistant: {}", text);
+                            }
+                        }
+                    }
+                    Message::Result { subtype, .. } => {
+                        println!("\n✓ Result: {}", subtype);
+                        break;
                     }
-                }
-                Message::Result(msg) => {
-                    println!("\n✓ Result: {:?}", msg.result_type);
-                    break;
-                }
-                _ => {}
-            },
-            Err(e) => return Err(e.into()),
+                    _ => {}
+                },
+                Err(e) => return Err(e.into()),
+            }
         }
     }
 
@@ -152,10 +180,15 @@ async fn main() {
 
     example_preset_with_append().await;
     println!("{}", "=".repeat(50));
+    println!();
+
+    example_system_prompt_file().await;
+    println!("{}", "=".repeat(50));
 
     println!("\nKey takeaways:");
     println!("- No system prompt: Uses Claude's default behavior");
     println!("- String: Complete custom system prompt");
     println!("- Preset: Uses Claude Code's built-in optimized prompt");
     println!("- Preset + Append: Extends the built-in prompt with custom instructions");
+    println!("- File / AppendFile: Read from disk at connect time, with {{var}} templates");
 }
@@ -12,11 +12,58 @@
 //! 2. Allows Read tool usage
 //! 3. Modifies Glob patterns to be more restrictive
 
-use claude_agent_sdk::{ClaudeAgentOptions, ClaudeSDKClient, Message, PermissionResult};
+use claude_agent_sdk::types::CanUseToolCallback;
+use claude_agent_sdk::{
+    BuiltinToolInput, ClaudeAgentOptions, ClaudeSDKClient, ContentBlock, Message, PermissionResult, ToolPermissionContext,
+};
 use futures::StreamExt;
 use serde_json::Value;
 use std::sync::Arc;
 
+fn decide(tool_name: &str, tool_input: &Value) -> PermissionResult {
+    println!("🔒 Permission check for tool: {}", tool_name);
+
+    match BuiltinToolInput::parse(tool_name, tool_input) {
+        BuiltinToolInput::Bash(bash) => {
+            println!("  ❌ DENIED: Bash command `{}` is not allowed", bash.command);
+            PermissionResult::Deny {
+                message: "Bash commands are disabled for security reasons".to_string(),
+                interrupt: false,
+            }
+        }
+        BuiltinToolInput::Read(read) => {
+            println!("  ✓ ALLOWED: Read {}", read.file_path);
+            PermissionResult::Allow {
+                updated_input: None,
+                updated_permissions: None,
+            }
+        }
+        BuiltinToolInput::Glob(mut glob) => {
+            // Modify glob patterns to be more restrictive
+            println!("  🔧 MODIFIED: Restricting glob pattern");
+            println!("     Original: {}", glob.pattern);
+
+            // Only allow searching in specific directories
+            if !glob.pattern.starts_with("examples/") {
+                glob.pattern = format!("examples/{}", glob.pattern);
+            }
+            println!("     Modified: {}", glob.pattern);
+
+            PermissionResult::Allow {
+                updated_input: serde_json::to_value(&glob).ok(),
+                updated_permissions: None,
+            }
+        }
+        _ => {
+            println!("  ✓ ALLOWED: {} tool", tool_name);
+            PermissionResult::Allow {
+                updated_input: None,
+                updated_permissions: None,
+            }
+        }
+    }
+}
+
 #[tokio::main]
 async fn main() {
     println!("Tool Permission Callback Example");
@@ -24,67 +71,10 @@ async fn main() {
     println!();
 
     // Create permission callback
-    let permission_callback = Arc::new(
-        move |tool_name: String, tool_input: Value| -> PermissionResult {
-            println!("🔒 Permission check for tool: {}", tool_name);
-
-            match tool_name.as_str() {
-                "Bash" => {
-                    println!("  ❌ DENIED: Bash commands are not allowed");
-                    PermissionResult::Deny {
-                        message: "Bash commands are disabled for security reasons".to_string(),
-                        interrupt: false,
-                    }
-                }
-                "Read" => {
-                    println!("  ✓ ALLOWED: Read tool");
-                    PermissionResult::Allow {
-                        updated_input: None,
-                        updated_permissions: None,
-                    }
-                }
-                "Glob" => {
-                    // Modify glob patterns to be more restrictive
-                    if let Some(pattern) = tool_input.get("pattern").and_then(|p| p.as_str()) {
-                        println!("  🔧 MODIFIED: Restricting glob pattern");
-                        println!("     Original: {}", pattern);
-
-                        // Only allow searching in specific directories
-                        let restricted_pattern = if pattern.starts_with("examples/") {
-                            pattern.to_string()
-                        } else {
-                            format!("examples/{}", pattern)
-                        };
-
-                        println!("     Modified: {}", restricted_pattern);
-
-                        let mut modified_input = tool_input.clone();
-                        if let Some(obj) = modified_input.as_object_mut() {
-                            obj.insert("pattern".to_string(), restricted_pattern.into());
-                        }
-
-                        PermissionResult::Allow {
-                            updated_input: Some(modified_input),
-                            updated_permissions: None,
-                        }
-                    } else {
-                        println!("  ✓ ALLOWED: Glob tool (no pattern to modify)");
-                        PermissionResult::Allow {
-                            updated_input: None,
-                            updated_permissions: None,
-                        }
-                    }
-                }
-                _ => {
-                    println!("  ✓ ALLOWED: {} tool", tool_name);
-                    PermissionResult::Allow {
-                        updated_input: None,
-                        updated_permissions: None,
-                    }
-                }
-            }
-        },
-    ) as Arc<dyn Fn(String, Value) -> PermissionResult + Send + Sync>;
+    let permission_callback: CanUseToolCallback =
+        Arc::new(|tool_name: String, tool_input: Value, _context: ToolPermissionContext| {
+            Box::pin(async move { decide(&tool_name, &tool_input) })
+        });
 
     let options = ClaudeAgentOptions {
         can_use_tool: Some(permission_callback),
@@ -103,7 +93,7 @@ async fn main() {
             let prompt = "List files in the current directory using bash";
             println!("Prompt: {}\n", prompt);
 
-            if let Err(e) = client.query(prompt).await {
+            if let Err(e) = client.query(prompt.to_string()).await {
                 eprintln!("Query error: {}", e);
                 return;
             }
@@ -113,13 +103,15 @@ async fn main() {
                 while let Some(result) = stream.next().await {
                     match result {
                         Ok(message) => match message {
-                            Message::Assistant(msg) => {
-                                if !msg.content.is_empty() {
-                                    println!("\nAssistant: {}", msg.content);
+                            Message::Assistant { message, .. } => {
+                                for block in message.message.content {
+                                    if let ContentBlock::Text { text } = block {
+                                        println!("\nAssistant: {}", text);
+                                    }
                                 }
                             }
-                            Message::Result(msg) => {
-                                println!("\n✓ Conversation ended: {:?}", msg.result_type);
+                            Message::Result { subtype, .. } => {
+                                println!("\n✓ Conversation ended: {}", subtype);
                                 break;
                             }
                             _ => {}
@@ -136,7 +128,7 @@ async fn main() {
             let prompt2 = "Find all .rs files in the project";
             println!("Prompt: {}\n", prompt2);
 
-            if let Err(e) = client.query(prompt2).await {
+            if let Err(e) = client.query(prompt2.to_string()).await {
                 eprintln!("Query error: {}", e);
                 return;
             }
@@ -146,13 +138,15 @@ async fn main() {
                 while let Some(result) = stream.next().await {
                     match result {
                         Ok(message) => match message {
-                            Message::Assistant(msg) => {
-                                if !msg.content.is_empty() {
-                                    println!("\nAssistant: {}", msg.content);
+                            Message::Assistant { message, .. } => {
+                                for block in message.message.content {
+                                    if let ContentBlock::Text { text } = block {
+                                        println!("\nAssistant: {}", text);
+                                    }
                                 }
                             }
-                            Message::Result(msg) => {
-                                println!("\n✓ Conversation ended: {:?}", msg.result_type);
+                            Message::Result { subtype, .. } => {
+                                println!("\n✓ Conversation ended: {}", subtype);
                                 break;
                             }
                             _ => {}
@@ -0,0 +1,322 @@
+//! Building the in-progress assistant message from partial stream events.
+//!
+//! With `include_partial_messages`, the CLI forwards the API's streaming events
+//! ahead of each complete assistant message. `MessageAccumulator` folds them
+//! into content blocks that can be rendered while the message is generated.
+
+use crate::types::{ContentBlock, ContentBlockDelta, Message, StreamEvent, Usage};
+use serde_json::Value;
+
+/// A content block of the message being streamed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PartialContentBlock {
+    Text { text: String },
+    Thinking { thinking: String, signature: Option<String> },
+    /// A tool call whose input JSON may still be incomplete.
+    ToolUse { id: String, name: String, input_json: String },
+    /// A server tool call, such as web search, whose input may still be incomplete.
+    ServerToolUse { id: String, name: String, input_json: String },
+    /// Any other block, kept as it was started.
+    Other(ContentBlock),
+}
+
+impl PartialContentBlock {
+    /// The tool input parsed so far, for `ToolUse` and `ServerToolUse` blocks.
+    ///
+    /// Incomplete JSON is closed off, so `{"path": "/tm` reads as
+    /// `{"path": "/tm"}`. Returns `None` until a value can be recovered.
+    pub fn partial_input(&self) -> Option<Value> {
+        match self {
+            Self::ToolUse { input_json, .. } | Self::ServerToolUse { input_json, .. } if input_json.trim().is_empty() => {
+                Some(Value::Object(Default::default()))
+            }
+            Self::ToolUse { input_json, .. } | Self::ServerToolUse { input_json, .. } => parse_partial_json(input_json),
+            _ => None,
+        }
+    }
+
+    /// Snapshot this block as a regular content block.
+    pub fn to_content_block(&self) -> ContentBlock {
+        match self {
+            Self::Text { text } => ContentBlock::Text { text: text.clone() },
+            Self::Thinking { thinking, signature } => ContentBlock::Thinking {
+                thinking: thinking.clone(),
+                signature: signature.clone(),
+            },
+            Self::ToolUse { id, name, .. } => ContentBlock::ToolUse {
+                id: id.clone(),
+                name: name.clone(),
+                input: self.partial_input().unwrap_or_else(|| Value::Object(Default::default())),
+            },
+            Self::ServerToolUse { id, name, .. } => ContentBlock::ServerToolUse {
+                id: id.clone(),
+                name: name.clone(),
+                input: self.partial_input().unwrap_or_else(|| Value::Object(Default::default())),
+            },
+            Self::Other(block) => block.clone(),
+        }
+    }
+}
+
+impl From<ContentBlock> for PartialContentBlock {
+    fn from(block: ContentBlock) -> Self {
+        match block {
+            ContentBlock::Text { text } => Self::Text { text },
+            ContentBlock::Thinking { thinking, signature } => Self::Thinking { thinking, signature },
+            // The start event carries an empty input; the real one arrives as deltas
+            ContentBlock::ToolUse { id, name, .. } => Self::ToolUse {
+                id,
+                name,
+                input_json: String::new(),
+            },
+            ContentBlock::ServerToolUse { id, name, .. } => Self::ServerToolUse {
+                id,
+                name,
+                input_json: String::new(),
+            },
+            other => Self::Other(other),
+        }
+    }
+}
+
+/// Accumulates stream events into the assistant message being generated.
+///
+/// Each `message_start` begins a new message, so one accumulator can follow a
+/// whole turn:
+///
+/// ```no_run
+/// # use claude_agent_sdk::{MessageAccumulator, Message};
+/// # fn render(messages: Vec<Message>) {
+/// let mut acc = MessageAccumulator::new();
+/// for message in &messages {
+///     if acc.push_message(message) {
+///         println!("{}", acc.text());
+///     }
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MessageAccumulator {
+    id: Option<String>,
+    model: Option<String>,
+    blocks: Vec<PartialContentBlock>,
+    usage: Usage,
+    stop_reason: Option<String>,
+    complete: bool,
+}
+
+impl MessageAccumulator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Apply a `StreamEvent` message from the main conversation.
+    ///
+    /// Other messages and events from subagents (those with a
+    /// `parent_tool_use_id`) are ignored. Returns whether the event was applied.
+    pub fn push_message(&mut self, message: &Message) -> bool {
+        match message {
+            Message::StreamEvent {
+                event,
+                parent_tool_use_id: None,
+                ..
+            } => {
+                self.push(event);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Apply one stream event.
+    pub fn push(&mut self, event: &StreamEvent) {
+        match event {
+            StreamEvent::MessageStart { message } => {
+                *self = Self {
+                    id: message.id.clone(),
+                    model: message.model.clone(),
+                    usage: message.usage.clone(),
+                    ..Default::default()
+                };
+            }
+            StreamEvent::ContentBlockStart { index, content_block } => {
+                let block = PartialContentBlock::from(content_block.clone());
+                match self.blocks.get_mut(*index) {
+                    Some(existing) => *existing = block,
+                    None => self.blocks.push(block),
+                }
+            }
+            StreamEvent::ContentBlockDelta { index, delta } => {
+                let Some(block) = self.blocks.get_mut(*index) else {
+                    return;
+                };
+                match (block, delta) {
+                    (PartialContentBlock::Text { text }, ContentBlockDelta::TextDelta { text: delta }) => {
+                        text.push_str(delta)
+                    }
+                    (PartialContentBlock::Thinking { thinking, .. }, ContentBlockDelta::ThinkingDelta { thinking: delta }) => {
+                        thinking.push_str(delta)
+                    }
+                    (PartialContentBlock::Thinking { signature, .. }, ContentBlockDelta::SignatureDelta { signature: delta }) => {
+                        signature.get_or_insert_with(String::new).push_str(delta)
+                    }
+                    (
+                        PartialContentBlock::ToolUse { input_json, .. } | PartialContentBlock::ServerToolUse { input_json, .. },
+                        ContentBlockDelta::InputJsonDelta { partial_json },
+                    ) => input_json.push_str(partial_json),
+                    _ => {}
+                }
+            }
+            StreamEvent::MessageDelta { delta, usage } => {
+                if delta.stop_reason.is_some() {
+                    self.stop_reason = delta.stop_reason.clone();
+                }
+                if let Some(usage) = usage {
+                    self.usage.output_tokens = Some(usage.output_tokens);
+                }
+            }
+            StreamEvent::MessageStop => self.complete = true,
+            StreamEvent::ContentBlockStop { .. } | StreamEvent::Unknown(_) => {}
+        }
+    }
+
+    /// The blocks received so far, in order.
+    pub fn blocks(&self) -> &[PartialContentBlock] {
+        &self.blocks
+    }
+
+    /// Snapshot the blocks received so far as regular content blocks.
+    pub fn content(&self) -> Vec<ContentBlock> {
+        self.blocks.iter().map(PartialContentBlock::to_content_block).collect()
+    }
+
+    /// Concatenated text of the text blocks received so far.
+    pub fn text(&self) -> String {
+        self.blocks
+            .iter()
+            .filter_map(|block| match block {
+                PartialContentBlock::Text { text } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn message_id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    pub fn model(&self) -> Option<&str> {
+        self.model.as_deref()
+    }
+
+    /// Usage reported so far; output tokens grow with each `message_delta`.
+    pub fn usage(&self) -> &Usage {
+        &self.usage
+    }
+
+    pub fn stop_reason(&self) -> Option<&str> {
+        self.stop_reason.as_deref()
+    }
+
+    /// Whether `message_stop` has been received for the current message.
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+}
+
+/// Parse a prefix of a JSON document, closing any open strings, arrays and
+/// objects.
+///
+/// A trailing member or element that cannot be completed (such as a key with
+/// no value yet, or a half-written `true`) is dropped. Returns `None` if no
+/// value can be recovered.
+pub fn parse_partial_json(input: &str) -> Option<Value> {
+    let mut end = input.len();
+    loop {
+        let scan = scan_json_prefix(&input[..end]);
+        // A string ending in a lone backslash is closed without it
+        let prefix = &input[..end - usize::from(scan.dangling_escape)];
+        if let Ok(value) = serde_json::from_str(&format!("{}{}", prefix, scan.closers)) {
+            return Some(value);
+        }
+        // Retry without the last, incomplete member or element
+        end = scan.cut_points.into_iter().rev().find(|&cut| cut < end)?;
+    }
+}
+
+struct PrefixScan {
+    /// Text that closes every open string, array and object.
+    closers: String,
+    /// Offsets the prefix can be cut back to while staying well-formed: just
+    /// before each separating comma and just after each opening bracket.
+    cut_points: Vec<usize>,
+    dangling_escape: bool,
+}
+
+fn scan_json_prefix(prefix: &str) -> PrefixScan {
+    let mut stack = Vec::new();
+    let mut cut_points = Vec::new();
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for (i, c) in prefix.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '{' => {
+                stack.push('}');
+                cut_points.push(i + 1);
+            }
+            '[' => {
+                stack.push(']');
+                cut_points.push(i + 1);
+            }
+            '}' | ']' => {
+                stack.pop();
+            }
+            ',' => cut_points.push(i),
+            _ => {}
+        }
+    }
+
+    let mut closers = String::new();
+    if in_string {
+        closers.push('"');
+    }
+    closers.extend(stack.iter().rev());
+    PrefixScan {
+        closers,
+        cut_points,
+        dangling_escape: in_string && escaped,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_partial_json_closes_open_values() {
+        assert_eq!(parse_partial_json(r#"{"path": "/tm"#), Some(json!({"path": "/tm"})));
+        assert_eq!(parse_partial_json(r#"{"a": [1, 2"#), Some(json!({"a": [1, 2]})));
+        assert_eq!(parse_partial_json(r#"{"a": {"b": 1}, "#), Some(json!({"a": {"b": 1}})));
+    }
+
+    #[test]
+    fn test_partial_json_drops_incomplete_members() {
+        assert_eq!(parse_partial_json(r#"{"a": 1, "b"#), Some(json!({"a": 1})));
+        assert_eq!(parse_partial_json(r#"{"a": 1, "b": tr"#), Some(json!({"a": 1})));
+        assert_eq!(parse_partial_json(r#"{"a": "x\"#), Some(json!({"a": "x"})));
+        assert_eq!(parse_partial_json(r#"{"#), Some(json!({})));
+        assert_eq!(parse_partial_json("tr"), None);
+    }
+}
@@ -0,0 +1,204 @@
+//! Subagents defined in markdown files.
+//!
+//! Claude Code reads filesystem agents from `.claude/agents/*.md`: a YAML
+//! frontmatter block with `name`, `description` and optional `tools` and
+//! `model`, followed by the agent's system prompt. These functions read such
+//! files into `AgentDefinition`s for `ClaudeAgentOptions::agents`, and write
+//! definitions back out in the same format.
+
+use crate::errors::{ClaudeSDKError, Result};
+use crate::frontmatter;
+use crate::tools::{is_known_tool, is_valid_tool_name};
+use crate::types::AgentDefinition;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Model aliases an agent's `model` can name, besides full `claude-*` model IDs.
+pub const AGENT_MODEL_ALIASES: &[&str] = &["sonnet", "opus", "haiku", "inherit"];
+
+#[derive(Deserialize)]
+struct AgentFrontmatter {
+    name: String,
+    description: String,
+    #[serde(default, deserialize_with = "frontmatter::tool_list")]
+    tools: Option<Vec<String>>,
+    #[serde(default)]
+    model: Option<String>,
+}
+
+impl AgentDefinition {
+    /// Read an agent file, returning the agent's name and definition.
+    ///
+    /// Fails with `InvalidConfig` naming the file when the frontmatter is
+    /// missing or malformed, or `problems()` reports anything. Tools this SDK
+    /// does not know are logged as warnings but still loaded.
+    pub fn from_markdown(path: impl AsRef<Path>) -> Result<(String, Self)> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)?;
+        Self::parse_markdown(&text, path)
+    }
+
+    /// Parse the contents of an agent file; `path` is only used in errors.
+    pub fn parse_markdown(text: &str, path: &Path) -> Result<(String, Self)> {
+        let (header, body) = frontmatter::split(text).ok_or_else(|| {
+            ClaudeSDKError::invalid_config(format!("{}: missing --- frontmatter block", path.display()))
+        })?;
+        let header: AgentFrontmatter = frontmatter::parse(header, path)?;
+        let definition = AgentDefinition {
+            description: header.description,
+            prompt: body.trim().to_string(),
+            tools: header.tools,
+            model: header.model,
+        };
+
+        let problems = definition.problems(&header.name);
+        if !problems.is_empty() {
+            return Err(ClaudeSDKError::invalid_config(format!("{}: {}", path.display(), problems.join("; "))));
+        }
+        for tool in definition.unknown_tools() {
+            tracing::warn!("{}: unknown tool '{}', passing it to the CLI as is", path.display(), tool);
+        }
+        Ok((header.name, definition))
+    }
+
+    /// Every problem with this definition under `name`: a name other than
+    /// lowercase letters, digits and hyphens, an empty description or prompt,
+    /// malformed tool names and unknown models.
+    ///
+    /// Well-formed tool names are not checked against `BUILTIN_TOOLS`, which
+    /// can lag the CLI; see `unknown_tools()`.
+    pub fn problems(&self, name: &str) -> Vec<String> {
+        let mut problems = Vec::new();
+        let valid_name = !name.is_empty()
+            && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+        if !valid_name {
+            problems.push(format!("agent name '{}' must be lowercase letters, digits and hyphens", name));
+        }
+        if self.description.trim().is_empty() {
+            problems.push("description is empty".to_string());
+        }
+        if self.prompt.trim().is_empty() {
+            problems.push("prompt is empty".to_string());
+        }
+        for tool in self.tools.iter().flatten() {
+            if !is_valid_tool_name(tool) {
+                problems.push(format!("invalid tool name '{}'", tool));
+            }
+        }
+        if let Some(ref model) = self.model {
+            if !AGENT_MODEL_ALIASES.contains(&model.as_str()) && !model.starts_with("claude-") {
+                problems.push(format!(
+                    "unknown model '{}', expected one of {} or a claude-* model ID",
+                    model,
+                    AGENT_MODEL_ALIASES.join(", ")
+                ));
+            }
+        }
+        problems
+    }
+
+    /// Well-formed tools that are neither in `BUILTIN_TOOLS` nor MCP tools.
+    /// These may be tools of a newer CLI, or typos.
+    pub fn unknown_tools(&self) -> Vec<&str> {
+        self.tools
+            .iter()
+            .flatten()
+            .map(String::as_str)
+            .filter(|tool| is_valid_tool_name(tool) && !is_known_tool(tool))
+            .collect()
+    }
+
+    /// Render this definition as an agent file for `name`.
+    pub fn to_markdown(&self, name: &str) -> String {
+        let mut out = String::from("---\n");
+        frontmatter::push_field(&mut out, "name", name);
+        frontmatter::push_field(&mut out, "description", &self.description);
+        if let Some(ref tools) = self.tools {
+            frontmatter::push_field(&mut out, "tools", &tools.join(", "));
+        }
+        if let Some(ref model) = self.model {
+            frontmatter::push_field(&mut out, "model", model);
+        }
+        out.push_str("---\n\n");
+        out.push_str(self.prompt.trim());
+        out.push('\n');
+        out
+    }
+
+    /// Write this definition to `<dir>/<name>.md`, creating `dir` if needed,
+    /// and return the file's path.
+    pub fn write_markdown(&self, name: &str, dir: impl AsRef<Path>) -> Result<PathBuf> {
+        let problems = self.problems(name);
+        if !problems.is_empty() {
+            return Err(ClaudeSDKError::invalid_config(format!("agent '{}': {}", name, problems.join("; "))));
+        }
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(format!("{}.md", name));
+        std::fs::write(&path, self.to_markdown(name))?;
+        Ok(path)
+    }
+}
+
+/// Load every `*.md` agent file directly in `dir`, keyed by agent name.
+///
+/// A missing directory yields no agents. Every invalid file and every name
+/// defined by more than one file is reported in a single `InvalidConfig`.
+pub fn load_agents_dir(dir: impl AsRef<Path>) -> Result<HashMap<String, AgentDefinition>> {
+    let dir = dir.as_ref();
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(HashMap::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut paths = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path.is_file() && path.extension().is_some_and(|ext| ext == "md") {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+
+    let mut agents = HashMap::new();
+    let mut sources: HashMap<String, PathBuf> = HashMap::new();
+    let mut problems = Vec::new();
+    for path in paths {
+        match AgentDefinition::from_markdown(&path) {
+            Ok((name, definition)) => {
+                if let Some(first) = sources.get(&name) {
+                    problems.push(format!(
+                        "agent '{}' is defined in both {} and {}",
+                        name,
+                        first.display(),
+                        path.display()
+                    ));
+                    continue;
+                }
+                sources.insert(name.clone(), path);
+                agents.insert(name, definition);
+            }
+            Err(ClaudeSDKError::InvalidConfig(message)) => problems.push(message),
+            Err(e) => return Err(e),
+        }
+    }
+
+    if !problems.is_empty() {
+        return Err(ClaudeSDKError::invalid_config(format!(
+            "{} problem{} in {}: {}",
+            problems.len(),
+            if problems.len() == 1 { "" } else { "s" },
+            dir.display(),
+            problems.join("; ")
+        )));
+    }
+    Ok(agents)
+}
+
+/// Write each agent to `<dir>/<name>.md`, returning the paths in name order.
+pub fn write_agents_dir(agents: &HashMap<String, AgentDefinition>, dir: impl AsRef<Path>) -> Result<Vec<PathBuf>> {
+    let mut names: Vec<&String> = agents.keys().collect();
+    names.sort();
+    names.into_iter().map(|name| agents[name].write_markdown(name, dir.as_ref())).collect()
+}
@@ -0,0 +1,221 @@
+//! Offline batch runs: JSONL jobs in, one JSONL result per job out.
+//!
+//! This backs the `claude-batch` binary. Jobs run through a `QueryPool`, and
+//! each result is appended to the output file as soon as its job finishes, so
+//! an interrupted run can be resumed by pointing it at the same output file.
+
+use crate::errors::{ClaudeSDKError, Result};
+use crate::pool::{JobId, JobOverrides, PoolJob, QueryPool};
+use crate::types::{ClaudeAgentOptions, ModelUsage, Usage};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// One line of the jobs file.
+///
+/// `id` defaults to the 1-based line number. Option overrides such as `cwd`
+/// and `model` sit alongside the prompt. Unknown keys are an error, so a
+/// misspelled override does not silently run with the default.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "JobLine")]
+pub struct BatchJob {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub prompt: String,
+    #[serde(default)]
+    pub priority: i32,
+    #[serde(flatten)]
+    pub overrides: JobOverrides,
+}
+
+/// `BatchJob` as read, with the keys neither it nor `JobOverrides` knows.
+/// `deny_unknown_fields` does not work with `flatten`.
+#[derive(Deserialize)]
+struct JobLine {
+    #[serde(default)]
+    id: Option<String>,
+    prompt: String,
+    #[serde(default)]
+    priority: i32,
+    #[serde(flatten)]
+    overrides: JobOverrides,
+    #[serde(flatten)]
+    unknown: serde_json::Map<String, serde_json::Value>,
+}
+
+impl TryFrom<JobLine> for BatchJob {
+    type Error = String;
+
+    fn try_from(line: JobLine) -> std::result::Result<Self, String> {
+        if !line.unknown.is_empty() {
+            let keys: Vec<String> = line.unknown.keys().map(|key| format!("`{}`", key)).collect();
+            return Err(format!("unknown field{} {}", if keys.len() == 1 { "" } else { "s" }, keys.join(", ")));
+        }
+        Ok(BatchJob {
+            id: line.id,
+            prompt: line.prompt,
+            priority: line.priority,
+            overrides: line.overrides,
+        })
+    }
+}
+
+/// One line of the results file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BatchResult {
+    pub id: String,
+    pub text: Option<String>,
+    pub total_cost_usd: Option<f64>,
+    pub usage: Option<Usage>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model_usage: Option<HashMap<String, ModelUsage>>,
+    pub duration_ms: Option<i64>,
+    pub session_id: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Settings for a batch run.
+#[derive(Debug, Clone)]
+pub struct BatchConfig {
+    pub input: PathBuf,
+    pub output: PathBuf,
+    pub concurrency: usize,
+    /// Re-run jobs whose recorded result has an error.
+    pub retry_errors: bool,
+    pub options: ClaudeAgentOptions,
+}
+
+/// Counts reported at the end of a batch run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BatchSummary {
+    pub succeeded: usize,
+    pub failed: usize,
+    pub skipped: usize,
+}
+
+/// Read a jobs file, assigning line-number IDs where missing.
+pub fn read_jobs(path: &Path) -> Result<Vec<(String, BatchJob)>> {
+    let content = std::fs::read_to_string(path)?;
+    let mut seen = HashSet::new();
+    let mut jobs = Vec::new();
+
+    for (index, line) in content.lines().enumerate() {
+        let line_no = index + 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let job: BatchJob = serde_json::from_str(line)
+            .map_err(|e| ClaudeSDKError::invalid_config(format!("{}:{}: {}", path.display(), line_no, e)))?;
+        let id = job.id.clone().unwrap_or_else(|| line_no.to_string());
+        if !seen.insert(id.clone()) {
+            return Err(ClaudeSDKError::invalid_config(format!(
+                "{}:{}: duplicate job id {:?}",
+                path.display(),
+                line_no,
+                id
+            )));
+        }
+        jobs.push((id, job));
+    }
+
+    Ok(jobs)
+}
+
+/// Read the results already recorded in an output file, keyed by job ID.
+///
+/// A missing file has no results. A truncated last line, as left by a run
+/// that was killed mid-write, is ignored.
+pub fn read_results(path: &Path) -> Result<HashMap<String, BatchResult>> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(HashMap::new()),
+        Err(e) => return Err(e.into()),
+    };
+
+    Ok(content
+        .lines()
+        .filter_map(|line| serde_json::from_str::<BatchResult>(line).ok())
+        .map(|result| (result.id.clone(), result))
+        .collect())
+}
+
+/// Run every job not yet recorded in the output file.
+pub async fn run_batch(config: BatchConfig) -> Result<BatchSummary> {
+    let jobs = read_jobs(&config.input)?;
+    let recorded = read_results(&config.output)?;
+
+    let total = jobs.len();
+    let pending: Vec<_> = jobs
+        .into_iter()
+        .filter(|(id, _)| match recorded.get(id) {
+            Some(result) => config.retry_errors && result.error.is_some(),
+            None => true,
+        })
+        .collect();
+    let mut summary = BatchSummary {
+        skipped: total - pending.len(),
+        ..Default::default()
+    };
+
+    // Start on a fresh line if a previous run was killed mid-write
+    let needs_newline = std::fs::read(&config.output)
+        .map(|bytes| bytes.last().is_some_and(|b| *b != b'\n'))
+        .unwrap_or(false);
+    let mut output = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&config.output)?;
+    if needs_newline {
+        writeln!(output)?;
+    }
+
+    let pool = QueryPool::new(config.options.clone(), config.concurrency);
+    let mut ids: HashMap<JobId, String> = HashMap::new();
+    for (id, job) in pending {
+        let job_id = pool.submit_job(PoolJob {
+            prompt: job.prompt,
+            priority: job.priority,
+            overrides: job.overrides,
+        })?;
+        ids.insert(job_id, id);
+    }
+    pool.close();
+
+    let mut results = pool.results();
+    while let Some(outcome) = results.next().await {
+        let id = ids.remove(&outcome.job_id).unwrap_or_default();
+        let result = match outcome.result {
+            Ok(turn) => BatchResult {
+                id,
+                text: Some(turn.text),
+                total_cost_usd: turn.total_cost_usd,
+                usage: turn.usage,
+                model_usage: turn.model_usage,
+                duration_ms: Some(turn.duration_ms),
+                session_id: Some(turn.session_id),
+                error: turn.is_error.then(|| turn.result.unwrap_or_else(|| turn.subtype.to_string())),
+            },
+            Err(e) => BatchResult {
+                id,
+                text: None,
+                total_cost_usd: None,
+                usage: None,
+                model_usage: None,
+                duration_ms: None,
+                session_id: None,
+                error: Some(e.to_string()),
+            },
+        };
+        if result.error.is_some() {
+            summary.failed += 1;
+        } else {
+            summary.succeeded += 1;
+        }
+        writeln!(output, "{}", serde_json::to_string(&result)?)?;
+        output.flush()?;
+    }
+
+    Ok(summary)
+}
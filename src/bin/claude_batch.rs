@@ -0,0 +1,108 @@
+//! Run a JSONL file of prompts through Claude Code and record one JSONL result per job.
+//!
+//! Usage: claude-batch <jobs.jsonl> <results.jsonl> [options]
+//!
+//! Jobs already recorded in the results file are skipped, so an interrupted
+//! run can be resumed with the same command.
+
+use claude_agent_sdk::batch::{run_batch, BatchConfig};
+use claude_agent_sdk::ClaudeAgentOptions;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+Usage: claude-batch <jobs.jsonl> <results.jsonl> [options]
+
+Each job line is a JSON object with a \"prompt\" and optional \"id\", \"priority\",
+\"cwd\", \"model\", \"system_prompt\", \"max_turns\", \"permission_mode\",
+\"allowed_tools\" and \"env\". Jobs without an id are identified by line number.
+
+Options:
+  --concurrency <N>      Maximum concurrent CLI processes (default: 4)
+  --model <MODEL>        Default model for jobs that do not set one
+  --max-turns <N>        Default max turns for jobs that do not set one
+  --max-budget-usd <USD> Per-job cost limit
+  --cli-path <PATH>      Path to the Claude Code CLI
+  --retry-errors         Re-run jobs whose recorded result has an error
+  -h, --help             Show this help";
+
+fn parse_args(args: Vec<String>) -> Result<BatchConfig, String> {
+    let mut positional = Vec::new();
+    let mut options = ClaudeAgentOptions::default();
+    let mut concurrency = 4;
+    let mut retry_errors = false;
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or_else(|| format!("{} requires a value", name));
+        match arg.as_str() {
+            "--concurrency" => {
+                concurrency = value("--concurrency")?
+                    .parse()
+                    .map_err(|e| format!("invalid --concurrency: {}", e))?
+            }
+            "--model" => options.model = Some(value("--model")?),
+            "--max-turns" => {
+                options.max_turns = Some(
+                    value("--max-turns")?
+                        .parse()
+                        .map_err(|e| format!("invalid --max-turns: {}", e))?,
+                )
+            }
+            "--max-budget-usd" => {
+                options.max_budget_usd = Some(
+                    value("--max-budget-usd")?
+                        .parse()
+                        .map_err(|e| format!("invalid --max-budget-usd: {}", e))?,
+                )
+            }
+            "--cli-path" => options.cli_path = Some(PathBuf::from(value("--cli-path")?)),
+            "--retry-errors" => retry_errors = true,
+            flag if flag.starts_with('-') => return Err(format!("unknown option {}", flag)),
+            _ => positional.push(PathBuf::from(arg)),
+        }
+    }
+
+    let [input, output]: [PathBuf; 2] = positional
+        .try_into()
+        .map_err(|_| "expected a jobs file and a results file".to_string())?;
+
+    Ok(BatchConfig {
+        input,
+        output,
+        concurrency,
+        retry_errors,
+        options,
+    })
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|a| a == "-h" || a == "--help") {
+        println!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+
+    let config = match parse_args(args) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("claude-batch: {}\n\n{}", e, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    match run_batch(config).await {
+        Ok(summary) => {
+            eprintln!(
+                "claude-batch: {} succeeded, {} failed, {} skipped",
+                summary.succeeded, summary.failed, summary.skipped
+            );
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("claude-batch: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
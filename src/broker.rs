@@ -0,0 +1,188 @@
+//! Tool permission prompts answered from outside the callback.
+//!
+//! `can_use_tool` must produce a decision inline, which is awkward when the
+//! decision comes from a web UI or a chat bot. `PermissionBroker` turns each
+//! prompt into a `PendingApproval` sent over a channel; whoever receives it
+//! answers through its responder, and the CLI waits until then. Any number of
+//! approvals can be pending at once, and each one falls back to a default
+//! decision when it times out or is dropped unanswered.
+
+use crate::tools::BuiltinToolInput;
+use crate::types::{CanUseToolCallback, PermissionResult, PermissionUpdate};
+use serde_json::Value;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+
+/// A permission prompt waiting for a decision.
+///
+/// Answer it with `allow`, `deny` or `respond`. Dropping it unanswered
+/// applies the broker's default decision.
+#[derive(Debug)]
+pub struct PendingApproval {
+    /// Unique per broker, in the order the prompts arrived.
+    pub id: u64,
+    pub tool_name: String,
+    /// The input parsed for a built-in tool, or `Other` for any other tool.
+    pub input: BuiltinToolInput,
+    pub raw_input: Value,
+    /// Rule changes the CLI suggests offering, such as "always allow".
+    pub suggestions: Vec<PermissionUpdate>,
+    /// The path outside the allowed directories that triggered the prompt.
+    pub blocked_path: Option<String>,
+    pub tool_use_id: Option<String>,
+    responder: oneshot::Sender<PermissionResult>,
+}
+
+impl PendingApproval {
+    /// Answer the prompt. Returns false if it already timed out or the
+    /// session ended.
+    pub fn respond(self, result: PermissionResult) -> bool {
+        self.responder.send(result).is_ok()
+    }
+
+    /// Allow the call with its original input.
+    pub fn allow(self) -> bool {
+        self.respond(PermissionResult::Allow {
+            updated_input: None,
+            updated_permissions: None,
+        })
+    }
+
+    /// Allow the call with `input` in place of the original.
+    pub fn allow_with_input(self, input: Value) -> bool {
+        self.respond(PermissionResult::Allow {
+            updated_input: Some(input),
+            updated_permissions: None,
+        })
+    }
+
+    /// Allow the call and apply the suggested rule changes, so the CLI stops
+    /// asking about similar calls.
+    pub fn allow_with_suggestions(mut self) -> bool {
+        let suggestions = std::mem::take(&mut self.suggestions);
+        self.respond(PermissionResult::Allow {
+            updated_input: None,
+            updated_permissions: Some(suggestions),
+        })
+    }
+
+    /// Deny the call; `message` is shown to the agent.
+    pub fn deny(self, message: impl Into<String>) -> bool {
+        self.respond(PermissionResult::Deny {
+            message: message.into(),
+            interrupt: false,
+        })
+    }
+
+    /// Whether the prompt can still be answered.
+    pub fn is_open(&self) -> bool {
+        !self.responder.is_closed()
+    }
+}
+
+/// Sends tool permission prompts to a channel as `PendingApproval`s.
+///
+/// ```no_run
+/// use claude_agent_sdk::{ClaudeAgentOptions, PermissionBroker};
+/// use std::time::Duration;
+///
+/// # async fn run() {
+/// let (broker, mut approvals) = PermissionBroker::new();
+/// let options = ClaudeAgentOptions {
+///     can_use_tool: Some(broker.timeout(Duration::from_secs(300)).callback()),
+///     ..Default::default()
+/// };
+///
+/// tokio::spawn(async move {
+///     while let Some(approval) = approvals.recv().await {
+///         // Hand the approval to a UI; here everything but Bash is allowed.
+///         if approval.tool_name == "Bash" {
+///             approval.deny("Shell commands are not allowed");
+///         } else {
+///             approval.allow();
+///         }
+///     }
+/// });
+/// # let _ = options;
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct PermissionBroker {
+    sender: mpsc::UnboundedSender<PendingApproval>,
+    timeout: Option<Duration>,
+    default_decision: PermissionResult,
+    next_id: Arc<AtomicU64>,
+}
+
+impl PermissionBroker {
+    /// A broker with no timeout, and the receiver its approvals arrive on.
+    ///
+    /// Approvals dropped unanswered, or sent after the receiver is dropped,
+    /// are denied.
+    pub fn new() -> (Self, mpsc::UnboundedReceiver<PendingApproval>) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let broker = Self {
+            sender,
+            timeout: None,
+            default_decision: PermissionResult::Deny {
+                message: "No permission decision was made".to_string(),
+                interrupt: false,
+            },
+            next_id: Arc::new(AtomicU64::new(1)),
+        };
+        (broker, receiver)
+    }
+
+    /// How long an approval waits for a decision before the default applies.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// The decision used when an approval times out or is dropped unanswered.
+    pub fn default_decision(mut self, decision: PermissionResult) -> Self {
+        self.default_decision = decision;
+        self
+    }
+
+    /// The `can_use_tool` callback that sends prompts to this broker.
+    pub fn callback(&self) -> CanUseToolCallback {
+        let broker = self.clone();
+        Arc::new(move |tool_name, input, context| {
+            let broker = broker.clone();
+            Box::pin(async move {
+                let (responder, response) = oneshot::channel();
+                let approval = PendingApproval {
+                    id: broker.next_id.fetch_add(1, Ordering::Relaxed),
+                    input: BuiltinToolInput::parse(&tool_name, &input),
+                    tool_name,
+                    raw_input: input,
+                    suggestions: context.suggestions,
+                    blocked_path: context.blocked_path,
+                    tool_use_id: context.tool_use_id,
+                    responder,
+                };
+                if broker.sender.send(approval).is_err() {
+                    return broker.default_decision;
+                }
+
+                let decision = match broker.timeout {
+                    Some(timeout) => tokio::time::timeout(timeout, response).await.ok().and_then(Result::ok),
+                    None => response.await.ok(),
+                };
+                decision.unwrap_or(broker.default_decision)
+            })
+        })
+    }
+}
+
+impl std::fmt::Debug for PermissionBroker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PermissionBroker")
+            .field("timeout", &self.timeout)
+            .field("default_decision", &self.default_decision)
+            .finish_non_exhaustive()
+    }
+}
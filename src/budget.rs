@@ -0,0 +1,125 @@
+//! Cost and token budget tracking.
+//!
+//! Every Result message carries the cost of the CLI process so far
+//! (`total_cost_usd`, cumulative) and the token usage of the turn it ends
+//! (`usage`, not cumulative), so costs are replaced and tokens are summed.
+//! Between results, token usage is estimated from partial-message stream events
+//! (`include_partial_messages`), so a runaway turn can be interrupted before it
+//! finishes.
+//!
+//! Cost is not estimated between results, since that would need per-model
+//! prices the SDK does not have. `max_budget_usd` is therefore only enforced
+//! when a Result arrives and cannot stop a turn partway; only
+//! `max_total_tokens` can.
+//!
+//! Token counts come from [`Usage::total_tokens`](crate::types::Usage::total_tokens),
+//! so cache creation and cache read tokens count toward `max_total_tokens`.
+//!
+//! Limits are enforced on the client side, after the CLI has reported the
+//! tokens: by the time a limit is seen to be crossed, the tokens have already
+//! been produced. With `include_partial_messages`, `max_total_tokens` can be
+//! overshot by up to one streamed message; without it, by up to a whole turn.
+
+use crate::types::{ClaudeAgentOptions, Message, StreamEvent};
+
+/// Spend observed so far in a session.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SpendTotals {
+    pub total_cost_usd: f64,
+    pub total_tokens: u64,
+}
+
+/// Tracks spend against `max_budget_usd` and `max_total_tokens`.
+#[derive(Debug, Clone, Default)]
+pub struct BudgetTracker {
+    max_budget_usd: Option<f64>,
+    max_total_tokens: Option<u64>,
+    // Totals from CLI processes that have since been replaced (crash recovery)
+    committed: SpendTotals,
+    // Latest cost reported by the current CLI process, and the sum of the
+    // per-turn token usage in its results
+    process: SpendTotals,
+    // Estimated tokens for the turn in progress
+    turn_completed_tokens: u64,
+    message_input_tokens: u64,
+    message_output_tokens: u64,
+}
+
+impl BudgetTracker {
+    /// Create a tracker with the given limits.
+    pub fn new(max_budget_usd: Option<f64>, max_total_tokens: Option<u64>) -> Self {
+        Self {
+            max_budget_usd,
+            max_total_tokens,
+            ..Default::default()
+        }
+    }
+
+    /// Create a tracker with the limits configured in `options`.
+    pub fn from_options(options: &ClaudeAgentOptions) -> Self {
+        Self::new(options.max_budget_usd, options.max_total_tokens)
+    }
+
+    /// Total spend so far, including the estimate for the turn in progress.
+    pub fn totals(&self) -> SpendTotals {
+        SpendTotals {
+            total_cost_usd: self.committed.total_cost_usd + self.process.total_cost_usd,
+            total_tokens: self.committed.total_tokens
+                + self.process.total_tokens
+                + self.turn_completed_tokens
+                + self.message_input_tokens
+                + self.message_output_tokens,
+        }
+    }
+
+    /// Whether spend has crossed either limit.
+    pub fn is_exceeded(&self) -> bool {
+        let totals = self.totals();
+        self.max_budget_usd.is_some_and(|max| totals.total_cost_usd > max)
+            || self.max_total_tokens.is_some_and(|max| totals.total_tokens > max)
+    }
+
+    /// Update the totals from a message received from the CLI.
+    pub fn record(&mut self, message: &Message) {
+        match message {
+            Message::Result {
+                total_cost_usd, usage, ..
+            } => {
+                if let Some(cost) = total_cost_usd {
+                    self.process.total_cost_usd = *cost;
+                }
+                // Usage covers only this turn; fall back to the stream estimate without it
+                self.process.total_tokens += match usage {
+                    Some(usage) => usage.total_tokens(),
+                    None => self.turn_completed_tokens + self.message_input_tokens + self.message_output_tokens,
+                };
+                self.turn_completed_tokens = 0;
+                self.message_input_tokens = 0;
+                self.message_output_tokens = 0;
+            }
+            Message::StreamEvent { event, .. } => match event {
+                StreamEvent::MessageStart { message } => {
+                    self.turn_completed_tokens += self.message_input_tokens + self.message_output_tokens;
+                    let output_tokens = message.usage.output_tokens.unwrap_or(0);
+                    self.message_input_tokens = message.usage.total_tokens() - output_tokens;
+                    self.message_output_tokens = output_tokens;
+                }
+                StreamEvent::MessageDelta { usage: Some(usage), .. } => {
+                    // Output tokens in message_delta are cumulative for the message
+                    self.message_output_tokens = usage.output_tokens;
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    /// Carry the current totals over when the CLI process is replaced.
+    pub fn start_new_process(&mut self) {
+        self.committed = self.totals();
+        self.process = SpendTotals::default();
+        self.turn_completed_tokens = 0;
+        self.message_input_tokens = 0;
+        self.message_output_tokens = 0;
+    }
+}
@@ -1,43 +1,110 @@
 //! ClaudeSDKClient for bidirectional conversations with Claude Code.
 
-use crate::errors::Result;
-use crate::message_parser::parse_message;
+use crate::budget::{BudgetTracker, SpendTotals};
+use crate::commands::SlashCommand;
+use crate::errors::{ClaudeSDKError, Result};
+use crate::message_parser::parse_message_with;
 use crate::query::Query;
 use crate::transport::subprocess::SubprocessCLITransport;
-use crate::types::{ClaudeAgentOptions, Message};
+use crate::types::{
+    ClaudeAgentOptions, McpServerStatus, Message, PermissionMode, SystemInit, SystemMessage, TurnResult,
+};
 use futures::stream::{Stream, StreamExt};
 use std::pin::Pin;
+use tracing::warn;
 
 /// Client for bidirectional, interactive conversations with Claude Code.
 pub struct ClaudeSDKClient {
     options: ClaudeAgentOptions,
     query: Option<Query>,
+    budget: BudgetTracker,
+    budget_interrupted: bool,
+    server_info: Option<SystemInit>,
 }
 
 impl ClaudeSDKClient {
     /// Create a new ClaudeSDKClient with the given options.
     pub fn new(options: ClaudeAgentOptions) -> Self {
-        Self { options, query: None }
+        let budget = BudgetTracker::from_options(&options);
+        Self {
+            options,
+            query: None,
+            budget,
+            budget_interrupted: false,
+            server_info: None,
+        }
     }
 
     /// Connect to Claude Code and start the session.
     pub async fn connect(&mut self) -> Result<()> {
-        let transport = SubprocessCLITransport::new(self.options.clone(), true)?;
+        let query = Self::start_query(self.options.clone()).await?;
+        self.query = Some(query);
+        Ok(())
+    }
+
+    async fn start_query(options: ClaudeAgentOptions) -> Result<Query> {
+        let transport = SubprocessCLITransport::new(options.clone(), true)?;
         let mut boxed_transport = Box::new(transport) as Box<dyn crate::transport::Transport>;
         boxed_transport.connect().await?;
 
-        let can_use_tool = self.options.can_use_tool.clone();
+        let can_use_tool = options.can_use_tool.clone();
+        let hooks = options.hooks.clone();
 
-        let mut query = Query::new(boxed_transport, true, can_use_tool, None);
+        let mut query = Query::new(boxed_transport, true, can_use_tool, Some(hooks));
         query.start().await?;
         query.initialize().await?;
+        Ok(query)
+    }
 
-        self.query = Some(query);
-        Ok(())
+    /// Respawn the CLI after it exited unexpectedly, resuming the last known session.
+    ///
+    /// Returns the attempt number that succeeded.
+    async fn recover(&mut self) -> Result<u32> {
+        let policy = match self.options.recovery_policy {
+            Some(ref policy) => policy.clone(),
+            None => return Err(ClaudeSDKError::process("Claude Code exited unexpectedly", None, None)),
+        };
+        let session_id = self.session_id();
+
+        let mut options = self.options.clone();
+        options.resume = session_id.clone();
+        options.fork_session = false;
+        options.continue_conversation = false;
+
+        let mut last_error = None;
+        for attempt in 1..=policy.max_retries {
+            tokio::time::sleep(policy.backoff_for(attempt)).await;
+            warn!("Claude Code exited unexpectedly, reconnecting (attempt {})", attempt);
+
+            match Self::start_query(options.clone()).await {
+                Ok(query) => {
+                    query.set_session_id(session_id);
+                    self.query = Some(query);
+                    self.budget.start_new_process();
+                    return Ok(attempt);
+                }
+                Err(e) => last_error = Some(e),
+            }
+        }
+
+        Err(ClaudeSDKError::process(
+            format!(
+                "Claude Code exited unexpectedly and could not be restarted after {} attempts: {}",
+                policy.max_retries,
+                last_error.map(|e| e.to_string()).unwrap_or_default()
+            ),
+            None,
+            None,
+        ))
     }
 
     /// Send a query/prompt to Claude.
+    ///
+    /// Fails with `BudgetExceeded` once the session has spent its budget.
     pub async fn query(&self, prompt: String) -> Result<()> {
+        if self.budget.is_exceeded() {
+            return Err(self.budget_error());
+        }
         if let Some(ref query) = self.query {
             let message = serde_json::json!({
                 "type": "user",
@@ -53,32 +120,162 @@ impl ClaudeSDKClient {
             transport.write(format!("{}\n", serde_json::to_string(&message)?)).await?;
             Ok(())
         } else {
-            Err(crate::errors::ClaudeSDKError::cli_connection(
+            Err(ClaudeSDKError::cli_connection(
                 "Not connected. Call connect() first.",
             ))
         }
     }
 
     /// Receive messages from Claude.
+    ///
+    /// If the CLI exits unexpectedly and a `recovery_policy` is configured, the
+    /// CLI is respawned and a `Message::Reconnected` is yielded; otherwise the
+    /// stream yields an error and ends.
+    ///
+    /// When `max_total_tokens` is crossed mid-turn, the turn is interrupted.
+    /// `max_budget_usd` is only checked against the cost in each Result, so the
+    /// turn that crosses it runs to completion. Either way, the Result is then
+    /// replaced by a `BudgetExceeded` error carrying the final totals, and the
+    /// stream ends.
     pub fn receive_messages(&mut self) -> Pin<Box<dyn Stream<Item = Result<Message>> + Send + '_>> {
-        if let Some(ref mut query) = self.query {
-            Box::pin(query.receive_messages().filter_map(|result| async move {
-                match result {
-                    Ok(value) => Some(parse_message(value)),
-                    Err(e) => Some(Err(e)),
+        Box::pin(futures::stream::unfold((self, false), |(client, done)| async move {
+            if done {
+                return None;
+            }
+            let query = client.query.as_mut()?;
+            match query.next_message().await {
+                Some(Ok(value)) => {
+                    let message = parse_message_with(value, client.options.strict_parsing);
+                    if let Ok(ref msg) = message {
+                        if let Message::System(SystemMessage::Init(init)) = msg {
+                            client.server_info = Some(init.as_ref().clone());
+                        }
+                        client.budget.record(msg);
+                        if client.budget.is_exceeded() {
+                            if matches!(msg, Message::Result { .. }) {
+                                client.budget_interrupted = false;
+                                return Some((Err(client.budget_error()), (client, true)));
+                            }
+                            if !client.budget_interrupted {
+                                client.budget_interrupted = true;
+                                warn!("Budget exceeded, interrupting the current turn");
+                                if let Err(e) = query.interrupt().await {
+                                    warn!("Failed to interrupt after exceeding budget: {}", e);
+                                }
+                            }
+                        }
+                    }
+                    Some((message, (client, false)))
                 }
-            }))
-        } else {
-            Box::pin(futures::stream::empty())
-        }
+                Some(Err(e)) => Some((Err(e), (client, false))),
+                None if query.is_closed() => None,
+                None => match client.recover().await {
+                    Ok(attempt) => {
+                        let session_id = client.session_id();
+                        Some((Ok(Message::Reconnected { session_id, attempt }), (client, false)))
+                    }
+                    Err(e) => Some((Err(e), (client, true))),
+                },
+            }
+        }))
     }
 
-    /// Receive messages until a Result message is received.
+    /// Receive messages until and including a Result message.
+    ///
+    /// The stream also ends after a `Message::Reconnected`, since the turn in
+    /// flight was lost with the crashed CLI.
     pub fn receive_response(&mut self) -> Pin<Box<dyn Stream<Item = Result<Message>> + Send + '_>> {
-        Box::pin(self.receive_messages().take_while(|msg| {
-            let is_result = matches!(msg, Ok(Message::Result { .. }));
-            futures::future::ready(!is_result)
-        }))
+        Box::pin(futures::stream::unfold(
+            (self.receive_messages(), false),
+            |(mut stream, done)| async move {
+                if done {
+                    return None;
+                }
+                let message = stream.next().await?;
+                let is_last = matches!(message, Ok(Message::Result { .. }) | Ok(Message::Reconnected { .. }));
+                Some((message, (stream, is_last)))
+            },
+        ))
+    }
+
+    /// Receive the rest of the current turn and summarize it.
+    pub(crate) async fn receive_turn(&mut self) -> Result<TurnResult> {
+        let mut messages = Vec::new();
+        let mut stream = self.receive_response();
+        while let Some(message) = stream.next().await {
+            messages.push(message?);
+        }
+        drop(stream);
+
+        TurnResult::from_messages(messages)
+            .ok_or_else(|| ClaudeSDKError::process("Claude Code ended the turn without a result", None, None))
+    }
+
+    /// Get the cost and tokens spent so far in this session.
+    pub fn spend(&self) -> SpendTotals {
+        self.budget.totals()
+    }
+
+    fn budget_error(&self) -> ClaudeSDKError {
+        let totals = self.budget.totals();
+        ClaudeSDKError::budget_exceeded(totals.total_cost_usd, totals.total_tokens)
+    }
+
+    /// Get the session details from the CLI's most recent `init` message.
+    ///
+    /// The CLI sends `init` when the first turn starts, so this is `None` until
+    /// the first messages of a response have been received.
+    pub fn server_info(&self) -> Option<&SystemInit> {
+        self.server_info.as_ref()
+    }
+
+    /// Get the slash commands the session can run, from the `init` message.
+    ///
+    /// `None` until the first messages of a response have been received.
+    pub fn slash_commands(&self) -> Option<&[String]> {
+        self.server_info.as_ref().map(|info| info.slash_commands.as_slice())
+    }
+
+    /// Run a slash command as the next prompt.
+    ///
+    /// Once the `init` message has been received, a command it does not list
+    /// fails with `InvalidConfig` without being sent.
+    pub async fn send_slash_command(&self, command: &SlashCommand) -> Result<()> {
+        if let Some(ref info) = self.server_info {
+            if !info.has_slash_command(&command.name) {
+                return Err(ClaudeSDKError::invalid_config(format!(
+                    "unknown slash command /{}; available: {}",
+                    command.name,
+                    info.slash_commands.iter().map(|name| format!("/{}", name)).collect::<Vec<_>>().join(", ")
+                )));
+            }
+        }
+        self.query(command.to_prompt()).await
+    }
+
+    /// Get the ID of the current session, once the CLI has reported one.
+    pub fn session_id(&self) -> Option<String> {
+        self.query.as_ref().and_then(|query| query.session_id())
+    }
+
+    /// Fork the current session into a new, independently running client.
+    ///
+    /// This starts a second CLI process that resumes the current session with
+    /// `--fork-session`, so the fork continues from the same conversation point
+    /// under a new session ID while this client carries on unaffected.
+    pub async fn fork(&self) -> Result<ClaudeSDKClient> {
+        let session_id = self.session_id().ok_or_else(|| {
+            ClaudeSDKError::cli_connection("No session to fork. Receive at least one message first.")
+        })?;
+
+        let mut options = self.options.clone();
+        options.resume = Some(session_id);
+        options.fork_session = true;
+        options.continue_conversation = false;
+
+        let mut forked = ClaudeSDKClient::new(options);
+        forked.connect().await?;
+        Ok(forked)
     }
 
     /// Send an interrupt signal.
@@ -86,23 +283,55 @@ impl ClaudeSDKClient {
         if let Some(ref query) = self.query {
             query.interrupt().await
         } else {
-            Err(crate::errors::ClaudeSDKError::cli_connection(
+            Err(ClaudeSDKError::cli_connection(
                 "Not connected. Call connect() first.",
             ))
         }
     }
 
     /// Change permission mode during conversation.
-    pub async fn set_permission_mode(&self, mode: String) -> Result<()> {
+    pub async fn set_permission_mode(&self, mode: PermissionMode) -> Result<()> {
         if let Some(ref query) = self.query {
             query.set_permission_mode(mode).await
         } else {
-            Err(crate::errors::ClaudeSDKError::cli_connection(
+            Err(ClaudeSDKError::cli_connection(
                 "Not connected. Call connect() first.",
             ))
         }
     }
 
+    /// Switch the model for the following turns; `None` returns to the default.
+    ///
+    /// The new model is also used if the CLI is restarted by the recovery
+    /// policy or the session is forked. Fails with `Unsupported` on CLI
+    /// versions without model switching.
+    pub async fn set_model(&mut self, model: Option<String>) -> Result<()> {
+        self.connected()?.set_model(model.as_deref()).await?;
+        self.options.model = model;
+        Ok(())
+    }
+
+    /// Ask the CLI for the current state of every MCP server.
+    pub async fn mcp_server_status(&self) -> Result<Vec<McpServerStatus>> {
+        self.connected()?.mcp_status().await
+    }
+
+    /// Reconnect an MCP server, e.g. after it failed or needed authentication.
+    pub async fn reconnect_mcp_server(&self, name: &str) -> Result<()> {
+        self.connected()?.mcp_reconnect(name).await
+    }
+
+    /// Enable or disable an MCP server for the rest of the session.
+    pub async fn toggle_mcp_server(&self, name: &str, enabled: bool) -> Result<()> {
+        self.connected()?.mcp_toggle(name, enabled).await
+    }
+
+    fn connected(&self) -> Result<&Query> {
+        self.query
+            .as_ref()
+            .ok_or_else(|| ClaudeSDKError::cli_connection("Not connected. Call connect() first."))
+    }
+
     /// Disconnect from Claude.
     pub async fn disconnect(&self) -> Result<()> {
         if let Some(ref query) = self.query {
@@ -0,0 +1,288 @@
+//! Slash commands: invoking them and defining custom commands.
+//!
+//! The CLI lists the commands a session can run in the `init` message's
+//! `slash_commands`. A `SlashCommand` is sent as a `/name args` prompt through
+//! `ClaudeSDKClient::send_slash_command`. Custom commands are markdown files in
+//! `.claude/commands/`, with optional frontmatter (`description`,
+//! `allowed-tools`, `argument-hint`, `model`) and the prompt as the body.
+
+use crate::errors::{ClaudeSDKError, Result};
+use crate::frontmatter;
+use crate::tools::{is_known_tool, is_valid_tool_name};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// A slash command invocation, such as `/review src/lib.rs`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlashCommand {
+    /// The command name, without the leading `/`.
+    pub name: String,
+    pub args: String,
+}
+
+impl SlashCommand {
+    /// A command with no arguments; a leading `/` in `name` is ignored.
+    pub fn new(name: impl Into<String>) -> Self {
+        let name = name.into();
+        Self {
+            name: name.strip_prefix('/').map(String::from).unwrap_or(name),
+            args: String::new(),
+        }
+    }
+
+    pub fn with_args(mut self, args: impl Into<String>) -> Self {
+        self.args = args.into();
+        self
+    }
+
+    /// Parse a `/name args` prompt, or `None` if it does not start with `/`.
+    pub fn parse(prompt: &str) -> Option<Self> {
+        let rest = prompt.trim_start().strip_prefix('/')?;
+        let (name, args) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+        if name.is_empty() {
+            return None;
+        }
+        Some(Self::new(name).with_args(args.trim()))
+    }
+
+    /// The prompt that runs this command.
+    pub fn to_prompt(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl std::fmt::Display for SlashCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "/{}", self.name)?;
+        if !self.args.is_empty() {
+            write!(f, " {}", self.args)?;
+        }
+        Ok(())
+    }
+}
+
+/// A custom slash command defined in a markdown file.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CommandDefinition {
+    /// The command name: the file name without `.md`.
+    pub name: String,
+    pub description: Option<String>,
+    /// Tools the command may use without asking, e.g. `Bash(git add:*)`.
+    pub allowed_tools: Vec<String>,
+    /// Shown after the command name when completing it, e.g. `[file]`.
+    pub argument_hint: Option<String>,
+    pub model: Option<String>,
+    /// The prompt, with `$ARGUMENTS` or `$1`..`$9` standing for the arguments.
+    pub prompt: String,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, rename_all = "kebab-case")]
+struct CommandFrontmatter {
+    description: Option<String>,
+    #[serde(deserialize_with = "frontmatter::tool_list")]
+    allowed_tools: Option<Vec<String>>,
+    argument_hint: Option<String>,
+    model: Option<String>,
+}
+
+impl CommandDefinition {
+    /// A command running `prompt`, with no frontmatter.
+    pub fn new(name: impl Into<String>, prompt: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            prompt: prompt.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Read a command file; the name comes from the file name.
+    pub fn from_markdown(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let name = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or_default().to_string();
+        let text = std::fs::read_to_string(path)?;
+        Self::parse_markdown(&name, &text, path)
+    }
+
+    /// Parse the contents of a command file; `path` is only used in errors.
+    /// The frontmatter is optional.
+    pub fn parse_markdown(name: &str, text: &str, path: &Path) -> Result<Self> {
+        let (header, body) = match frontmatter::split(text) {
+            Some((header, body)) => (frontmatter::parse::<Option<CommandFrontmatter>>(header, path)?, body),
+            None => (None, text),
+        };
+        let header = header.unwrap_or_default();
+        let command = CommandDefinition {
+            name: name.to_string(),
+            description: header.description,
+            allowed_tools: header.allowed_tools.unwrap_or_default(),
+            argument_hint: header.argument_hint,
+            model: header.model,
+            prompt: body.trim().to_string(),
+        };
+
+        let problems = command.problems();
+        if !problems.is_empty() {
+            return Err(ClaudeSDKError::invalid_config(format!("{}: {}", path.display(), problems.join("; "))));
+        }
+        for rule in command.unknown_tools() {
+            tracing::warn!("{}: unknown tool '{}', passing it to the CLI as is", path.display(), rule);
+        }
+        Ok(command)
+    }
+
+    /// Every problem with this command: a name other than letters, digits,
+    /// `-` and `_`, an empty prompt, and allowed tools whose tool name is
+    /// malformed. As for agents, tools missing from `BUILTIN_TOOLS` are only
+    /// reported by `unknown_tools()`.
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        let valid_name = !self.name.is_empty()
+            && self.name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid_name {
+            problems.push(format!("command name '{}' must be letters, digits, '-' and '_'", self.name));
+        }
+        if self.prompt.trim().is_empty() {
+            problems.push("prompt is empty".to_string());
+        }
+        for rule in &self.allowed_tools {
+            if !is_valid_tool_name(rule_tool(rule)) {
+                problems.push(format!("invalid tool name in '{}'", rule));
+            }
+        }
+        problems
+    }
+
+    /// Allowed-tool rules whose tool is well-formed but neither in
+    /// `BUILTIN_TOOLS` nor an MCP tool.
+    pub fn unknown_tools(&self) -> Vec<&str> {
+        self.allowed_tools
+            .iter()
+            .map(String::as_str)
+            .filter(|rule| is_valid_tool_name(rule_tool(rule)) && !is_known_tool(rule_tool(rule)))
+            .collect()
+    }
+
+    /// The prompt with the arguments filled in, as the CLI expands it:
+    /// `$ARGUMENTS` is the whole argument string and `$1`..`$9` are its
+    /// whitespace-separated words, empty when missing.
+    pub fn render(&self, args: &str) -> String {
+        let words: Vec<&str> = args.split_whitespace().collect();
+        let mut out = String::with_capacity(self.prompt.len() + args.len());
+        let mut rest = self.prompt.as_str();
+        while let Some(i) = rest.find('$') {
+            out.push_str(&rest[..i]);
+            let after = &rest[i + 1..];
+            if let Some(tail) = after.strip_prefix("ARGUMENTS") {
+                out.push_str(args);
+                rest = tail;
+            } else if let Some(digit) = after.chars().next().and_then(|c| c.to_digit(10)).filter(|d| *d > 0) {
+                out.push_str(words.get(digit as usize - 1).copied().unwrap_or_default());
+                rest = &after[1..];
+            } else {
+                out.push('$');
+                rest = after;
+            }
+        }
+        out.push_str(rest);
+        out
+    }
+
+    /// The invocation of this command with `args`.
+    pub fn invocation(&self, args: impl Into<String>) -> SlashCommand {
+        SlashCommand::new(self.name.clone()).with_args(args)
+    }
+
+    /// Render this command as a command file. Frontmatter is only written
+    /// when a field is set.
+    pub fn to_markdown(&self) -> String {
+        let mut header = String::new();
+        if let Some(ref description) = self.description {
+            frontmatter::push_field(&mut header, "description", description);
+        }
+        if !self.allowed_tools.is_empty() {
+            frontmatter::push_field(&mut header, "allowed-tools", &self.allowed_tools.join(", "));
+        }
+        if let Some(ref hint) = self.argument_hint {
+            frontmatter::push_field(&mut header, "argument-hint", hint);
+        }
+        if let Some(ref model) = self.model {
+            frontmatter::push_field(&mut header, "model", model);
+        }
+
+        let mut out = String::new();
+        if !header.is_empty() {
+            out.push_str("---\n");
+            out.push_str(&header);
+            out.push_str("---\n\n");
+        }
+        out.push_str(self.prompt.trim());
+        out.push('\n');
+        out
+    }
+
+    /// Write this command to `<dir>/<name>.md`, creating `dir` if needed, and
+    /// return the file's path.
+    pub fn write_markdown(&self, dir: impl AsRef<Path>) -> Result<PathBuf> {
+        let problems = self.problems();
+        if !problems.is_empty() {
+            return Err(ClaudeSDKError::invalid_config(format!(
+                "command '{}': {}",
+                self.name,
+                problems.join("; ")
+            )));
+        }
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(format!("{}.md", self.name));
+        std::fs::write(&path, self.to_markdown())?;
+        Ok(path)
+    }
+}
+
+/// The tool an allowed-tool rule such as `Bash(git commit:*)` applies to.
+fn rule_tool(rule: &str) -> &str {
+    rule.split_once('(').map_or(rule, |(tool, _)| tool)
+}
+
+/// Load every `*.md` command file directly in `dir`, sorted by name.
+///
+/// A missing directory yields no commands. Every invalid file is reported in
+/// a single `InvalidConfig`.
+pub fn load_commands_dir(dir: impl AsRef<Path>) -> Result<Vec<CommandDefinition>> {
+    let dir = dir.as_ref();
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut paths = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path.is_file() && path.extension().is_some_and(|ext| ext == "md") {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+
+    let mut commands = Vec::new();
+    let mut problems = Vec::new();
+    for path in paths {
+        match CommandDefinition::from_markdown(&path) {
+            Ok(command) => commands.push(command),
+            Err(ClaudeSDKError::InvalidConfig(message)) => problems.push(message),
+            Err(e) => return Err(e),
+        }
+    }
+
+    if !problems.is_empty() {
+        return Err(ClaudeSDKError::invalid_config(format!(
+            "{} problem{} in {}: {}",
+            problems.len(),
+            if problems.len() == 1 { "" } else { "s" },
+            dir.display(),
+            problems.join("; ")
+        )));
+    }
+    Ok(commands)
+}
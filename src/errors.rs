@@ -33,6 +33,10 @@ pub enum ClaudeSDKError {
     #[error("Control protocol error: {0}")]
     ControlProtocol(String),
 
+    /// The CLI does not support a control request, usually because it is too old.
+    #[error("Unsupported by this Claude Code version: {request}: {message}")]
+    Unsupported { request: String, message: String },
+
     #[error("Transport error: {0}")]
     Transport(String),
 
@@ -41,6 +45,12 @@ pub enum ClaudeSDKError {
 
     #[error("Timeout: {0}")]
     Timeout(String),
+
+    #[error("Budget exceeded: ${total_cost_usd:.4} spent, {total_tokens} tokens used")]
+    BudgetExceeded { total_cost_usd: f64, total_tokens: u64 },
+
+    #[error("Cancelled: {0}")]
+    Cancelled(String),
 }
 
 pub type Result<T> = std::result::Result<T, ClaudeSDKError>;
@@ -78,6 +88,14 @@ impl ClaudeSDKError {
         Self::ControlProtocol(msg.into())
     }
 
+    /// Create an error for a control request the CLI does not support.
+    pub fn unsupported(request: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::Unsupported {
+            request: request.into(),
+            message: message.into(),
+        }
+    }
+
     /// Create a transport error.
     pub fn transport(msg: impl Into<String>) -> Self {
         Self::Transport(msg.into())
@@ -92,4 +110,17 @@ impl ClaudeSDKError {
     pub fn timeout(msg: impl Into<String>) -> Self {
         Self::Timeout(msg.into())
     }
+
+    /// Create a budget exceeded error with the final totals.
+    pub fn budget_exceeded(total_cost_usd: f64, total_tokens: u64) -> Self {
+        Self::BudgetExceeded {
+            total_cost_usd,
+            total_tokens,
+        }
+    }
+
+    /// Create a cancelled error.
+    pub fn cancelled(msg: impl Into<String>) -> Self {
+        Self::Cancelled(msg.into())
+    }
 }
@@ -0,0 +1,95 @@
+//! Markdown files with a YAML frontmatter block, as used for Claude Code's
+//! filesystem agents and slash commands.
+
+use crate::errors::{ClaudeSDKError, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer};
+use std::path::Path;
+
+/// Split `text` into its frontmatter and the body after it, or `None` when it
+/// does not start with a `---` line.
+pub(crate) fn split(text: &str) -> Option<(&str, &str)> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let rest = text.strip_prefix("---")?.trim_start_matches([' ', '\t']);
+    let rest = rest.strip_prefix("\r\n").or_else(|| rest.strip_prefix('\n'))?;
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            return Some((&rest[..offset], &rest[offset + line.len()..]));
+        }
+        offset += line.len();
+    }
+    None
+}
+
+/// Parse frontmatter as `T`, with errors naming `path` and the failing key.
+pub(crate) fn parse<T: DeserializeOwned>(frontmatter: &str, path: &Path) -> Result<T> {
+    serde_path_to_error::deserialize(serde_yaml::Deserializer::from_str(frontmatter)).map_err(|e| {
+        ClaudeSDKError::invalid_config(match e.path().to_string().as_str() {
+            "." => format!("{}: {}", path.display(), e.inner()),
+            key => format!("{}: {}: {}", path.display(), key, e.inner()),
+        })
+    })
+}
+
+/// Reads a tool list (an agent's `tools`, a command's `allowed-tools`) as a
+/// YAML list or a comma-separated string.
+pub(crate) fn tool_list<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Option<Vec<String>>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Tools {
+        List(Vec<String>),
+        Text(String),
+    }
+
+    Ok(Option::<Tools>::deserialize(deserializer)?.map(|tools| match tools {
+        Tools::List(tools) => tools,
+        Tools::Text(text) => split_tools(&text),
+    }))
+}
+
+/// Split a comma-separated tool list, keeping commas inside `Tool(...)` rules.
+fn split_tools(text: &str) -> Vec<String> {
+    let mut tools = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in text.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                tools.push(text[start..i].trim().to_string());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    tools.push(text[start..].trim().to_string());
+    tools.retain(|tool| !tool.is_empty());
+    tools
+}
+
+/// Write `key: value`, quoting `value` when YAML would not read it back as
+/// the same string.
+pub(crate) fn push_field(out: &mut String, key: &str, value: &str) {
+    out.push_str(key);
+    out.push_str(": ");
+    if is_plain_scalar(value) {
+        out.push_str(value);
+    } else {
+        // JSON strings are valid double-quoted YAML scalars
+        out.push_str(&serde_json::to_string(value).unwrap());
+    }
+    out.push('\n');
+}
+
+fn is_plain_scalar(value: &str) -> bool {
+    let first = value.chars().next();
+    first.is_some_and(|c| c.is_alphabetic() || c == '/')
+        && value.trim() == value
+        && value.chars().all(|c| c.is_alphanumeric() || " .,;()/_-'<>=+@$".contains(c))
+        && !matches!(
+            value.to_ascii_lowercase().as_str(),
+            "true" | "false" | "yes" | "no" | "on" | "off" | "null" | "y" | "n"
+        )
+}
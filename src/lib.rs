@@ -25,32 +25,66 @@
 //! ## Features
 //!
 //! - **Simple Query API**: One-shot queries with the `query()` function
+//! - **Validated Options**: Build options fluently and catch conflicting settings with `ClaudeAgentOptions::builder()`
 //! - **Interactive Client**: Bidirectional communication with `ClaudeSDKClient`
+//! - **Slash Commands**: Run the CLI's slash commands and manage `.claude/commands` files
+//! - **Query Pool**: Fan out many prompts with bounded concurrency via `QueryPool`
 //! - **Tool Permissions**: Fine-grained control over tool execution
+//! - **Plan Approval**: Review plans before execution with `PlanWorkflow`
+//! - **Permission Broker**: Answer tool permission prompts from a UI over a channel with `PermissionBroker`
 //! - **Hooks**: Intercept and modify behavior at key points
+//! - **Typed Settings**: Pass `Settings` inline and preview merged user/project/local settings
+//! - **Typed Output**: Deserialize structured output into Rust types with `query_typed::<T>()`
+//! - **Partial Messages**: Render text and tool input as it streams with `MessageAccumulator`
 //! - **MCP Support**: Integration with Model Context Protocol servers
 //! - **Type Safety**: Strong typing with serde serialization
 
+pub mod accumulator;
+pub mod agents;
+pub mod batch;
+pub mod broker;
+pub mod budget;
 pub mod client;
+pub mod commands;
 pub mod errors;
+mod frontmatter;
 pub mod mcp;
 pub mod message_parser;
+pub mod options;
+pub mod plan;
+pub mod pool;
 pub mod query;
+pub mod settings;
+pub mod tools;
 pub mod transport;
+pub mod typed;
 pub mod types;
 
 // Re-export main types
+pub use accumulator::{MessageAccumulator, PartialContentBlock};
+pub use agents::{load_agents_dir, write_agents_dir};
+pub use broker::{PendingApproval, PermissionBroker};
+pub use budget::{BudgetTracker, SpendTotals};
 pub use client::ClaudeSDKClient;
+pub use commands::{load_commands_dir, CommandDefinition, SlashCommand};
 pub use errors::{ClaudeSDKError, Result};
 pub use mcp::{create_mcp_server, McpTool, SdkMcpServer, ToolParameter};
+pub use options::ClaudeAgentOptionsBuilder;
+pub use plan::{PlanDecision, PlanRun, PlanWorkflow};
+pub use pool::{JobId, JobOutcome, JobOverrides, PoolJob, QueryPool};
+pub use settings::{PermissionSettings, Settings, SettingsArg, SettingsHook, SettingsHookMatcher};
+pub use tools::{BuiltinToolInput, BuiltinToolOutput, ToolUse};
+pub use transport::subprocess::CliInvocation;
+pub use typed::{query_typed, query_typed_with_retries, TypedResult};
 pub use types::{
-    AgentDefinition, ClaudeAgentOptions, ContentBlock, HookCallback, HookContext, HookJSONOutput, HookMatcher,
-    McpServerConfig, Message, PermissionMode, PermissionResult, PermissionUpdate, SettingSource, SystemPrompt,
-    ToolPermissionContext,
+    AgentDefinition, ClaudeAgentOptions, ContentBlock, HookCallback, HookContext, HookEvent, HookJSONOutput, HookMatcher,
+    McpServerConfig, Message, ModelUsage, PermissionBehavior, PermissionMode, PermissionResult, PermissionUpdate,
+    PermissionUpdateDestination, ResultSubtype, SettingSource, StreamEvent, SystemInit, SystemMessage, SystemPrompt,
+    ToolPermissionContext, TurnResult, Usage,
 };
 
-use futures::stream::{Stream, StreamExt};
-use message_parser::parse_message;
+use futures::stream::Stream;
+use message_parser::parse_message_with;
 use std::pin::Pin;
 use transport::subprocess::SubprocessCLITransport;
 
@@ -106,19 +140,11 @@ pub async fn query(
     boxed_transport.connect().await?;
 
     // For string prompts, write the prompt and close stdin
-    let prompt_msg = serde_json::json!({
-        "type": "user",
-        "message": {
-            "role": "user",
-            "content": prompt
-        }
-    });
-    boxed_transport
-        .write(format!("{}\n", serde_json::to_string(&prompt_msg)?))
-        .await?;
+    boxed_transport.write(format!("{}\n", prompt_line(&prompt))).await?;
     boxed_transport.end_input().await?;
 
     let can_use_tool = options.can_use_tool.clone();
+    let strict = options.strict_parsing;
 
     let mut q = query::Query::new(boxed_transport, false, can_use_tool, None);
     q.start().await?;
@@ -132,7 +158,7 @@ pub async fn query(
         use futures::stream::StreamExt;
         while let Some(result) = stream.next().await {
             let parsed = match result {
-                Ok(value) => parse_message(value),
+                Ok(value) => parse_message_with(value, strict),
                 Err(e) => Err(e),
             };
             if tx.send(parsed).is_err() {
@@ -147,5 +173,39 @@ pub async fn query(
     Ok(Box::pin(stream))
 }
 
+/// Dry run of `query()`: validate the options, find the CLI and return the
+/// invocation `query()` would start, without starting a process.
+///
+/// Environment and header values are redacted as in
+/// `ClaudeAgentOptions::to_invocation()`, and the prompt message appears in
+/// the invocation's `stdin`. The prompt and other arguments are not redacted.
+///
+/// ```no_run
+/// use claude_agent_sdk::{query_dry_run, ClaudeAgentOptions};
+///
+/// let invocation = query_dry_run("Hello, Claude!", &ClaudeAgentOptions::default())?;
+/// println!("{}", invocation);
+/// # Ok::<(), claude_agent_sdk::ClaudeSDKError>(())
+/// ```
+pub fn query_dry_run(prompt: &str, options: &ClaudeAgentOptions) -> Result<CliInvocation> {
+    options.validate()?;
+    let transport = SubprocessCLITransport::new(options.clone(), false)?;
+    let mut invocation = transport.invocation()?.redacted();
+    invocation.stdin.push(prompt_line(prompt));
+    Ok(invocation)
+}
+
+/// The stream-json line that sends a one-shot prompt.
+fn prompt_line(prompt: &str) -> String {
+    serde_json::json!({
+        "type": "user",
+        "message": {
+            "role": "user",
+            "content": prompt
+        }
+    })
+    .to_string()
+}
+
 /// Version of the SDK
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
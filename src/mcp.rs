@@ -19,17 +19,17 @@ pub struct ToolParameter {
     pub description: Option<String>,
 }
 
+/// Async handler invoked when an MCP tool is called
+pub type ToolHandler =
+    Arc<dyn Fn(Value) -> Pin<Box<dyn Future<Output = Result<Value, String>> + Send>> + Send + Sync>;
+
 /// Tool definition for MCP server
 #[derive(Clone)]
 pub struct McpTool {
     pub name: String,
     pub description: String,
     pub parameters: HashMap<String, ToolParameter>,
-    pub handler: Arc<
-        dyn Fn(Value) -> Pin<Box<dyn Future<Output = Result<Value, String>> + Send>>
-            + Send
-            + Sync,
-    >,
+    pub handler: ToolHandler,
 }
 
 impl std::fmt::Debug for McpTool {
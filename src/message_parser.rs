@@ -1,11 +1,27 @@
 //! Message parser for Claude Code SDK responses.
 
 use crate::errors::{ClaudeSDKError, Result};
-use crate::types::{AssistantMessageContent, AssistantMessageInner, ContentBlock, Message, UserMessageContent, UserMessageInner};
-use serde_json::Value;
+use crate::types::{
+    AssistantMessageContent, AssistantMessageInner, ContentBlock, Message, StreamEvent, SystemMessage, UserMessageContent,
+    UserMessageInner,
+};
+use serde_json::{Map, Value};
 
 /// Parse a message from CLI output into a typed Message object.
+///
+/// Message and content block types this SDK does not know are returned as
+/// `Unknown` variants holding the raw JSON.
 pub fn parse_message(data: Value) -> Result<Message> {
+    parse_message_with(data, false)
+}
+
+/// Parse a message, failing on unknown message and content block types.
+pub fn parse_message_strict(data: Value) -> Result<Message> {
+    parse_message_with(data, true)
+}
+
+/// Parse with the strictness configured in `strict_parsing`.
+pub(crate) fn parse_message_with(data: Value, strict: bool) -> Result<Message> {
     let obj = data
         .as_object()
         .ok_or_else(|| ClaudeSDKError::message_parse("Expected message to be an object", Some(data.clone())))?;
@@ -17,14 +33,15 @@ pub fn parse_message(data: Value) -> Result<Message> {
 
     match message_type {
         "user" => parse_user_message(&data),
-        "assistant" => parse_assistant_message(&data),
+        "assistant" => parse_assistant_message(&data, strict),
         "system" => parse_system_message(&data),
         "result" => parse_result_message(&data),
         "stream_event" => parse_stream_event(&data),
-        _ => Err(ClaudeSDKError::message_parse(
+        _ if strict => Err(ClaudeSDKError::message_parse(
             format!("Unknown message type: {}", message_type),
             Some(data),
         )),
+        _ => Ok(Message::Unknown { raw: data }),
     }
 }
 
@@ -33,7 +50,7 @@ fn parse_user_message(data: &Value) -> Result<Message> {
         ClaudeSDKError::message_parse("User message must be an object", Some(data.clone()))
     })?;
 
-    let parent_tool_use_id = obj.get("parent_tool_use_id").and_then(|v| v.as_str()).map(String::from);
+    let parent_tool_use_id = string_field(obj, "parent_tool_use_id");
 
     let message_obj = obj
         .get("message")
@@ -50,18 +67,25 @@ fn parse_user_message(data: &Value) -> Result<Message> {
 
     Ok(Message::User {
         message: UserMessageContent {
-            message: UserMessageInner { role, content },
+            message: UserMessageInner {
+                role,
+                content,
+                extra: extra_fields(message_obj, &["role", "content"]),
+            },
         },
         parent_tool_use_id,
+        session_id: string_field(obj, "session_id"),
+        uuid: string_field(obj, "uuid"),
+        extra: extra_fields(obj, &["type", "message", "parent_tool_use_id", "session_id", "uuid"]),
     })
 }
 
-fn parse_assistant_message(data: &Value) -> Result<Message> {
+fn parse_assistant_message(data: &Value, strict: bool) -> Result<Message> {
     let obj = data.as_object().ok_or_else(|| {
         ClaudeSDKError::message_parse("Assistant message must be an object", Some(data.clone()))
     })?;
 
-    let parent_tool_use_id = obj.get("parent_tool_use_id").and_then(|v| v.as_str()).map(String::from);
+    let parent_tool_use_id = string_field(obj, "parent_tool_use_id");
 
     let message_obj = obj
         .get("message")
@@ -81,18 +105,37 @@ fn parse_assistant_message(data: &Value) -> Result<Message> {
 
     let mut content_blocks = Vec::new();
     for block in content_array {
-        content_blocks.push(parse_content_block(block)?);
+        content_blocks.push(parse_content_block(block, strict)?);
+    }
+
+    let usage = message_obj.get("usage").and_then(|v| serde_json::from_value(v.clone()).ok());
+    let mut known = vec!["content", "model"];
+    if usage.is_some() {
+        known.push("usage");
     }
+    // Explicit nulls stay in `extra`, since the typed fields skip serializing `None`
+    known.extend(["id", "stop_reason", "stop_sequence"].into_iter().filter(|key| string_field(message_obj, key).is_some()));
 
     Ok(Message::Assistant {
         message: AssistantMessageContent {
-            message: AssistantMessageInner { content: content_blocks, model },
+            message: AssistantMessageInner {
+                id: string_field(message_obj, "id"),
+                content: content_blocks,
+                model,
+                stop_reason: string_field(message_obj, "stop_reason"),
+                stop_sequence: string_field(message_obj, "stop_sequence"),
+                usage,
+                extra: extra_fields(message_obj, &known),
+            },
         },
         parent_tool_use_id,
+        session_id: string_field(obj, "session_id"),
+        uuid: string_field(obj, "uuid"),
+        extra: extra_fields(obj, &["type", "message", "parent_tool_use_id", "session_id", "uuid"]),
     })
 }
 
-fn parse_content_block(block: &Value) -> Result<ContentBlock> {
+fn parse_content_block(block: &Value, strict: bool) -> Result<ContentBlock> {
     let obj = block
         .as_object()
         .ok_or_else(|| ClaudeSDKError::message_parse("Content block must be an object", Some(block.clone())))?;
@@ -117,11 +160,7 @@ fn parse_content_block(block: &Value) -> Result<ContentBlock> {
                 .and_then(|v| v.as_str())
                 .ok_or_else(|| ClaudeSDKError::message_parse("Thinking block missing 'thinking' field", Some(block.clone())))?
                 .to_string();
-            let signature = obj
-                .get("signature")
-                .and_then(|v| v.as_str())
-                .ok_or_else(|| ClaudeSDKError::message_parse("Thinking block missing 'signature' field", Some(block.clone())))?
-                .to_string();
+            let signature = obj.get("signature").and_then(|v| v.as_str()).map(String::from);
             Ok(ContentBlock::Thinking { thinking, signature })
         }
         "tool_use" => {
@@ -155,10 +194,16 @@ fn parse_content_block(block: &Value) -> Result<ContentBlock> {
                 is_error,
             })
         }
-        _ => Err(ClaudeSDKError::message_parse(
+        "redacted_thinking" | "server_tool_use" | "web_search_tool_result" | "image" => {
+            serde_json::from_value(block.clone()).map_err(|_| {
+                ClaudeSDKError::message_parse(format!("Malformed {} block", block_type), Some(block.clone()))
+            })
+        }
+        _ if strict => Err(ClaudeSDKError::message_parse(
             format!("Unknown content block type: {}", block_type),
             Some(block.clone()),
         )),
+        _ => Ok(ContentBlock::Unknown { raw: block.clone() }),
     }
 }
 
@@ -173,10 +218,16 @@ fn parse_system_message(data: &Value) -> Result<Message> {
         .ok_or_else(|| ClaudeSDKError::message_parse("System message missing 'subtype' field", Some(data.clone())))?
         .to_string();
 
-    Ok(Message::System {
-        subtype,
-        data: data.clone(),
-    })
+    let fields = Value::Object(extra_fields(obj, &["type", "subtype"]));
+
+    // A known subtype whose shape has changed falls back to the raw data rather than failing
+    let typed = match subtype.as_str() {
+        "init" => serde_json::from_value(fields.clone()).ok().map(SystemMessage::Init),
+        "compact_boundary" => serde_json::from_value(fields.clone()).ok().map(SystemMessage::CompactBoundary),
+        _ => None,
+    };
+
+    Ok(Message::System(typed.unwrap_or(SystemMessage::Other { subtype, data: fields })))
 }
 
 fn parse_result_message(data: &Value) -> Result<Message> {
@@ -188,7 +239,7 @@ fn parse_result_message(data: &Value) -> Result<Message> {
         .get("subtype")
         .and_then(|v| v.as_str())
         .ok_or_else(|| ClaudeSDKError::message_parse("Result message missing 'subtype' field", Some(data.clone())))?
-        .to_string();
+        .into();
 
     let duration_ms = obj
         .get("duration_ms")
@@ -218,8 +269,29 @@ fn parse_result_message(data: &Value) -> Result<Message> {
         .to_string();
 
     let total_cost_usd = obj.get("total_cost_usd").and_then(|v| v.as_f64());
-    let usage = obj.get("usage").cloned();
-    let result = obj.get("result").and_then(|v| v.as_str()).map(String::from);
+    // Usage is informational, so a malformed breakdown does not fail the whole message
+    let usage = obj.get("usage").and_then(|v| serde_json::from_value(v.clone()).ok());
+    let model_usage = obj.get("modelUsage").and_then(|v| serde_json::from_value(v.clone()).ok());
+    let result = string_field(obj, "result");
+    let structured_output = obj.get("structured_output").filter(|v| !v.is_null()).cloned();
+
+    let uuid = string_field(obj, "uuid");
+
+    // Optional fields sent as nulls (or with an unexpected shape) stay in `extra`,
+    // since the typed fields skip serializing `None`
+    let mut known = vec!["type", "subtype", "duration_ms", "duration_api_ms", "is_error", "num_turns", "session_id"];
+    for (key, present) in [
+        ("total_cost_usd", total_cost_usd.is_some()),
+        ("usage", usage.is_some()),
+        ("modelUsage", model_usage.is_some()),
+        ("result", result.is_some()),
+        ("structured_output", structured_output.is_some()),
+        ("uuid", uuid.is_some()),
+    ] {
+        if present {
+            known.push(key);
+        }
+    }
 
     Ok(Message::Result {
         subtype,
@@ -230,7 +302,11 @@ fn parse_result_message(data: &Value) -> Result<Message> {
         session_id,
         total_cost_usd,
         usage,
+        model_usage,
         result,
+        structured_output,
+        uuid,
+        extra: extra_fields(obj, &known),
     })
 }
 
@@ -253,19 +329,33 @@ fn parse_stream_event(data: &Value) -> Result<Message> {
 
     let event = obj
         .get("event")
-        .ok_or_else(|| ClaudeSDKError::message_parse("Stream event missing 'event' field", Some(data.clone())))?
-        .clone();
+        .ok_or_else(|| ClaudeSDKError::message_parse("Stream event missing 'event' field", Some(data.clone())))?;
+    let event = serde_json::from_value(event.clone()).unwrap_or_else(|_| StreamEvent::Unknown(event.clone()));
 
-    let parent_tool_use_id = obj.get("parent_tool_use_id").and_then(|v| v.as_str()).map(String::from);
+    let parent_tool_use_id = string_field(obj, "parent_tool_use_id");
 
     Ok(Message::StreamEvent {
         uuid,
         session_id,
         event,
         parent_tool_use_id,
+        extra: extra_fields(obj, &["type", "uuid", "session_id", "event", "parent_tool_use_id"]),
     })
 }
 
+fn string_field(obj: &Map<String, Value>, key: &str) -> Option<String> {
+    obj.get(key).and_then(|v| v.as_str()).map(String::from)
+}
+
+/// Copy the fields of `obj` not listed in `known`, so fields this SDK does not
+/// model survive serializing the message back out.
+fn extra_fields(obj: &Map<String, Value>, known: &[&str]) -> Map<String, Value> {
+    obj.iter()
+        .filter(|(key, _)| !known.contains(&key.as_str()))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -277,7 +367,7 @@ mod tests {
             "type": "text",
             "text": "Hello, world!"
         });
-        let result = parse_content_block(&block).unwrap();
+        let result = parse_content_block(&block, true).unwrap();
         match result {
             ContentBlock::Text { text } => assert_eq!(text, "Hello, world!"),
             _ => panic!("Expected TextBlock"),
@@ -0,0 +1,588 @@
+//! Fluent construction, validation and loading of `ClaudeAgentOptions`.
+//!
+//! Options built with struct-update syntax are passed to the CLI as they are.
+//! `ClaudeAgentOptions::builder()` checks the combination of settings first and
+//! reports every conflict at once. Options can also be read from TOML or JSON
+//! config files, with `CLAUDE_SDK_*` environment variables layered on top.
+
+use crate::errors::{ClaudeSDKError, Result};
+use crate::settings::{Settings, SettingsArg};
+use crate::transport::subprocess::{CliInvocation, SubprocessCLITransport};
+use crate::types::{
+    AgentDefinition, CanUseToolCallback, ClaudeAgentOptions, HookContext, HookJSONOutput, McpServerConfig,
+    PermissionMode, PermissionResult, RecoveryPolicy, SettingSource, StderrCallback, SystemPrompt,
+    ToolPermissionContext, TypedHookInput, SYSTEM_PROMPT_PRESETS,
+};
+use serde_json::{Map, Value};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+impl ClaudeAgentOptions {
+    /// Start building options with validation.
+    ///
+    /// ```
+    /// use claude_agent_sdk::{ClaudeAgentOptions, PermissionMode};
+    ///
+    /// let options = ClaudeAgentOptions::builder()
+    ///     .allowed_tools(["Read", "Grep"])
+    ///     .permission_mode(PermissionMode::Plan)
+    ///     .max_turns(5)
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(options.max_turns, Some(5));
+    /// ```
+    pub fn builder() -> ClaudeAgentOptionsBuilder {
+        ClaudeAgentOptionsBuilder::default()
+    }
+
+    /// The CLI command a `ClaudeSDKClient` with these options would start,
+    /// without starting it.
+    ///
+    /// Values in `env` and in MCP server headers and env are replaced with
+    /// `"<redacted>"` unless their names are in `NON_SECRET_NAMES`. Fails if the CLI cannot be found and `cli_path` is not set,
+    /// or if the system prompt cannot be resolved.
+    pub fn to_invocation(&self) -> Result<CliInvocation> {
+        Ok(SubprocessCLITransport::new(self.clone(), true)?.invocation()?.redacted())
+    }
+
+    /// The settings a session with these options would see: the
+    /// `setting_sources` files merged in the CLI's order, then `settings`.
+    ///
+    /// Relative paths resolve against `cwd`, or the current directory when it
+    /// is unset. With no `setting_sources`, only `settings` applies.
+    pub fn effective_settings(&self) -> Result<Settings> {
+        let cwd = match self.cwd {
+            Some(ref cwd) => cwd.clone(),
+            None => std::env::current_dir()?,
+        };
+        let mut settings = Settings::load_sources(self.setting_sources.as_deref().unwrap_or_default(), &cwd)?;
+        if let Some(ref arg) = self.settings {
+            settings.merge(arg.load(&cwd)?);
+        }
+        Ok(settings)
+    }
+
+    /// Add the agents defined in `dir`'s markdown files to `agents`.
+    ///
+    /// Fails without changing `agents` if a file is invalid or names an agent
+    /// that is already defined.
+    pub fn load_agents_dir(&mut self, dir: impl AsRef<Path>) -> Result<()> {
+        let loaded = crate::agents::load_agents_dir(dir.as_ref())?;
+        let mut duplicates: Vec<&str> =
+            loaded.keys().filter(|name| self.agents.contains_key(*name)).map(String::as_str).collect();
+        if !duplicates.is_empty() {
+            duplicates.sort_unstable();
+            return Err(ClaudeSDKError::invalid_config(format!(
+                "{}: agents already defined: {}",
+                dir.as_ref().display(),
+                duplicates.join(", ")
+            )));
+        }
+        self.agents.extend(loaded);
+        Ok(())
+    }
+
+    /// Every conflicting or invalid setting, as human-readable descriptions.
+    ///
+    /// Empty when the options can be passed to the CLI.
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        if self.continue_conversation && self.resume.is_some() {
+            problems.push("continue_conversation and resume cannot both be set".to_string());
+        }
+        if self.fork_session && self.resume.is_none() {
+            problems.push("fork_session requires resume".to_string());
+        }
+        if let Some(max_turns) = self.max_turns {
+            if max_turns <= 0 {
+                problems.push(format!("max_turns must be positive, got {}", max_turns));
+            }
+        }
+        if let Some(max_budget_usd) = self.max_budget_usd {
+            if !(max_budget_usd.is_finite() && max_budget_usd > 0.0) {
+                problems.push(format!("max_budget_usd must be positive, got {}", max_budget_usd));
+            }
+        }
+        if self.max_total_tokens == Some(0) {
+            problems.push("max_total_tokens must be positive".to_string());
+        }
+        if self.fallback_model.is_some() && self.fallback_model == self.model {
+            problems.push("fallback_model must differ from model".to_string());
+        }
+        if let Some(ref schema) = self.json_schema {
+            if !schema.is_object() {
+                problems.push("json_schema must be a JSON object".to_string());
+            }
+        }
+        for tool in &self.allowed_tools {
+            if self.disallowed_tools.contains(tool) {
+                problems.push(format!("tool '{}' is both allowed and disallowed", tool));
+            }
+        }
+        if let Some(ref cwd) = self.cwd {
+            if !cwd.is_dir() {
+                problems.push(format!("cwd {} is not an existing directory", cwd.display()));
+            }
+        }
+        for dir in &self.add_dirs {
+            if !dir.is_dir() {
+                problems.push(format!("add_dirs entry {} is not an existing directory", dir.display()));
+            }
+        }
+        match self.system_prompt {
+            Some(SystemPrompt::Preset { ref preset, .. }) if !SYSTEM_PROMPT_PRESETS.contains(&preset.as_str()) => {
+                problems.push(format!("unknown system prompt preset '{}'", preset));
+            }
+            Some(SystemPrompt::File(ref path) | SystemPrompt::AppendFile(ref path)) if !path.is_file() => {
+                problems.push(format!("system prompt file {} does not exist", path.display()));
+            }
+            _ => {}
+        }
+        if let Some(ref arg @ SettingsArg::File(ref path)) = self.settings {
+            let resolved = self.cwd.as_ref().map_or_else(|| path.clone(), |cwd| cwd.join(path));
+            if arg.inline_json().is_none() && !resolved.is_file() {
+                problems.push(format!("settings file {} does not exist", path.display()));
+            }
+        }
+        let mut agents: Vec<_> = self.agents.iter().collect();
+        agents.sort_unstable_by_key(|(name, _)| name.as_str());
+        for (name, definition) in agents {
+            for problem in definition.problems(name) {
+                problems.push(format!("agent '{}': {}", name, problem));
+            }
+        }
+        if self.can_use_tool.is_some() && self.permission_prompt_tool_name.is_some() {
+            problems.push("can_use_tool and permission_prompt_tool_name cannot both be set".to_string());
+        }
+        problems
+    }
+
+    /// Check the options, failing with `InvalidConfig` listing every problem.
+    pub fn validate(&self) -> Result<()> {
+        let problems = self.problems();
+        if problems.is_empty() {
+            return Ok(());
+        }
+        Err(ClaudeSDKError::invalid_config(format!(
+            "{} problem{}: {}",
+            problems.len(),
+            if problems.len() == 1 { "" } else { "s" },
+            problems.join("; ")
+        )))
+    }
+}
+
+/// Builder for `ClaudeAgentOptions`, created by `ClaudeAgentOptions::builder()`.
+///
+/// Setters for list and map options add to what was set before; `build()`
+/// validates the result.
+#[derive(Debug, Clone, Default)]
+pub struct ClaudeAgentOptionsBuilder {
+    options: ClaudeAgentOptions,
+}
+
+impl ClaudeAgentOptionsBuilder {
+    /// Start from existing options instead of the defaults.
+    pub fn from_options(options: ClaudeAgentOptions) -> Self {
+        Self { options }
+    }
+
+    pub fn allowed_tools<I, S>(mut self, tools: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.options.allowed_tools.extend(tools.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn disallowed_tools<I, S>(mut self, tools: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.options.disallowed_tools.extend(tools.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn system_prompt(mut self, prompt: impl Into<String>) -> Self {
+        self.options.system_prompt = Some(SystemPrompt::Text(prompt.into()));
+        self
+    }
+
+    /// Use a preset system prompt, optionally with text appended.
+    pub fn system_prompt_preset(mut self, preset: impl Into<String>, append: Option<String>) -> Self {
+        self.options.system_prompt = Some(SystemPrompt::Preset {
+            preset: preset.into(),
+            append,
+        });
+        self
+    }
+
+    /// Replace the default system prompt with the contents of a file.
+    pub fn system_prompt_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.options.system_prompt = Some(SystemPrompt::File(path.into()));
+        self
+    }
+
+    /// Append the contents of a file to the default system prompt.
+    pub fn append_system_prompt_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.options.system_prompt = Some(SystemPrompt::AppendFile(path.into()));
+        self
+    }
+
+    /// Set a value for a `{name}` placeholder in the system prompt.
+    pub fn system_prompt_var(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.options.system_prompt_vars.insert(name.into(), value.into());
+        self
+    }
+
+    pub fn mcp_server(mut self, name: impl Into<String>, config: McpServerConfig) -> Self {
+        self.options.mcp_servers.insert(name.into(), config);
+        self
+    }
+
+    pub fn permission_mode(mut self, mode: PermissionMode) -> Self {
+        self.options.permission_mode = Some(mode);
+        self
+    }
+
+    pub fn continue_conversation(mut self, continue_conversation: bool) -> Self {
+        self.options.continue_conversation = continue_conversation;
+        self
+    }
+
+    pub fn resume(mut self, session_id: impl Into<String>) -> Self {
+        self.options.resume = Some(session_id.into());
+        self
+    }
+
+    pub fn fork_session(mut self, fork_session: bool) -> Self {
+        self.options.fork_session = fork_session;
+        self
+    }
+
+    pub fn max_turns(mut self, max_turns: i32) -> Self {
+        self.options.max_turns = Some(max_turns);
+        self
+    }
+
+    pub fn model(mut self, model: impl Into<String>) -> Self {
+        self.options.model = Some(model.into());
+        self
+    }
+
+    pub fn permission_prompt_tool_name(mut self, name: impl Into<String>) -> Self {
+        self.options.permission_prompt_tool_name = Some(name.into());
+        self
+    }
+
+    pub fn cwd(mut self, cwd: impl Into<PathBuf>) -> Self {
+        self.options.cwd = Some(cwd.into());
+        self
+    }
+
+    pub fn settings(mut self, settings: impl Into<SettingsArg>) -> Self {
+        self.options.settings = Some(settings.into());
+        self
+    }
+
+    pub fn add_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.options.add_dirs.push(dir.into());
+        self
+    }
+
+    pub fn env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.options.env.insert(key.into(), value.into());
+        self
+    }
+
+    /// Pass an extra `--flag [value]` to the CLI.
+    pub fn extra_arg(mut self, flag: impl Into<String>, value: Option<String>) -> Self {
+        self.options.extra_args.insert(flag.into(), value);
+        self
+    }
+
+    pub fn max_buffer_size(mut self, bytes: usize) -> Self {
+        self.options.max_buffer_size = Some(bytes);
+        self
+    }
+
+    pub fn stderr_callback(mut self, callback: impl Fn(String) + Send + Sync + 'static) -> Self {
+        self.options.stderr_callback = Some(Arc::new(callback) as StderrCallback);
+        self
+    }
+
+    pub fn can_use_tool<F, Fut>(mut self, callback: F) -> Self
+    where
+        F: Fn(String, serde_json::Value, ToolPermissionContext) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = PermissionResult> + Send + 'static,
+    {
+        let callback: CanUseToolCallback =
+            Arc::new(move |tool_name, input, context| Box::pin(callback(tool_name, input, context)));
+        self.options.can_use_tool = Some(callback);
+        self
+    }
+
+    /// Register a typed hook; see `ClaudeAgentOptions::add_hook`.
+    pub fn hook<I, F, Fut>(mut self, matcher: Option<&str>, callback: F) -> Self
+    where
+        I: TypedHookInput,
+        F: Fn(I, Option<String>, HookContext) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = HookJSONOutput> + Send + 'static,
+    {
+        self.options.add_hook(matcher, callback);
+        self
+    }
+
+    pub fn user(mut self, user: impl Into<String>) -> Self {
+        self.options.user = Some(user.into());
+        self
+    }
+
+    pub fn include_partial_messages(mut self, include: bool) -> Self {
+        self.options.include_partial_messages = include;
+        self
+    }
+
+    pub fn agent(mut self, name: impl Into<String>, definition: AgentDefinition) -> Self {
+        self.options.agents.insert(name.into(), definition);
+        self
+    }
+
+    pub fn setting_sources(mut self, sources: Vec<SettingSource>) -> Self {
+        self.options.setting_sources = Some(sources);
+        self
+    }
+
+    pub fn cli_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.options.cli_path = Some(path.into());
+        self
+    }
+
+    pub fn recovery_policy(mut self, policy: RecoveryPolicy) -> Self {
+        self.options.recovery_policy = Some(policy);
+        self
+    }
+
+    pub fn max_budget_usd(mut self, usd: f64) -> Self {
+        self.options.max_budget_usd = Some(usd);
+        self
+    }
+
+    pub fn max_total_tokens(mut self, tokens: u64) -> Self {
+        self.options.max_total_tokens = Some(tokens);
+        self
+    }
+
+    pub fn max_thinking_tokens(mut self, tokens: u32) -> Self {
+        self.options.max_thinking_tokens = Some(tokens);
+        self
+    }
+
+    pub fn fallback_model(mut self, model: impl Into<String>) -> Self {
+        self.options.fallback_model = Some(model.into());
+        self
+    }
+
+    /// Require the final output to match a JSON schema.
+    pub fn json_schema(mut self, schema: serde_json::Value) -> Self {
+        self.options.json_schema = Some(schema);
+        self
+    }
+
+    pub fn strict_parsing(mut self, strict: bool) -> Self {
+        self.options.strict_parsing = strict;
+        self
+    }
+
+    /// Validate and return the options.
+    ///
+    /// Fails with `ClaudeSDKError::InvalidConfig` describing every problem found.
+    pub fn build(self) -> Result<ClaudeAgentOptions> {
+        self.options.validate()?;
+        Ok(self.options)
+    }
+}
+
+/// Prefix of environment variables that override loaded options.
+pub const ENV_PREFIX: &str = "CLAUDE_SDK_";
+
+impl ClaudeAgentOptions {
+    /// Read options from a `.toml` or `.json` file.
+    ///
+    /// Keys match the field names; callbacks and hooks cannot be set from a
+    /// file. Errors name the file and the key path that failed, such as
+    /// `agent.toml: mcp_servers.docs.url: missing field`.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let parse: fn(&str) -> Result<Self> = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => Self::from_toml_str,
+            Some("json") => Self::from_json_str,
+            _ => {
+                return Err(ClaudeSDKError::invalid_config(format!(
+                    "{}: expected a .toml or .json file",
+                    path.display()
+                )))
+            }
+        };
+        let content = std::fs::read_to_string(path)?;
+        parse(&content).map_err(|e| match e {
+            ClaudeSDKError::InvalidConfig(message) => {
+                ClaudeSDKError::invalid_config(format!("{}: {}", path.display(), message))
+            }
+            other => other,
+        })
+    }
+
+    /// Parse options from a TOML document.
+    pub fn from_toml_str(content: &str) -> Result<Self> {
+        serde_path_to_error::deserialize(toml::Deserializer::new(content)).map_err(|e| key_path_error(&e))
+    }
+
+    /// Parse options from a JSON document.
+    pub fn from_json_str(content: &str) -> Result<Self> {
+        let mut deserializer = serde_json::Deserializer::from_str(content);
+        serde_path_to_error::deserialize(&mut deserializer).map_err(|e| key_path_error(&e))
+    }
+
+    /// Load options from an optional file, then apply `CLAUDE_SDK_*` overrides
+    /// from the environment.
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        let mut options = match path {
+            Some(path) => Self::from_file(path)?,
+            None => Self::default(),
+        };
+        options.apply_env()?;
+        Ok(options)
+    }
+
+    /// Apply `CLAUDE_SDK_*` overrides from the process environment.
+    pub fn apply_env(&mut self) -> Result<()> {
+        self.apply_env_vars(std::env::vars())
+    }
+
+    /// Apply `CLAUDE_SDK_*` overrides from the given variables.
+    ///
+    /// The rest of the variable name is the key, and `__` separates nested
+    /// keys, which are used verbatim: `CLAUDE_SDK_MAX_TURNS=5`,
+    /// `CLAUDE_SDK_ENV__DEBUG=1`, `CLAUDE_SDK_RECOVERY_POLICY__max_retries=2`.
+    /// Values are read as JSON where that fits the key, so lists can be given
+    /// as `["Read","Grep"]` or `Read,Grep`. Other variables are ignored.
+    pub fn apply_env_vars<I, K, V>(&mut self, vars: I) -> Result<()>
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        let mut overrides: Vec<(String, String)> = vars
+            .into_iter()
+            .filter_map(|(name, value)| {
+                let name = name.as_ref();
+                let key = name.strip_prefix(ENV_PREFIX)?;
+                (!key.is_empty()).then(|| (name.to_string(), value.as_ref().to_string()))
+            })
+            .collect();
+        overrides.sort();
+
+        for (name, value) in overrides {
+            let mut key_path = name[ENV_PREFIX.len()..].split("__");
+            let field = key_path.next().unwrap_or_default().to_lowercase();
+            let key_path: Vec<&str> = std::iter::once(field.as_str()).chain(key_path).collect();
+
+            let current = serde_json::to_value(&*self)?;
+            let mut first_error = None;
+            let mut applied = None;
+            for candidate in env_value_candidates(&value) {
+                let mut merged = current.clone();
+                set_key_path(&mut merged, &key_path, candidate);
+                match serde_path_to_error::deserialize::<_, ClaudeAgentOptions>(merged) {
+                    Ok(options) => {
+                        applied = Some(options);
+                        break;
+                    }
+                    Err(e) => {
+                        first_error.get_or_insert_with(|| key_path_error(&e));
+                    }
+                }
+            }
+
+            match applied {
+                Some(options) => self.merge_serialized(options),
+                None => {
+                    let message = match first_error {
+                        Some(ClaudeSDKError::InvalidConfig(message)) => message,
+                        _ => "invalid value".to_string(),
+                    };
+                    return Err(ClaudeSDKError::invalid_config(format!("{}: {}", name, message)));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Take every serializable field from `other`, keeping this value's
+    /// callbacks, hooks and SDK MCP server instances.
+    fn merge_serialized(&mut self, mut other: ClaudeAgentOptions) {
+        for (name, config) in other.mcp_servers.iter_mut() {
+            if let (McpServerConfig::SDK { instance, .. }, Some(McpServerConfig::SDK { instance: existing, .. })) =
+                (config, self.mcp_servers.get(name))
+            {
+                instance.clone_from(existing);
+            }
+        }
+        other.stderr_callback = self.stderr_callback.take();
+        other.can_use_tool = self.can_use_tool.take();
+        other.hooks = std::mem::take(&mut self.hooks);
+        *self = other;
+    }
+}
+
+fn key_path_error<E: std::fmt::Display>(error: &serde_path_to_error::Error<E>) -> ClaudeSDKError {
+    let inner = error.inner().to_string();
+    // TOML errors span several lines with a source excerpt; the message is the last one
+    let message = inner.lines().rev().find(|line| !line.trim().is_empty()).unwrap_or_default().trim();
+    match error.path().to_string().as_str() {
+        "." => ClaudeSDKError::invalid_config(message),
+        path => ClaudeSDKError::invalid_config(format!("{}: {}", path, message)),
+    }
+}
+
+/// Interpretations of an environment value, tried in order.
+fn env_value_candidates(value: &str) -> Vec<Value> {
+    let mut candidates = Vec::new();
+    if let Ok(parsed) = serde_json::from_str::<Value>(value) {
+        candidates.push(parsed);
+    }
+    candidates.push(Value::String(value.to_string()));
+    if value.contains(',') {
+        candidates.push(Value::Array(
+            value.split(',').map(|item| Value::String(item.trim().to_string())).collect(),
+        ));
+    }
+    candidates
+}
+
+fn set_key_path(target: &mut Value, key_path: &[&str], value: Value) {
+    let Some((last, parents)) = key_path.split_last() else {
+        return;
+    };
+    let mut current = target;
+    for key in parents {
+        if !current.is_object() {
+            *current = Value::Object(Map::new());
+        }
+        current = current
+            .as_object_mut()
+            .map(|object| object.entry(key.to_string()).or_insert(Value::Null))
+            .expect("just made an object");
+    }
+    if !current.is_object() {
+        *current = Value::Object(Map::new());
+    }
+    if let Some(object) = current.as_object_mut() {
+        object.insert(last.to_string(), value);
+    }
+}
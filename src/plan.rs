@@ -0,0 +1,201 @@
+//! Plan-then-execute sessions.
+//!
+//! In plan mode the agent researches without changing anything, then proposes
+//! a plan by calling the `ExitPlanMode` tool. `PlanWorkflow` answers that
+//! tool's permission prompt with an async approval callback: approving
+//! switches the session to an execution mode and lets the agent carry on in
+//! the same turn, rejecting sends feedback so it revises the plan, and editing
+//! approves a changed plan.
+
+use crate::client::ClaudeSDKClient;
+use crate::errors::Result;
+use crate::types::{
+    CanUseToolCallback, ClaudeAgentOptions, PermissionMode, PermissionResult, PermissionUpdate,
+    PermissionUpdateDestination, TurnResult,
+};
+use futures::future::BoxFuture;
+use std::future::Future;
+use std::sync::{Arc, Mutex as StdMutex};
+
+/// What to do with a proposed plan.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlanDecision {
+    /// Carry out the plan as proposed.
+    Approve,
+    /// Keep planning; `feedback` is sent to the agent.
+    Reject { feedback: String },
+    /// Carry out this plan instead of the proposed one.
+    Edit { plan: String },
+}
+
+/// Async callback deciding on each proposed plan.
+pub type PlanApprover = Arc<dyn Fn(String) -> BoxFuture<'static, PlanDecision> + Send + Sync>;
+
+/// The outcome of a planning turn.
+#[derive(Debug, Clone)]
+pub struct PlanRun {
+    /// Every plan the agent proposed during the turn, in order.
+    pub proposals: Vec<String>,
+    /// The plan that was approved, as edited; `None` if none was.
+    pub approved_plan: Option<String>,
+    pub result: TurnResult,
+}
+
+#[derive(Debug, Default)]
+struct PlanState {
+    proposals: Vec<String>,
+    approved_plan: Option<String>,
+}
+
+/// Runs a session that starts in plan mode and switches to `execution_mode`
+/// once a plan is approved.
+///
+/// ```no_run
+/// use claude_agent_sdk::{ClaudeAgentOptions, PermissionMode, PlanDecision, PlanWorkflow};
+///
+/// # async fn run() -> claude_agent_sdk::Result<()> {
+/// let workflow = PlanWorkflow::new(ClaudeAgentOptions::default(), |plan: String| async move {
+///     println!("Proposed plan:\n{}", plan);
+///     if plan.contains("tests") {
+///         PlanDecision::Approve
+///     } else {
+///         PlanDecision::Reject {
+///             feedback: "Include a step that adds tests.".to_string(),
+///         }
+///     }
+/// })
+/// .execution_mode(PermissionMode::AcceptEdits);
+///
+/// let mut client = workflow.connect().await?;
+/// let run = workflow.run(&mut client, "Add input validation to the signup form").await?;
+/// println!("approved: {:?}", run.approved_plan);
+/// client.disconnect().await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct PlanWorkflow {
+    options: ClaudeAgentOptions,
+    execution_mode: PermissionMode,
+    approver: PlanApprover,
+    state: Arc<StdMutex<PlanState>>,
+}
+
+impl PlanWorkflow {
+    /// A workflow for `options` that executes approved plans in `acceptEdits` mode.
+    pub fn new<F, Fut>(options: ClaudeAgentOptions, approver: F) -> Self
+    where
+        F: Fn(String) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = PlanDecision> + Send + 'static,
+    {
+        Self {
+            options,
+            execution_mode: PermissionMode::AcceptEdits,
+            approver: Arc::new(move |plan| Box::pin(approver(plan))),
+            state: Arc::new(StdMutex::new(PlanState::default())),
+        }
+    }
+
+    /// The permission mode to switch to once a plan is approved.
+    pub fn execution_mode(mut self, mode: PermissionMode) -> Self {
+        self.execution_mode = mode;
+        self
+    }
+
+    /// The options the workflow's client runs with: `options` in plan mode,
+    /// with `ExitPlanMode` prompts sent to the approver.
+    ///
+    /// Other permission prompts go to `options.can_use_tool`, and are denied
+    /// when it is not set.
+    pub fn options(&self) -> ClaudeAgentOptions {
+        let mut options = self.options.clone();
+        options.permission_mode = Some(PermissionMode::Plan);
+
+        let fallback = self.options.can_use_tool.clone();
+        let approver = self.approver.clone();
+        let state = self.state.clone();
+        let execution_mode = self.execution_mode.clone();
+        let callback: CanUseToolCallback = Arc::new(move |tool_name, input, context| {
+            if tool_name != "ExitPlanMode" {
+                return match fallback {
+                    Some(ref fallback) => fallback(tool_name, input, context),
+                    None => Box::pin(async move {
+                        PermissionResult::Deny {
+                            message: format!("{} requires permission and no can_use_tool callback is set", tool_name),
+                            interrupt: false,
+                        }
+                    }),
+                };
+            }
+
+            let approver = approver.clone();
+            let state = state.clone();
+            let execution_mode = execution_mode.clone();
+            Box::pin(async move {
+                let plan = input.get("plan").and_then(|plan| plan.as_str()).unwrap_or_default().to_string();
+                state.lock().unwrap().proposals.push(plan.clone());
+
+                let approved = match approver(plan.clone()).await {
+                    PlanDecision::Approve => plan,
+                    PlanDecision::Edit { plan } => plan,
+                    PlanDecision::Reject { feedback } => {
+                        return PermissionResult::Deny {
+                            message: feedback,
+                            interrupt: false,
+                        }
+                    }
+                };
+                state.lock().unwrap().approved_plan = Some(approved.clone());
+
+                let mut input = input;
+                input["plan"] = approved.into();
+                PermissionResult::Allow {
+                    updated_input: Some(input),
+                    updated_permissions: Some(vec![PermissionUpdate::SetMode {
+                        mode: execution_mode,
+                        destination: Some(PermissionUpdateDestination::Session),
+                    }]),
+                }
+            })
+        });
+        options.can_use_tool = Some(callback);
+        options
+    }
+
+    /// Start a client in plan mode.
+    pub async fn connect(&self) -> Result<ClaudeSDKClient> {
+        let mut client = ClaudeSDKClient::new(self.options());
+        client.connect().await?;
+        Ok(client)
+    }
+
+    /// Send `prompt` on a client from `connect()` and wait for the turn to end.
+    ///
+    /// The agent usually carries out an approved plan in the same turn. Once a
+    /// plan is approved, the client is also switched to the execution mode for
+    /// later turns.
+    pub async fn run(&self, client: &mut ClaudeSDKClient, prompt: impl Into<String>) -> Result<PlanRun> {
+        std::mem::take(&mut *self.state.lock().unwrap());
+        client.query(prompt.into()).await?;
+        let result = client.receive_turn().await?;
+
+        let state = std::mem::take(&mut *self.state.lock().unwrap());
+        if state.approved_plan.is_some() {
+            client.set_permission_mode(self.execution_mode.clone()).await?;
+        }
+        Ok(PlanRun {
+            proposals: state.proposals,
+            approved_plan: state.approved_plan,
+            result,
+        })
+    }
+}
+
+impl std::fmt::Debug for PlanWorkflow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PlanWorkflow")
+            .field("options", &self.options)
+            .field("execution_mode", &self.execution_mode)
+            .finish_non_exhaustive()
+    }
+}
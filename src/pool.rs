@@ -0,0 +1,318 @@
+//! Pool for running many independent prompts with bounded concurrency.
+//!
+//! Each job runs in its own CLI process through a `ClaudeSDKClient`, so budgets
+//! and crash recovery configured on the base options apply per job.
+
+use crate::client::ClaudeSDKClient;
+use crate::errors::{ClaudeSDKError, Result};
+use crate::types::{ClaudeAgentOptions, PermissionMode, SystemPrompt, TurnResult};
+use futures::stream::Stream;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering as CmpOrdering;
+use std::collections::{BinaryHeap, HashMap};
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex as StdMutex};
+use tokio::sync::{mpsc, watch, Notify, OwnedSemaphorePermit, Semaphore};
+use tokio::task::{JoinHandle, JoinSet};
+
+/// Identifier assigned to a job when it is submitted.
+pub type JobId = u64;
+
+/// Per-job changes applied on top of the pool's base options.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct JobOverrides {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system_prompt: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_turns: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub permission_mode: Option<PermissionMode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allowed_tools: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub env: HashMap<String, String>,
+}
+
+impl JobOverrides {
+    /// Apply these overrides to a copy of the base options.
+    pub fn apply(&self, options: &mut ClaudeAgentOptions) {
+        if let Some(ref cwd) = self.cwd {
+            options.cwd = Some(cwd.clone());
+        }
+        if let Some(ref model) = self.model {
+            options.model = Some(model.clone());
+        }
+        if let Some(ref system_prompt) = self.system_prompt {
+            options.system_prompt = Some(SystemPrompt::Text(system_prompt.clone()));
+        }
+        if let Some(max_turns) = self.max_turns {
+            options.max_turns = Some(max_turns);
+        }
+        if let Some(ref mode) = self.permission_mode {
+            options.permission_mode = Some(mode.clone());
+        }
+        if let Some(ref tools) = self.allowed_tools {
+            options.allowed_tools = tools.clone();
+        }
+        options.env.extend(self.env.iter().map(|(k, v)| (k.clone(), v.clone())));
+    }
+}
+
+/// A prompt to run in the pool.
+///
+/// Jobs with a higher `priority` start first; jobs of equal priority start in
+/// submission order.
+#[derive(Debug, Clone, Default)]
+pub struct PoolJob {
+    pub prompt: String,
+    pub priority: i32,
+    pub overrides: JobOverrides,
+}
+
+impl PoolJob {
+    /// Create a job with default priority and no overrides.
+    pub fn new(prompt: impl Into<String>) -> Self {
+        Self {
+            prompt: prompt.into(),
+            ..Default::default()
+        }
+    }
+}
+
+/// The outcome of one job, reported through `QueryPool::results`.
+#[derive(Debug)]
+pub struct JobOutcome {
+    pub job_id: JobId,
+    pub result: Result<TurnResult>,
+}
+
+struct QueuedJob {
+    id: JobId,
+    job: PoolJob,
+}
+
+impl PartialEq for QueuedJob {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == CmpOrdering::Equal
+    }
+}
+
+impl Eq for QueuedJob {}
+
+impl PartialOrd for QueuedJob {
+    fn partial_cmp(&self, other: &Self) -> Option<CmpOrdering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for QueuedJob {
+    fn cmp(&self, other: &Self) -> CmpOrdering {
+        // Max-heap: higher priority first, then lower (earlier) id first
+        self.job
+            .priority
+            .cmp(&other.job.priority)
+            .then_with(|| other.id.cmp(&self.id))
+    }
+}
+
+#[derive(Default)]
+struct PoolState {
+    queue: BinaryHeap<QueuedJob>,
+    closed: bool,
+}
+
+struct PoolShared {
+    state: StdMutex<PoolState>,
+    job_available: Notify,
+    cancel: watch::Sender<bool>,
+}
+
+/// Runs prompts concurrently, each in its own CLI process, with at most
+/// `max_concurrency` processes alive at a time.
+///
+/// Must be created inside a tokio runtime. Dropping the pool cancels it.
+pub struct QueryPool {
+    shared: Arc<PoolShared>,
+    next_id: AtomicU64,
+    results_rx: StdMutex<Option<mpsc::UnboundedReceiver<JobOutcome>>>,
+    dispatcher: tokio::sync::Mutex<Option<JoinHandle<()>>>,
+}
+
+impl QueryPool {
+    /// Create a pool running jobs with `base_options` and at most
+    /// `max_concurrency` concurrent CLI processes.
+    pub fn new(base_options: ClaudeAgentOptions, max_concurrency: usize) -> Self {
+        let (cancel, _) = watch::channel(false);
+        let shared = Arc::new(PoolShared {
+            state: StdMutex::new(PoolState::default()),
+            job_available: Notify::new(),
+            cancel,
+        });
+        let (results_tx, results_rx) = mpsc::unbounded_channel();
+        let dispatcher = tokio::spawn(dispatch(
+            shared.clone(),
+            base_options,
+            Arc::new(Semaphore::new(max_concurrency.max(1))),
+            results_tx,
+        ));
+
+        Self {
+            shared,
+            next_id: AtomicU64::new(1),
+            results_rx: StdMutex::new(Some(results_rx)),
+            dispatcher: tokio::sync::Mutex::new(Some(dispatcher)),
+        }
+    }
+
+    /// Queue a prompt with default priority and no overrides.
+    pub fn submit(&self, prompt: impl Into<String>) -> Result<JobId> {
+        self.submit_job(PoolJob::new(prompt))
+    }
+
+    /// Queue a job. Fails once the pool has been closed or cancelled.
+    pub fn submit_job(&self, job: PoolJob) -> Result<JobId> {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        {
+            let mut state = self.shared.state.lock().unwrap();
+            if state.closed {
+                return Err(ClaudeSDKError::cancelled("Pool no longer accepts jobs"));
+            }
+            state.queue.push(QueuedJob { id, job });
+        }
+        self.shared.job_available.notify_one();
+        Ok(id)
+    }
+
+    /// Number of jobs waiting for a free slot.
+    pub fn queued(&self) -> usize {
+        self.shared.state.lock().unwrap().queue.len()
+    }
+
+    /// Stream of job outcomes, in completion order.
+    ///
+    /// The stream ends after `close()` once every queued job has finished, or
+    /// after `cancel()`. It can only be taken once; later calls return an
+    /// empty stream.
+    pub fn results(&self) -> Pin<Box<dyn Stream<Item = JobOutcome> + Send>> {
+        match self.results_rx.lock().unwrap().take() {
+            Some(rx) => Box::pin(tokio_stream::wrappers::UnboundedReceiverStream::new(rx)),
+            None => Box::pin(futures::stream::empty()),
+        }
+    }
+
+    /// Stop accepting new jobs. Queued and running jobs still complete.
+    pub fn close(&self) {
+        self.shared.state.lock().unwrap().closed = true;
+        self.shared.job_available.notify_one();
+    }
+
+    /// Cancel all jobs and shut down every CLI process.
+    ///
+    /// Running jobs are disconnected and queued jobs never start; each reports
+    /// a `Cancelled` error. Returns once all child processes have exited.
+    pub async fn cancel(&self) {
+        self.shared.state.lock().unwrap().closed = true;
+        self.shared.cancel.send_replace(true);
+        self.shared.job_available.notify_one();
+
+        if let Some(dispatcher) = self.dispatcher.lock().await.take() {
+            let _ = dispatcher.await;
+        }
+    }
+}
+
+impl Drop for QueryPool {
+    fn drop(&mut self) {
+        self.shared.state.lock().unwrap().closed = true;
+        self.shared.cancel.send_replace(true);
+        self.shared.job_available.notify_one();
+    }
+}
+
+async fn dispatch(
+    shared: Arc<PoolShared>,
+    base_options: ClaudeAgentOptions,
+    semaphore: Arc<Semaphore>,
+    results_tx: mpsc::UnboundedSender<JobOutcome>,
+) {
+    let mut cancel = shared.cancel.subscribe();
+    let mut running = JoinSet::new();
+
+    loop {
+        while running.try_join_next().is_some() {}
+
+        let permit = tokio::select! {
+            permit = semaphore.clone().acquire_owned() => permit.expect("pool semaphore is never closed"),
+            _ = cancel.wait_for(|cancelled| *cancelled) => break,
+        };
+
+        let next = loop {
+            {
+                let mut state = shared.state.lock().unwrap();
+                if let Some(queued) = state.queue.pop() {
+                    break Some(queued);
+                }
+                if state.closed {
+                    break None;
+                }
+            }
+            tokio::select! {
+                _ = shared.job_available.notified() => {}
+                _ = cancel.wait_for(|cancelled| *cancelled) => break None,
+            }
+        };
+        let Some(queued) = next else { break };
+
+        let mut options = base_options.clone();
+        queued.job.overrides.apply(&mut options);
+        running.spawn(run_job(
+            queued.id,
+            queued.job.prompt,
+            options,
+            shared.cancel.subscribe(),
+            results_tx.clone(),
+            permit,
+        ));
+    }
+
+    while running.join_next().await.is_some() {}
+
+    // Jobs still queued here were cancelled before they could start
+    let remaining = std::mem::take(&mut shared.state.lock().unwrap().queue);
+    for queued in remaining.into_sorted_vec().into_iter().rev() {
+        let _ = results_tx.send(JobOutcome {
+            job_id: queued.id,
+            result: Err(ClaudeSDKError::cancelled("Pool was cancelled before the job started")),
+        });
+    }
+}
+
+async fn run_job(
+    job_id: JobId,
+    prompt: String,
+    options: ClaudeAgentOptions,
+    mut cancel: watch::Receiver<bool>,
+    results_tx: mpsc::UnboundedSender<JobOutcome>,
+    _permit: OwnedSemaphorePermit,
+) {
+    let mut client = ClaudeSDKClient::new(options);
+    let result = tokio::select! {
+        result = run_turn(&mut client, prompt) => result,
+        _ = cancel.wait_for(|cancelled| *cancelled) => Err(ClaudeSDKError::cancelled("Pool was cancelled")),
+    };
+    let _ = client.disconnect().await;
+    let _ = results_tx.send(JobOutcome { job_id, result });
+}
+
+async fn run_turn(client: &mut ClaudeSDKClient, prompt: String) -> Result<TurnResult> {
+    client.connect().await?;
+    client.query(prompt).await?;
+    client.receive_turn().await
+}
@@ -2,31 +2,43 @@
 
 use crate::errors::{ClaudeSDKError, Result};
 use crate::transport::Transport;
-use crate::types::{ControlResponseType, HookCallback, PermissionResult, SDKControlResponse, ToolPermissionContext};
+use crate::types::{
+    CanUseToolCallback, ControlResponseType, HookCallback, HookContext, HookEvent, HookMatcher, McpServerStatus,
+    PermissionMode, PermissionResult, SDKControlResponse, ToolPermissionContext,
+};
 use futures::stream::{Stream, StreamExt};
 use serde_json::Value;
 use std::collections::HashMap;
-use std::pin::Pin;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex as StdMutex};
 use tokio::sync::{mpsc, oneshot, Mutex};
 
-type ToolPermissionCallback = Arc<
-    dyn Fn(String, Value, ToolPermissionContext) -> Pin<Box<dyn futures::Future<Output = PermissionResult> + Send>>
-        + Send
-        + Sync,
->;
+type ToolPermissionCallback = CanUseToolCallback;
+
+/// Whether a control error means the CLI does not know the request subtype,
+/// e.g. "Unsupported control request subtype: set_model".
+fn is_unsupported_error(message: &str) -> bool {
+    let message = message.to_ascii_lowercase();
+    message.contains("subtype") && (message.contains("unsupported") || message.contains("unknown"))
+}
+
+/// Hook registrations keyed by event: each matcher with its callback IDs.
+type HookRegistrations = HashMap<HookEvent, Vec<(Option<String>, Vec<String>)>>;
 
 pub struct Query {
     pub transport: Arc<Mutex<Box<dyn Transport>>>,
     is_streaming: bool,
     can_use_tool: Option<ToolPermissionCallback>,
-    hooks: HashMap<String, Vec<(Option<String>, Vec<String>)>>,
-    hook_callbacks: Arc<Mutex<HashMap<String, HookCallback>>>,
-    next_callback_id: Arc<Mutex<usize>>,
+    hooks: HookRegistrations,
+    hook_callbacks: Arc<HashMap<String, HookCallback>>,
     request_counter: Arc<Mutex<usize>>,
     pending_responses: Arc<Mutex<HashMap<String, oneshot::Sender<Result<Value>>>>>,
-    message_tx: mpsc::UnboundedSender<Result<Value>>,
-    message_rx: Option<mpsc::UnboundedReceiver<Result<Value>>>,
+    session_id: Arc<StdMutex<Option<String>>>,
+    closed: AtomicBool,
+    transport_ended: Arc<AtomicBool>,
+    // Owned by the reader task once started, so the message stream ends with the transport
+    message_tx: Option<mpsc::UnboundedSender<Result<Value>>>,
+    message_rx: mpsc::UnboundedReceiver<Result<Value>>,
     _initialization_result: Option<Value>,
 }
 
@@ -35,62 +47,67 @@ impl Query {
         transport: Box<dyn Transport>,
         is_streaming: bool,
         can_use_tool: Option<ToolPermissionCallback>,
-        hooks: Option<HashMap<String, Vec<(Option<String>, Vec<HookCallback>)>>>,
+        hooks: Option<HashMap<HookEvent, Vec<HookMatcher>>>,
     ) -> Self {
         let (message_tx, message_rx) = mpsc::unbounded_channel();
-        let hook_callbacks = Arc::new(Mutex::new(HashMap::new()));
-        let next_callback_id = Arc::new(Mutex::new(0));
-
-        // Convert hooks format
-        let converted_hooks = if let Some(hooks_map) = hooks {
-            let mut result = HashMap::new();
-            for (event, matchers) in hooks_map {
-                let mut matcher_data = Vec::new();
-                for (matcher, callbacks) in matchers {
-                    let mut callback_ids = Vec::new();
-                    for _cb in callbacks {
-                        // Store callbacks and generate IDs
-                        // This is simplified - full implementation would store actual callbacks
-                        let id = format!("hook_{}", callback_ids.len());
-                        callback_ids.push(id);
-                    }
-                    matcher_data.push((matcher, callback_ids));
+
+        // Give every callback an ID the CLI can refer to in hook_callback requests
+        let mut registrations = HashMap::new();
+        let mut hook_callbacks = HashMap::new();
+        for (event, matchers) in hooks.unwrap_or_default() {
+            let mut matcher_data = Vec::new();
+            for matcher in matchers {
+                let mut callback_ids = Vec::new();
+                for callback in matcher.hooks {
+                    let id = format!("hook_{}", hook_callbacks.len());
+                    hook_callbacks.insert(id.clone(), callback);
+                    callback_ids.push(id);
                 }
-                result.insert(event, matcher_data);
+                matcher_data.push((matcher.matcher, callback_ids));
             }
-            result
-        } else {
-            HashMap::new()
-        };
+            registrations.insert(event, matcher_data);
+        }
 
         Self {
             transport: Arc::new(Mutex::new(transport)),
             is_streaming,
             can_use_tool,
-            hooks: converted_hooks,
-            hook_callbacks,
-            next_callback_id,
+            hooks: registrations,
+            hook_callbacks: Arc::new(hook_callbacks),
             request_counter: Arc::new(Mutex::new(0)),
             pending_responses: Arc::new(Mutex::new(HashMap::new())),
-            message_tx,
-            message_rx: Some(message_rx),
+            session_id: Arc::new(StdMutex::new(None)),
+            closed: AtomicBool::new(false),
+            transport_ended: Arc::new(AtomicBool::new(false)),
+            message_tx: Some(message_tx),
+            message_rx,
             _initialization_result: None,
         }
     }
 
-    pub async fn start(&self) -> Result<()> {
+    pub async fn start(&mut self) -> Result<()> {
+        let message_tx = self
+            .message_tx
+            .take()
+            .ok_or_else(|| ClaudeSDKError::control_protocol("Query already started"))?;
         let transport = self.transport.clone();
-        let message_tx = self.message_tx.clone();
         let pending_responses = self.pending_responses.clone();
         let can_use_tool = self.can_use_tool.clone();
+        let hook_callbacks = self.hook_callbacks.clone();
+        let session_id = self.session_id.clone();
+        let transport_ended = self.transport_ended.clone();
 
-        tokio::spawn(async move {
-            let mut transport_guard = transport.lock().await;
-            let mut stream = transport_guard.read_messages();
+        // Take the stream out while holding the lock only briefly, so writes can proceed while reading
+        let mut stream = transport.lock().await.read_messages();
 
+        tokio::spawn(async move {
             while let Some(result) = stream.next().await {
                 match result {
                     Ok(value) => {
+                        if let Some(id) = value.get("session_id").and_then(|v| v.as_str()) {
+                            *session_id.lock().unwrap() = Some(id.to_string());
+                        }
+
                         // Route control messages
                         if let Some(msg_type) = value.get("type").and_then(|v| v.as_str()) {
                             match msg_type {
@@ -99,7 +116,13 @@ impl Query {
                                     continue;
                                 }
                                 "control_request" => {
-                                    Self::handle_control_request(value, transport.clone(), can_use_tool.clone()).await;
+                                    // Answer off the reader task, so callbacks can themselves use the control protocol
+                                    tokio::spawn(Self::handle_control_request(
+                                        value,
+                                        transport.clone(),
+                                        can_use_tool.clone(),
+                                        hook_callbacks.clone(),
+                                    ));
                                     continue;
                                 }
                                 _ => {}
@@ -117,6 +140,10 @@ impl Query {
                     }
                 }
             }
+
+            // The transport is gone, so no outstanding control request can be answered
+            transport_ended.store(true, Ordering::SeqCst);
+            pending_responses.lock().await.clear();
         });
 
         Ok(())
@@ -146,6 +173,7 @@ impl Query {
         value: Value,
         transport: Arc<Mutex<Box<dyn Transport>>>,
         can_use_tool: Option<ToolPermissionCallback>,
+        hook_callbacks: Arc<HashMap<String, HookCallback>>,
     ) {
         let request_id = value
             .get("request_id")
@@ -158,7 +186,7 @@ impl Query {
             return;
         }
 
-        let response_data = match Self::process_control_request(request.unwrap(), can_use_tool).await {
+        let response_data = match Self::process_control_request(request.unwrap(), can_use_tool, &hook_callbacks).await {
             Ok(data) => SDKControlResponse::ControlResponse {
                 response: ControlResponseType::Success {
                     request_id: request_id.clone(),
@@ -183,6 +211,7 @@ impl Query {
     async fn process_control_request(
         request: &Value,
         can_use_tool: Option<ToolPermissionCallback>,
+        hook_callbacks: &HashMap<String, HookCallback>,
     ) -> Result<Value> {
         let subtype = request.get("subtype").and_then(|v| v.as_str()).unwrap_or("");
 
@@ -195,35 +224,63 @@ impl Query {
                         .ok_or_else(|| ClaudeSDKError::control_protocol("Missing tool_name"))?
                         .to_string();
                     let input = request.get("input").cloned().unwrap_or(Value::Null);
+                    // Suggestions this SDK cannot represent are left out rather than failing the prompt
                     let suggestions = request
                         .get("permission_suggestions")
                         .and_then(|v| v.as_array())
-                        .map(|_| Vec::new())
+                        .map(|suggestions| {
+                            suggestions.iter().filter_map(|s| serde_json::from_value(s.clone()).ok()).collect()
+                        })
                         .unwrap_or_default();
+                    let string_field = |key: &str| request.get(key).and_then(|v| v.as_str()).map(String::from);
 
-                    let context = ToolPermissionContext { suggestions };
+                    let context = ToolPermissionContext {
+                        suggestions,
+                        blocked_path: string_field("blocked_path"),
+                        tool_use_id: string_field("tool_use_id"),
+                    };
+                    let original_input = input.clone();
                     let result = callback(tool_name, input, context).await;
 
                     match result {
                         PermissionResult::Allow {
                             updated_input,
-                            updated_permissions: _,
+                            updated_permissions,
                         } => {
-                            let mut response = serde_json::json!({ "allow": true });
-                            if let Some(input) = updated_input {
-                                response["input"] = input;
+                            let mut response = serde_json::json!({
+                                "behavior": "allow",
+                                "updatedInput": updated_input.unwrap_or(original_input),
+                            });
+                            if let Some(updates) = updated_permissions {
+                                response["updatedPermissions"] = serde_json::to_value(updates)?;
                             }
                             Ok(response)
                         }
-                        PermissionResult::Deny { message, interrupt: _ } => {
-                            Ok(serde_json::json!({ "allow": false, "reason": message }))
-                        }
+                        PermissionResult::Deny { message, interrupt } => Ok(serde_json::json!({
+                            "behavior": "deny",
+                            "message": message,
+                            "interrupt": interrupt,
+                        })),
                     }
                 } else {
                     Err(ClaudeSDKError::control_protocol("can_use_tool callback not provided"))
                 }
             }
-            "initialize" | "interrupt" | "set_permission_mode" | "hook_callback" | "mcp_message" => {
+            "hook_callback" => {
+                let callback_id = request
+                    .get("callback_id")
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| ClaudeSDKError::control_protocol("Missing callback_id"))?;
+                let callback = hook_callbacks
+                    .get(callback_id)
+                    .ok_or_else(|| ClaudeSDKError::control_protocol(format!("No hook callback found for ID: {}", callback_id)))?;
+                let input = request.get("input").cloned().unwrap_or(Value::Null);
+                let tool_use_id = request.get("tool_use_id").and_then(|v| v.as_str()).map(str::to_string);
+
+                let output = callback(input, tool_use_id, HookContext {}).await;
+                Ok(serde_json::to_value(output)?)
+            }
+            "initialize" | "interrupt" | "set_permission_mode" | "mcp_message" => {
                 // Simplified - return empty success
                 Ok(Value::Null)
             }
@@ -247,8 +304,27 @@ impl Query {
     }
 
     async fn build_hooks_config(&self) -> Value {
-        // Simplified hooks configuration
-        serde_json::json!(null)
+        if self.hooks.is_empty() {
+            return Value::Null;
+        }
+
+        let config: serde_json::Map<String, Value> = self
+            .hooks
+            .iter()
+            .map(|(event, matchers)| {
+                let matchers: Vec<Value> = matchers
+                    .iter()
+                    .map(|(matcher, callback_ids)| {
+                        serde_json::json!({
+                            "matcher": matcher,
+                            "hookCallbackIds": callback_ids
+                        })
+                    })
+                    .collect();
+                (event.to_string(), Value::Array(matchers))
+            })
+            .collect();
+        Value::Object(config)
     }
 
     pub async fn send_control_request(&self, request: Value) -> Result<Value> {
@@ -256,6 +332,7 @@ impl Query {
             return Err(ClaudeSDKError::control_protocol("Control requests require streaming mode"));
         }
 
+        let subtype = request.get("subtype").and_then(Value::as_str).unwrap_or_default().to_string();
         let mut counter = self.request_counter.lock().await;
         *counter += 1;
         let request_id = format!("req_{}_{}", *counter, uuid::Uuid::new_v4());
@@ -263,6 +340,10 @@ impl Query {
 
         let (tx, rx) = oneshot::channel();
         self.pending_responses.lock().await.insert(request_id.clone(), tx);
+        if self.transport_ended.load(Ordering::SeqCst) {
+            self.pending_responses.lock().await.remove(&request_id);
+            return Err(ClaudeSDKError::control_protocol("Transport closed"));
+        }
 
         let control_request = serde_json::json!({
             "type": "control_request",
@@ -281,6 +362,12 @@ impl Query {
             .await
             .map_err(|_| ClaudeSDKError::timeout("Control request timeout"))?
             .map_err(|_| ClaudeSDKError::control_protocol("Response channel closed"))?
+            .map_err(|e| match e {
+                ClaudeSDKError::ControlProtocol(message) if is_unsupported_error(&message) => {
+                    ClaudeSDKError::unsupported(subtype, message)
+                }
+                other => other,
+            })
     }
 
     pub async fn interrupt(&self) -> Result<()> {
@@ -289,7 +376,7 @@ impl Query {
         Ok(())
     }
 
-    pub async fn set_permission_mode(&self, mode: String) -> Result<()> {
+    pub async fn set_permission_mode(&self, mode: PermissionMode) -> Result<()> {
         self.send_control_request(serde_json::json!({
             "subtype": "set_permission_mode",
             "mode": mode
@@ -298,12 +385,68 @@ impl Query {
         Ok(())
     }
 
+    pub async fn set_model(&self, model: Option<&str>) -> Result<()> {
+        self.send_control_request(serde_json::json!({
+            "subtype": "set_model",
+            "model": model
+        }))
+        .await?;
+        Ok(())
+    }
+
+    pub async fn mcp_status(&self) -> Result<Vec<McpServerStatus>> {
+        let response = self.send_control_request(serde_json::json!({ "subtype": "mcp_status" })).await?;
+        let servers = response.get("mcpServers").cloned().unwrap_or(Value::Array(Vec::new()));
+        serde_json::from_value(servers.clone())
+            .map_err(|e| ClaudeSDKError::message_parse(format!("Invalid mcp_status response: {}", e), Some(servers)))
+    }
+
+    pub async fn mcp_reconnect(&self, server_name: &str) -> Result<()> {
+        self.send_control_request(serde_json::json!({
+            "subtype": "mcp_reconnect",
+            "serverName": server_name
+        }))
+        .await?;
+        Ok(())
+    }
+
+    pub async fn mcp_toggle(&self, server_name: &str, enabled: bool) -> Result<()> {
+        self.send_control_request(serde_json::json!({
+            "subtype": "mcp_toggle",
+            "serverName": server_name,
+            "enabled": enabled
+        }))
+        .await?;
+        Ok(())
+    }
+
     pub fn receive_messages(&mut self) -> impl Stream<Item = Result<Value>> + '_ {
-        let rx = self.message_rx.take().unwrap();
-        tokio_stream::wrappers::UnboundedReceiverStream::new(rx)
+        let rx = &mut self.message_rx;
+        futures::stream::poll_fn(move |cx| rx.poll_recv(cx))
+    }
+
+    /// Receive the next message, or `None` once the transport has ended.
+    pub async fn next_message(&mut self) -> Option<Result<Value>> {
+        self.message_rx.recv().await
+    }
+
+    /// Session ID reported by the most recent message carrying one, if any.
+    pub fn session_id(&self) -> Option<String> {
+        self.session_id.lock().unwrap().clone()
+    }
+
+    /// Seed the session ID, e.g. when resuming a known session.
+    pub fn set_session_id(&self, session_id: Option<String>) {
+        *self.session_id.lock().unwrap() = session_id;
+    }
+
+    /// Whether `close()` has been called, as opposed to the transport ending on its own.
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
     }
 
     pub async fn close(&self) -> Result<()> {
+        self.closed.store(true, Ordering::SeqCst);
         let mut transport = self.transport.lock().await;
         transport.close().await
     }
@@ -0,0 +1,323 @@
+//! Typed Claude Code settings.
+//!
+//! `Settings` models the documented keys of a `settings.json` file, keeping
+//! any others in `extra`. It can be passed inline through
+//! `ClaudeAgentOptions::settings`, in which case it is written to a private
+//! temp file when the CLI starts, and the user, project and local settings
+//! files can be loaded and merged to preview what a session will see.
+
+use crate::errors::{ClaudeSDKError, Result};
+use crate::transport::subprocess::home_dir;
+use crate::types::{HookEvent, PermissionMode, SettingSource};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// The contents of a Claude Code `settings.json` file.
+///
+/// Unset fields are left out when serialized, so a `Settings` only overrides
+/// what it sets.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Settings {
+    #[serde(skip_serializing_if = "PermissionSettings::is_empty")]
+    pub permissions: PermissionSettings,
+    /// Environment variables set for every session.
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub env: HashMap<String, String>,
+    /// Shell commands run on hook events.
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub hooks: HashMap<HookEvent, Vec<SettingsHookMatcher>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disable_all_hooks: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Script that prints the API key to use.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_key_helper: Option<String>,
+    /// Days to keep chat transcripts.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cleanup_period_days: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_co_authored_by: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_style: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status_line: Option<Value>,
+    /// `claudeai` or `console`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub force_login_method: Option<String>,
+    /// Approve every server in the project's `.mcp.json`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enable_all_project_mcp_servers: Option<bool>,
+    #[serde(rename = "enabledMcpjsonServers", skip_serializing_if = "Vec::is_empty")]
+    pub enabled_mcpjson_servers: Vec<String>,
+    #[serde(rename = "disabledMcpjsonServers", skip_serializing_if = "Vec::is_empty")]
+    pub disabled_mcpjson_servers: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub aws_auth_refresh: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub aws_credential_export: Option<String>,
+    /// Keys this SDK does not model.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// The `permissions` section of a settings file.
+///
+/// Rules use the CLI's syntax, such as `Bash(npm run test:*)` or `Read(./.env)`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct PermissionSettings {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub allow: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub deny: Vec<String>,
+    /// Rules that always prompt, even when another rule would allow.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub ask: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub additional_directories: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_mode: Option<PermissionMode>,
+    /// Set to `disable` to forbid `bypassPermissions` mode.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disable_bypass_permissions_mode: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl PermissionSettings {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Hooks run for tools matching `matcher`, or for every tool when it is unset.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SettingsHookMatcher {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub matcher: Option<String>,
+    #[serde(default)]
+    pub hooks: Vec<SettingsHook>,
+}
+
+/// A hook in a settings file. Claude Code runs `command` through the shell.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SettingsHook {
+    /// Always `command` in current CLI versions.
+    #[serde(rename = "type")]
+    pub hook_type: String,
+    pub command: String,
+    /// Seconds before the command is cancelled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl SettingsHook {
+    /// A command hook with no timeout.
+    pub fn command(command: impl Into<String>) -> Self {
+        Self {
+            hook_type: "command".to_string(),
+            command: command.into(),
+            timeout: None,
+            extra: Map::new(),
+        }
+    }
+}
+
+impl Settings {
+    /// Parse a settings file. Errors name the file and the offending key.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)?;
+        Self::from_json_str(&text).map_err(|e| match e {
+            ClaudeSDKError::InvalidConfig(message) => {
+                ClaudeSDKError::invalid_config(format!("{}: {}", path.display(), message))
+            }
+            other => other,
+        })
+    }
+
+    /// Like `from_file`, but a missing file is `None` rather than an error.
+    pub fn load(path: impl AsRef<Path>) -> Result<Option<Self>> {
+        match Self::from_file(path) {
+            Err(ClaudeSDKError::IO(e)) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            other => other.map(Some),
+        }
+    }
+
+    pub fn from_json_str(json: &str) -> Result<Self> {
+        let deserializer = &mut serde_json::Deserializer::from_str(json);
+        serde_path_to_error::deserialize(deserializer).map_err(|e| {
+            ClaudeSDKError::invalid_config(match e.path().to_string().as_str() {
+                "." => e.inner().to_string(),
+                path => format!("{}: {}", path, e.inner()),
+            })
+        })
+    }
+
+    /// Load and merge the settings files for `sources`, local over project
+    /// over user as in the CLI. Missing files, and user settings when there
+    /// is no home directory, are skipped.
+    pub fn load_sources(sources: &[SettingSource], cwd: &Path) -> Result<Self> {
+        let mut settings = Self::default();
+        for source in SettingSource::ALL.iter().filter(|source| sources.contains(source)) {
+            let Some(path) = source.path(cwd) else { continue };
+            if let Some(loaded) = Self::load(path)? {
+                settings.merge(loaded);
+            }
+        }
+        Ok(settings)
+    }
+
+    /// Layer `other` on top of these settings.
+    ///
+    /// Values set in `other` replace ours; permission rules, MCP server lists
+    /// and hooks are combined, and `env` and `extra` are merged key by key.
+    pub fn merge(&mut self, other: Settings) {
+        self.permissions.merge(other.permissions);
+        self.env.extend(other.env);
+        for (event, matchers) in other.hooks {
+            self.hooks.entry(event).or_default().extend(matchers);
+        }
+        merge_option(&mut self.disable_all_hooks, other.disable_all_hooks);
+        merge_option(&mut self.model, other.model);
+        merge_option(&mut self.api_key_helper, other.api_key_helper);
+        merge_option(&mut self.cleanup_period_days, other.cleanup_period_days);
+        merge_option(&mut self.include_co_authored_by, other.include_co_authored_by);
+        merge_option(&mut self.output_style, other.output_style);
+        merge_option(&mut self.status_line, other.status_line);
+        merge_option(&mut self.force_login_method, other.force_login_method);
+        merge_option(&mut self.enable_all_project_mcp_servers, other.enable_all_project_mcp_servers);
+        merge_list(&mut self.enabled_mcpjson_servers, other.enabled_mcpjson_servers);
+        merge_list(&mut self.disabled_mcpjson_servers, other.disabled_mcpjson_servers);
+        merge_option(&mut self.aws_auth_refresh, other.aws_auth_refresh);
+        merge_option(&mut self.aws_credential_export, other.aws_credential_export);
+        self.extra.extend(other.extra);
+    }
+
+    /// Write the settings to a temp file readable only by the current user,
+    /// deleted when the returned handle is dropped.
+    pub(crate) fn write_temp_file(&self) -> Result<tempfile::NamedTempFile> {
+        let mut file = tempfile::Builder::new().prefix("claude-settings-").suffix(".json").tempfile()?;
+        serde_json::to_writer(&mut file, self)?;
+        Ok(file)
+    }
+}
+
+impl PermissionSettings {
+    fn merge(&mut self, other: PermissionSettings) {
+        merge_list(&mut self.allow, other.allow);
+        merge_list(&mut self.deny, other.deny);
+        merge_list(&mut self.ask, other.ask);
+        merge_list(&mut self.additional_directories, other.additional_directories);
+        merge_option(&mut self.default_mode, other.default_mode);
+        merge_option(&mut self.disable_bypass_permissions_mode, other.disable_bypass_permissions_mode);
+        self.extra.extend(other.extra);
+    }
+}
+
+fn merge_option<T>(ours: &mut Option<T>, theirs: Option<T>) {
+    if theirs.is_some() {
+        *ours = theirs;
+    }
+}
+
+fn merge_list(ours: &mut Vec<String>, theirs: Vec<String>) {
+    for item in theirs {
+        if !ours.contains(&item) {
+            ours.push(item);
+        }
+    }
+}
+
+impl SettingSource {
+    /// Every source, in increasing order of precedence.
+    pub const ALL: [SettingSource; 3] = [SettingSource::User, SettingSource::Project, SettingSource::Local];
+
+    /// The settings file this source reads for a session in `cwd`.
+    ///
+    /// User settings live in `$CLAUDE_CONFIG_DIR`, or `~/.claude` when that is
+    /// unset; with no home directory either, there is no user settings file.
+    pub fn path(&self, cwd: &Path) -> Option<PathBuf> {
+        match self {
+            SettingSource::User => {
+                let dir = match std::env::var_os("CLAUDE_CONFIG_DIR") {
+                    Some(dir) => PathBuf::from(dir),
+                    None => home_dir()?.join(".claude"),
+                };
+                Some(dir.join("settings.json"))
+            }
+            SettingSource::Project => Some(cwd.join(".claude").join("settings.json")),
+            SettingSource::Local => Some(cwd.join(".claude").join("settings.local.json")),
+        }
+    }
+}
+
+/// The `--settings` argument: a settings file or a `Settings` value.
+///
+/// In config files a string is a path and a table is inline settings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum SettingsArg {
+    /// A path, or a JSON string, passed to the CLI as is.
+    File(PathBuf),
+    /// Settings written to a private temp file when the CLI starts.
+    Inline(Box<Settings>),
+}
+
+impl SettingsArg {
+    /// The settings this argument stands for, reading a relative path against
+    /// the session's `cwd`. A missing file is an error.
+    pub fn load(&self, cwd: &Path) -> Result<Settings> {
+        match self {
+            SettingsArg::Inline(settings) => Ok(settings.as_ref().clone()),
+            SettingsArg::File(path) => match self.inline_json() {
+                Some(json) => Settings::from_json_str(json),
+                None => Settings::from_file(cwd.join(path)),
+            },
+        }
+    }
+
+    /// The JSON text of a `File` holding a JSON string rather than a path.
+    pub(crate) fn inline_json(&self) -> Option<&str> {
+        match self {
+            SettingsArg::File(path) => path.to_str().filter(|text| text.trim_start().starts_with('{')),
+            SettingsArg::Inline(_) => None,
+        }
+    }
+}
+
+impl From<Settings> for SettingsArg {
+    fn from(settings: Settings) -> Self {
+        SettingsArg::Inline(Box::new(settings))
+    }
+}
+
+impl From<PathBuf> for SettingsArg {
+    fn from(path: PathBuf) -> Self {
+        SettingsArg::File(path)
+    }
+}
+
+impl From<&Path> for SettingsArg {
+    fn from(path: &Path) -> Self {
+        SettingsArg::File(path.to_path_buf())
+    }
+}
+
+impl From<String> for SettingsArg {
+    fn from(path: String) -> Self {
+        SettingsArg::File(path.into())
+    }
+}
+
+impl From<&str> for SettingsArg {
+    fn from(path: &str) -> Self {
+        SettingsArg::File(path.into())
+    }
+}
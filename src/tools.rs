@@ -0,0 +1,412 @@
+//! Typed inputs and outputs for Claude Code's built-in tools.
+//!
+//! Tool calls arrive as a tool name plus untyped JSON, both in
+//! `ContentBlock::ToolUse` and in the `can_use_tool` callback.
+//! `BuiltinToolInput::parse` turns them into one of the structs below, falling
+//! back to `Other` for MCP tools and tools this SDK does not know, and to
+//! `Malformed` for built-in tools whose input does not match the expected shape.
+
+use crate::types::{string_enum, ContentBlock, PostToolUseHookInput, PreToolUseHookInput};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Names of Claude Code's built-in tools known to this SDK. MCP tools are
+/// named `mcp__<server>__<tool>` instead.
+///
+/// Newer CLI versions may have tools missing here, so this list is only used
+/// for warnings, never to reject a tool.
+pub const BUILTIN_TOOLS: &[&str] = &[
+    "Bash",
+    "BashOutput",
+    "KillShell",
+    "Read",
+    "Write",
+    "Edit",
+    "MultiEdit",
+    "LS",
+    "Glob",
+    "Grep",
+    "WebFetch",
+    "WebSearch",
+    "Task",
+    "TodoWrite",
+    "NotebookRead",
+    "NotebookEdit",
+    "ExitPlanMode",
+    "SlashCommand",
+    "Skill",
+];
+
+/// Whether `name` is a built-in tool in `BUILTIN_TOOLS` or an MCP tool.
+pub fn is_known_tool(name: &str) -> bool {
+    BUILTIN_TOOLS.contains(&name) || name.starts_with("mcp__")
+}
+
+/// Whether `name` could be a tool name: non-empty ASCII letters, digits, `_`
+/// and `-`, whether or not this SDK knows the tool.
+pub fn is_valid_tool_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BashInput {
+    pub command: String,
+    /// Timeout in milliseconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run_in_background: Option<bool>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReadInput {
+    pub file_path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offset: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WriteInput {
+    pub file_path: String,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EditInput {
+    pub file_path: String,
+    pub old_string: String,
+    pub new_string: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replace_all: Option<bool>,
+}
+
+/// One replacement within a `MultiEdit` call.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EditOperation {
+    pub old_string: String,
+    pub new_string: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replace_all: Option<bool>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MultiEditInput {
+    pub file_path: String,
+    pub edits: Vec<EditOperation>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GlobInput {
+    pub pattern: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+}
+
+string_enum! {
+    /// What a Grep call returns.
+    pub enum GrepOutputMode {
+        Content => "content",
+        FilesWithMatches => "files_with_matches",
+        Count => "count",
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GrepInput {
+    pub pattern: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub glob: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_mode: Option<GrepOutputMode>,
+    #[serde(default, rename = "-B", skip_serializing_if = "Option::is_none")]
+    pub lines_before: Option<u64>,
+    #[serde(default, rename = "-A", skip_serializing_if = "Option::is_none")]
+    pub lines_after: Option<u64>,
+    #[serde(default, rename = "-C", skip_serializing_if = "Option::is_none")]
+    pub context: Option<u64>,
+    #[serde(default, rename = "-n", skip_serializing_if = "Option::is_none")]
+    pub line_numbers: Option<bool>,
+    #[serde(default, rename = "-i", skip_serializing_if = "Option::is_none")]
+    pub case_insensitive: Option<bool>,
+    #[serde(default, rename = "type", skip_serializing_if = "Option::is_none")]
+    pub file_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub head_limit: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub multiline: Option<bool>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebFetchInput {
+    pub url: String,
+    pub prompt: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebSearchInput {
+    pub query: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowed_domains: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blocked_domains: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskInput {
+    pub description: String,
+    pub prompt: String,
+    pub subagent_type: String,
+}
+
+string_enum! {
+    /// Progress of a todo item.
+    pub enum TodoStatus {
+        Pending => "pending",
+        InProgress => "in_progress",
+        Completed => "completed",
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TodoItem {
+    pub content: String,
+    pub status: TodoStatus,
+    #[serde(rename = "activeForm")]
+    pub active_form: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TodoWriteInput {
+    pub todos: Vec<TodoItem>,
+}
+
+string_enum! {
+    /// Kind of notebook cell.
+    pub enum NotebookCellType {
+        Code => "code",
+        Markdown => "markdown",
+    }
+}
+
+string_enum! {
+    /// How a NotebookEdit call changes the notebook.
+    pub enum NotebookEditMode {
+        Replace => "replace",
+        Insert => "insert",
+        Delete => "delete",
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NotebookEditInput {
+    pub notebook_path: String,
+    pub new_source: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cell_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cell_type: Option<NotebookCellType>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub edit_mode: Option<NotebookEditMode>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExitPlanModeInput {
+    pub plan: String,
+}
+
+/// The input of a tool call, typed for Claude Code's built-in tools.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BuiltinToolInput {
+    Bash(BashInput),
+    Read(ReadInput),
+    Write(WriteInput),
+    Edit(EditInput),
+    MultiEdit(MultiEditInput),
+    Glob(GlobInput),
+    Grep(GrepInput),
+    WebFetch(WebFetchInput),
+    WebSearch(WebSearchInput),
+    Task(TaskInput),
+    TodoWrite(TodoWriteInput),
+    NotebookEdit(NotebookEditInput),
+    ExitPlanMode(ExitPlanModeInput),
+    /// An MCP tool or a tool this SDK does not know.
+    Other { name: String, input: Value },
+    /// A built-in tool whose input did not parse. Permission policies should
+    /// treat this as suspect rather than as an unrelated tool.
+    Malformed { name: String, input: Value, error: String },
+}
+
+impl BuiltinToolInput {
+    /// Parse the input of a call to the tool `name`.
+    pub fn parse(name: &str, input: &Value) -> Self {
+        let parsed = match name {
+            "Bash" => parse_as(input, Self::Bash),
+            "Read" => parse_as(input, Self::Read),
+            "Write" => parse_as(input, Self::Write),
+            "Edit" => parse_as(input, Self::Edit),
+            "MultiEdit" => parse_as(input, Self::MultiEdit),
+            "Glob" => parse_as(input, Self::Glob),
+            "Grep" => parse_as(input, Self::Grep),
+            "WebFetch" => parse_as(input, Self::WebFetch),
+            "WebSearch" => parse_as(input, Self::WebSearch),
+            "Task" => parse_as(input, Self::Task),
+            "TodoWrite" => parse_as(input, Self::TodoWrite),
+            "NotebookEdit" => parse_as(input, Self::NotebookEdit),
+            "ExitPlanMode" => parse_as(input, Self::ExitPlanMode),
+            _ => {
+                return Self::Other {
+                    name: name.to_string(),
+                    input: input.clone(),
+                }
+            }
+        };
+        parsed.unwrap_or_else(|e| Self::Malformed {
+            name: name.to_string(),
+            input: input.clone(),
+            error: e.to_string(),
+        })
+    }
+
+    /// The file this call reads or changes, for file-based tools.
+    pub fn file_path(&self) -> Option<&str> {
+        match self {
+            Self::Read(input) => Some(&input.file_path),
+            Self::Write(input) => Some(&input.file_path),
+            Self::Edit(input) => Some(&input.file_path),
+            Self::MultiEdit(input) => Some(&input.file_path),
+            Self::NotebookEdit(input) => Some(&input.notebook_path),
+            _ => None,
+        }
+    }
+}
+
+/// A borrowed view of a `ContentBlock::ToolUse`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ToolUse<'a> {
+    pub id: &'a str,
+    pub name: &'a str,
+    pub input: &'a Value,
+}
+
+impl ToolUse<'_> {
+    /// The tool input, typed when `name` is a built-in tool.
+    pub fn typed(&self) -> BuiltinToolInput {
+        BuiltinToolInput::parse(self.name, self.input)
+    }
+}
+
+impl ContentBlock {
+    /// View this block as a tool call, if it is one.
+    pub fn as_tool_use(&self) -> Option<ToolUse<'_>> {
+        match self {
+            ContentBlock::ToolUse { id, name, input } => Some(ToolUse { id, name, input }),
+            _ => None,
+        }
+    }
+}
+
+/// The `tool_response` of a Bash call, as seen by PostToolUse hooks.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct BashOutput {
+    pub stdout: String,
+    pub stderr: String,
+    pub interrupted: bool,
+    pub is_image: bool,
+}
+
+/// The `tool_response` of a Glob call.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct GlobOutput {
+    pub filenames: Vec<String>,
+    pub num_files: u64,
+    pub truncated: bool,
+    pub duration_ms: u64,
+}
+
+/// The `tool_response` of a Write call.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct WriteOutput {
+    /// `create` or `update`.
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub file_path: String,
+    pub content: String,
+}
+
+/// The `tool_response` of an Edit call.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct EditOutput {
+    pub file_path: String,
+    pub old_string: String,
+    pub new_string: String,
+    pub replace_all: bool,
+}
+
+/// The response of a tool call, typed for the built-in tools whose response
+/// shape is stable.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BuiltinToolOutput {
+    Bash(BashOutput),
+    Glob(GlobOutput),
+    Write(WriteOutput),
+    Edit(EditOutput),
+    /// Any other tool, or a response that did not parse.
+    Other { name: String, response: Value },
+}
+
+impl BuiltinToolOutput {
+    /// Parse the response of a call to the tool `name`.
+    pub fn parse(name: &str, response: &Value) -> Self {
+        let parsed = match name {
+            "Bash" => parse_as(response, Self::Bash),
+            "Glob" => parse_as(response, Self::Glob),
+            "Write" => parse_as(response, Self::Write),
+            "Edit" => parse_as(response, Self::Edit),
+            _ => return Self::Other {
+                name: name.to_string(),
+                response: response.clone(),
+            },
+        };
+        parsed.unwrap_or_else(|_| Self::Other {
+            name: name.to_string(),
+            response: response.clone(),
+        })
+    }
+}
+
+impl PreToolUseHookInput {
+    /// The input of the tool about to run, typed for built-in tools.
+    pub fn typed_input(&self) -> BuiltinToolInput {
+        BuiltinToolInput::parse(&self.tool_name, &self.tool_input)
+    }
+}
+
+impl PostToolUseHookInput {
+    /// The input of the tool that ran, typed for built-in tools.
+    pub fn typed_input(&self) -> BuiltinToolInput {
+        BuiltinToolInput::parse(&self.tool_name, &self.tool_input)
+    }
+
+    /// The tool's response, typed for built-in tools with a stable response shape.
+    pub fn typed_response(&self) -> BuiltinToolOutput {
+        BuiltinToolOutput::parse(&self.tool_name, &self.tool_response)
+    }
+}
+
+fn parse_as<T, U>(value: &Value, variant: fn(T) -> U) -> serde_json::Result<U>
+where
+    T: serde::de::DeserializeOwned,
+{
+    serde_json::from_value(value.clone()).map(variant)
+}
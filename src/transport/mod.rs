@@ -22,7 +22,10 @@ pub trait Transport: Send {
     async fn write(&mut self, data: String) -> Result<()>;
 
     /// Read and parse messages from the transport.
-    fn read_messages(&mut self) -> Pin<Box<dyn Stream<Item = Result<Value>> + Send + '_>>;
+    ///
+    /// The returned stream does not borrow the transport, so writes can continue
+    /// while messages are being read.
+    fn read_messages(&mut self) -> Pin<Box<dyn Stream<Item = Result<Value>> + Send + 'static>>;
 
     /// Close the transport connection and clean up resources.
     async fn close(&mut self) -> Result<()>;
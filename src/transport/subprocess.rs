@@ -1,17 +1,18 @@
 //! Subprocess transport implementation using Claude Code CLI.
 
 use crate::errors::{ClaudeSDKError, Result};
+use crate::settings::SettingsArg;
 use crate::transport::Transport;
-use crate::types::{ClaudeAgentOptions, McpServerConfig, SystemPrompt};
+use crate::types::{ClaudeAgentOptions, McpServerConfig, StderrCallback};
 use async_trait::async_trait;
 use bytes::BytesMut;
 use futures::stream::Stream;
 use serde_json::Value;
-use std::collections::HashMap;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
 use std::pin::Pin;
 use std::process::Stdio;
-use std::sync::Arc;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::process::{Child, ChildStdin, ChildStdout, Command};
 use tokio::sync::mpsc;
@@ -20,6 +21,142 @@ use tracing::{debug, error};
 const DEFAULT_MAX_BUFFER_SIZE: usize = 1024 * 1024; // 1MB
 const SDK_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+/// Shown in place of secret values in a redacted `CliInvocation`.
+pub const REDACTED: &str = "<redacted>";
+
+/// Environment variable and header names whose values a redacted
+/// `CliInvocation` shows as given, compared case-insensitively. Every other
+/// value is redacted, since a secret can hide behind any name
+/// (`DATABASE_URL=postgres://user:pw@host`).
+pub const NON_SECRET_NAMES: &[&str] = &[
+    "CLAUDE_CODE_ENTRYPOINT",
+    "CLAUDE_AGENT_SDK_VERSION",
+    "CLAUDE_CONFIG_DIR",
+    "PATH",
+    "HOME",
+    "USER",
+    "SHELL",
+    "TERM",
+    "LANG",
+    "TZ",
+    "DEBUG",
+    "LOG_LEVEL",
+    "RUST_LOG",
+    "NODE_ENV",
+    "Accept",
+    "Content-Type",
+    "User-Agent",
+];
+
+/// The command line, environment and working directory a CLI process is
+/// started with.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CliInvocation {
+    pub program: PathBuf,
+    pub args: Vec<String>,
+    /// Variables set on top of the inherited environment.
+    pub env: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<PathBuf>,
+    /// User the process runs as, on Unix.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    /// Lines written to stdin once the process starts, for one-shot queries.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub stdin: Vec<String>,
+}
+
+impl CliInvocation {
+    /// Replace the values of environment variables, MCP server headers and
+    /// env, and inline settings env with `"<redacted>"` (`REDACTED`), except
+    /// for the names in `NON_SECRET_NAMES`.
+    ///
+    /// Other arguments, such as the prompts and MCP server URLs, are kept as
+    /// given and may still contain secrets.
+    pub fn redacted(mut self) -> Self {
+        redact_secrets(&mut self.env);
+        let mut args = self.args.iter_mut();
+        while let Some(arg) = args.next() {
+            if arg != "--mcp-config" && arg != "--settings" {
+                continue;
+            }
+            let Some(config) = args.next() else { break };
+            if let Ok(mut value) = serde_json::from_str::<Value>(config) {
+                let servers = value.get_mut("mcpServers").and_then(Value::as_object_mut);
+                for server in servers.into_iter().flat_map(|servers| servers.values_mut()) {
+                    for key in ["headers", "env"] {
+                        redact_entries(server.get_mut(key));
+                    }
+                }
+                redact_entries(value.get_mut("env"));
+                *config = value.to_string();
+            }
+        }
+        self
+    }
+}
+
+impl std::fmt::Display for CliInvocation {
+    /// Formats the invocation as a shell command line.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(ref cwd) = self.cwd {
+            write!(f, "cd {} && ", shell_quote(&cwd.to_string_lossy()))?;
+        }
+        for (key, value) in &self.env {
+            write!(f, "{}={} ", key, shell_quote(value))?;
+        }
+        write!(f, "{}", shell_quote(&self.program.to_string_lossy()))?;
+        for arg in &self.args {
+            write!(f, " {}", shell_quote(arg))?;
+        }
+        Ok(())
+    }
+}
+
+fn shell_quote(value: &str) -> String {
+    let plain = !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '/' | ',' | ':' | '=' | '+' | '@'));
+    if plain {
+        value.to_string()
+    } else {
+        format!("'{}'", value.replace('\'', "'\\''"))
+    }
+}
+
+/// The current user's home directory, from `HOME` (or `USERPROFILE` on
+/// Windows); `None` when unset rather than guessing.
+pub(crate) fn home_dir() -> Option<PathBuf> {
+    let home = std::env::var_os("HOME").filter(|home| !home.is_empty());
+    #[cfg(windows)]
+    let home = home.or_else(|| std::env::var_os("USERPROFILE").filter(|home| !home.is_empty()));
+    home.map(PathBuf::from)
+}
+
+/// Whether an environment variable or header value may hold a credential.
+fn is_secret_name(name: &str) -> bool {
+    !NON_SECRET_NAMES.iter().any(|safe| safe.eq_ignore_ascii_case(name))
+}
+
+fn redact_entries(entries: Option<&mut Value>) {
+    if let Some(Value::Object(entries)) = entries {
+        for (name, value) in entries.iter_mut() {
+            if is_secret_name(name) {
+                *value = Value::String(REDACTED.to_string());
+            }
+        }
+    }
+}
+
+fn redact_secrets(env: &mut BTreeMap<String, String>) {
+    for (name, value) in env.iter_mut() {
+        if is_secret_name(name) {
+            *value = REDACTED.to_string();
+        }
+    }
+}
+
 pub struct SubprocessCLITransport {
     cli_path: PathBuf,
     options: ClaudeAgentOptions,
@@ -29,11 +166,16 @@ pub struct SubprocessCLITransport {
     ready: bool,
     max_buffer_size: usize,
     message_rx: Option<mpsc::UnboundedReceiver<Result<Value>>>,
+    /// Inline `settings`, written out while the process runs.
+    settings_file: Option<tempfile::NamedTempFile>,
 }
 
 impl SubprocessCLITransport {
     pub fn new(options: ClaudeAgentOptions, is_streaming: bool) -> Result<Self> {
-        let cli_path = Self::find_cli()?;
+        let cli_path = match options.cli_path {
+            Some(ref path) => path.clone(),
+            None => Self::find_cli()?,
+        };
         let max_buffer_size = options.max_buffer_size.unwrap_or(DEFAULT_MAX_BUFFER_SIZE);
 
         Ok(Self {
@@ -45,6 +187,7 @@ impl SubprocessCLITransport {
             ready: false,
             max_buffer_size,
             message_rx: None,
+            settings_file: None,
         })
     }
 
@@ -53,21 +196,19 @@ impl SubprocessCLITransport {
         self
     }
 
-    fn find_cli() -> Result<PathBuf> {
+    pub(crate) fn find_cli() -> Result<PathBuf> {
         // Check if 'claude' is in PATH
         if let Ok(path) = which::which("claude") {
             return Ok(path);
         }
 
         // Check common installation locations
-        let home = std::env::var("HOME").unwrap_or_else(|_| String::from("/root"));
-        let locations = vec![
-            PathBuf::from(format!("{}/.npm-global/bin/claude", home)),
-            PathBuf::from("/usr/local/bin/claude"),
-            PathBuf::from(format!("{}/.local/bin/claude", home)),
-            PathBuf::from(format!("{}/node_modules/.bin/claude", home)),
-            PathBuf::from(format!("{}/.yarn/bin/claude", home)),
-        ];
+        let mut locations = vec![PathBuf::from("/usr/local/bin/claude")];
+        if let Some(home) = home_dir() {
+            for dir in [".npm-global/bin", ".local/bin", "node_modules/.bin", ".yarn/bin"] {
+                locations.push(home.join(dir).join("claude"));
+            }
+        }
 
         for path in locations {
             if path.exists() && path.is_file() {
@@ -80,9 +221,27 @@ impl SubprocessCLITransport {
         ))
     }
 
-    fn build_command(&self) -> Vec<String> {
+    /// The unredacted invocation `connect()` starts.
+    ///
+    /// Fails if a system prompt file cannot be read or its template rendered.
+    pub(crate) fn invocation(&self) -> Result<CliInvocation> {
+        let mut env: BTreeMap<String, String> =
+            self.options.env.iter().map(|(key, value)| (key.clone(), value.clone())).collect();
+        env.insert("CLAUDE_CODE_ENTRYPOINT".to_string(), "sdk-rust".to_string());
+        env.insert("CLAUDE_AGENT_SDK_VERSION".to_string(), SDK_VERSION.to_string());
+
+        Ok(CliInvocation {
+            program: self.cli_path.clone(),
+            args: self.build_args()?,
+            env,
+            cwd: self.options.cwd.clone(),
+            user: self.options.user.clone(),
+            stdin: Vec::new(),
+        })
+    }
+
+    fn build_args(&self) -> Result<Vec<String>> {
         let mut cmd = vec![
-            self.cli_path.to_string_lossy().to_string(),
             "--output-format".to_string(),
             "stream-json".to_string(),
             "--verbose".to_string(),
@@ -90,17 +249,14 @@ impl SubprocessCLITransport {
 
         // System prompt
         if let Some(ref system_prompt) = self.options.system_prompt {
-            match system_prompt {
-                SystemPrompt::Text(text) => {
-                    cmd.push("--system-prompt".to_string());
-                    cmd.push(text.clone());
-                }
-                SystemPrompt::Preset { preset: _, append } => {
-                    if let Some(append_text) = append {
-                        cmd.push("--append-system-prompt".to_string());
-                        cmd.push(append_text.clone());
-                    }
-                }
+            let resolved = system_prompt.resolve(&self.options.system_prompt_vars)?;
+            if let Some(text) = resolved.replace {
+                cmd.push("--system-prompt".to_string());
+                cmd.push(text);
+            }
+            if let Some(text) = resolved.append {
+                cmd.push("--append-system-prompt".to_string());
+                cmd.push(text);
             }
         }
 
@@ -128,16 +284,38 @@ impl SubprocessCLITransport {
             cmd.push(model.clone());
         }
 
-        // Permission prompt tool
+        // Fallback model
+        if let Some(ref fallback_model) = self.options.fallback_model {
+            cmd.push("--fallback-model".to_string());
+            cmd.push(fallback_model.clone());
+        }
+
+        // Extended thinking
+        if let Some(max_thinking_tokens) = self.options.max_thinking_tokens {
+            cmd.push("--max-thinking-tokens".to_string());
+            cmd.push(max_thinking_tokens.to_string());
+        }
+
+        // Structured output
+        if let Some(ref schema) = self.options.json_schema {
+            cmd.push("--json-schema".to_string());
+            cmd.push(schema.to_string());
+        }
+
+        // Permission prompt tool; can_use_tool answers prompts over the control protocol, which
+        // needs stdin to stay open, so one-shot queries keep the CLI's own permission handling
         if let Some(ref tool_name) = self.options.permission_prompt_tool_name {
             cmd.push("--permission-prompt-tool".to_string());
             cmd.push(tool_name.clone());
+        } else if self.options.can_use_tool.is_some() && self.is_streaming {
+            cmd.push("--permission-prompt-tool".to_string());
+            cmd.push("stdio".to_string());
         }
 
         // Permission mode
         if let Some(ref mode) = self.options.permission_mode {
             cmd.push("--permission-mode".to_string());
-            cmd.push(mode.clone());
+            cmd.push(mode.to_string());
         }
 
         // Continue conversation
@@ -151,10 +329,14 @@ impl SubprocessCLITransport {
             cmd.push(resume.clone());
         }
 
-        // Settings
+        // Settings; inline settings are passed as JSON until connect() writes them to a file
         if let Some(ref settings) = self.options.settings {
             cmd.push("--settings".to_string());
-            cmd.push(settings.clone());
+            cmd.push(match (settings, &self.settings_file) {
+                (SettingsArg::File(path), _) => path.to_string_lossy().to_string(),
+                (SettingsArg::Inline(_), Some(file)) => file.path().to_string_lossy().to_string(),
+                (SettingsArg::Inline(settings), None) => serde_json::to_string(settings)?,
+            });
         }
 
         // Add directories
@@ -219,7 +401,7 @@ impl SubprocessCLITransport {
             cmd.push(String::new()); // Placeholder, actual prompt via stdin
         }
 
-        cmd
+        Ok(cmd)
     }
 
     fn build_mcp_config(&self) -> HashMap<String, Value> {
@@ -251,7 +433,7 @@ impl SubprocessCLITransport {
 
     fn spawn_stderr_handler(
         stderr: Option<tokio::process::ChildStderr>,
-        callback: Option<Arc<dyn Fn(String) + Send + Sync>>,
+        callback: Option<StderrCallback>,
     ) {
         if let Some(stderr) = stderr {
             tokio::spawn(async move {
@@ -274,11 +456,14 @@ impl Transport for SubprocessCLITransport {
             return Ok(());
         }
 
-        let cmd_args = self.build_command();
-        debug!("Starting Claude CLI: {:?}", cmd_args);
+        if let Some(SettingsArg::Inline(ref settings)) = self.options.settings {
+            self.settings_file = Some(settings.write_temp_file()?);
+        }
+        let invocation = self.invocation()?;
+        debug!("Starting Claude CLI: {}", invocation.clone().redacted());
 
-        let mut command = Command::new(&cmd_args[0]);
-        command.args(&cmd_args[1..]);
+        let mut command = Command::new(&invocation.program);
+        command.args(&invocation.args);
         command.stdin(Stdio::piped());
         command.stdout(Stdio::piped());
 
@@ -290,25 +475,18 @@ impl Transport for SubprocessCLITransport {
         }
 
         // Set working directory
-        if let Some(ref cwd) = self.options.cwd {
+        if let Some(ref cwd) = invocation.cwd {
             command.current_dir(cwd);
         }
 
         // Set environment variables
-        let mut env_vars = self.options.env.clone();
-        env_vars.insert("CLAUDE_CODE_ENTRYPOINT".to_string(), "sdk-rust".to_string());
-        env_vars.insert("CLAUDE_AGENT_SDK_VERSION".to_string(), SDK_VERSION.to_string());
-
-        for (key, value) in env_vars {
-            command.env(key, value);
-        }
+        command.envs(&invocation.env);
 
         // Set user if specified
         #[cfg(unix)]
         if let Some(ref user) = self.options.user {
             use users::get_user_by_name;
             if let Some(user_info) = get_user_by_name(user) {
-                use std::os::unix::process::CommandExt;
                 command.uid(user_info.uid());
             }
         }
@@ -370,7 +548,7 @@ impl Transport for SubprocessCLITransport {
         }
     }
 
-    fn read_messages(&mut self) -> Pin<Box<dyn Stream<Item = Result<Value>> + Send + '_>> {
+    fn read_messages(&mut self) -> Pin<Box<dyn Stream<Item = Result<Value>> + Send + 'static>> {
         if let Some(rx) = self.message_rx.take() {
             Box::pin(tokio_stream::wrappers::UnboundedReceiverStream::new(rx))
         } else {
@@ -391,6 +569,7 @@ impl Transport for SubprocessCLITransport {
             let _ = process.kill().await;
             let _ = process.wait().await;
         }
+        self.settings_file = None;
 
         Ok(())
     }
@@ -423,10 +602,10 @@ impl SubprocessCLITransport {
             json_buffer.extend_from_slice(line_trimmed.as_bytes());
 
             if json_buffer.len() > max_buffer_size {
-                let err = ClaudeSDKError::JSONDecode(serde_json::Error::io(std::io::Error::new(
-                    std::io::ErrorKind::Other,
-                    format!("JSON buffer exceeded {} bytes", max_buffer_size),
-                )));
+                let err = ClaudeSDKError::JSONDecode(serde_json::Error::io(std::io::Error::other(format!(
+                    "JSON buffer exceeded {} bytes",
+                    max_buffer_size
+                ))));
                 let _ = tx.send(Err(err));
                 json_buffer.clear();
                 continue;
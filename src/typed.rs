@@ -0,0 +1,164 @@
+//! Structured output deserialized into Rust types.
+//!
+//! `query_typed::<T>()` derives a JSON schema from `T`, passes it to the CLI
+//! as `json_schema`, and deserializes the turn's `structured_output` into `T`.
+//! When that fails, the validation error is sent back in the same session so
+//! the agent can correct its answer.
+
+use crate::budget::SpendTotals;
+use crate::client::ClaudeSDKClient;
+use crate::errors::{ClaudeSDKError, Result};
+use crate::types::{ClaudeAgentOptions, TurnResult};
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+/// Retries `query_typed` makes after the first answer fails to deserialize.
+pub const DEFAULT_MAX_RETRIES: u32 = 2;
+
+/// A value deserialized from structured output, with the cost of getting it.
+#[derive(Debug, Clone)]
+pub struct TypedResult<T> {
+    pub value: T,
+    /// Turns it took, including retries; at least 1.
+    pub attempts: u32,
+    pub session_id: String,
+    /// Cost and tokens for the whole session, including failed attempts.
+    pub spend: SpendTotals,
+    /// Every turn, the last one being the one `value` came from.
+    pub turns: Vec<TurnResult>,
+}
+
+/// The JSON schema `query_typed` sends for `T`.
+pub fn schema_for<T: JsonSchema>() -> Value {
+    schemars::schema_for!(T).to_value()
+}
+
+/// Ask for output matching `T`, retrying up to `DEFAULT_MAX_RETRIES` times.
+///
+/// ```no_run
+/// use claude_agent_sdk::{query_typed, ClaudeAgentOptions};
+/// use schemars::JsonSchema;
+/// use serde::Deserialize;
+///
+/// #[derive(Debug, Deserialize, JsonSchema)]
+/// struct Capital {
+///     city: String,
+///     population: u64,
+/// }
+///
+/// # async fn run() -> claude_agent_sdk::Result<()> {
+/// let answer = query_typed::<Capital>("What is the capital of Japan?", ClaudeAgentOptions::default()).await?;
+/// println!("{} ({} people), ${:.4}", answer.value.city, answer.value.population, answer.spend.total_cost_usd);
+/// # Ok(())
+/// # }
+/// ```
+pub async fn query_typed<T>(prompt: impl Into<String>, options: ClaudeAgentOptions) -> Result<TypedResult<T>>
+where
+    T: DeserializeOwned + JsonSchema,
+{
+    query_typed_with_retries(prompt, options, DEFAULT_MAX_RETRIES).await
+}
+
+/// Like `query_typed`, with the number of retries after the first attempt.
+///
+/// Each retry is a follow-up turn in the same session that quotes the
+/// validation error. Fails with `MessageParse`, carrying the last output, when
+/// no attempt deserializes, and with `Process` when a turn ends in an error.
+pub async fn query_typed_with_retries<T>(
+    prompt: impl Into<String>,
+    mut options: ClaudeAgentOptions,
+    max_retries: u32,
+) -> Result<TypedResult<T>>
+where
+    T: DeserializeOwned + JsonSchema,
+{
+    options.json_schema = Some(schema_for::<T>());
+    let mut client = ClaudeSDKClient::new(options);
+    let result = run_attempts(&mut client, prompt.into(), max_retries).await;
+    let _ = client.disconnect().await;
+    result
+}
+
+async fn run_attempts<T: DeserializeOwned>(
+    client: &mut ClaudeSDKClient,
+    prompt: String,
+    max_retries: u32,
+) -> Result<TypedResult<T>> {
+    client.connect().await?;
+
+    let mut turns = Vec::new();
+    let mut prompt = prompt;
+    loop {
+        client.query(prompt).await?;
+        let turn = client.receive_turn().await?;
+        if turn.is_error {
+            return Err(ClaudeSDKError::process(
+                format!("Claude Code ended the turn with {}", turn.subtype),
+                None,
+                turn.result.clone(),
+            ));
+        }
+
+        let output = structured_output(&turn);
+        let session_id = turn.session_id.clone();
+        turns.push(turn);
+        let attempts = turns.len() as u32;
+
+        let error = match output {
+            Some(output) => match deserialize::<T>(&output) {
+                Ok(value) => {
+                    return Ok(TypedResult {
+                        value,
+                        attempts,
+                        session_id,
+                        spend: client.spend(),
+                        turns,
+                    })
+                }
+                Err(error) => (error, Some(output)),
+            },
+            None => ("the turn ended without structured output".to_string(), None),
+        };
+
+        if attempts > max_retries {
+            return Err(ClaudeSDKError::message_parse(
+                format!(
+                    "Structured output did not match {} after {} attempt{}: {}",
+                    std::any::type_name::<T>(),
+                    attempts,
+                    if attempts == 1 { "" } else { "s" },
+                    error.0
+                ),
+                error.1,
+            ));
+        }
+        prompt = format!(
+            "Your output did not match the required JSON schema: {}. \
+             Answer again with output that matches the schema exactly.",
+            error.0
+        );
+    }
+}
+
+/// The turn's `structured_output`, or JSON in its result text for CLIs that
+/// answer in text.
+fn structured_output(turn: &TurnResult) -> Option<Value> {
+    if let Some(ref output) = turn.structured_output {
+        return Some(output.clone());
+    }
+    let text = turn.result.as_deref()?.trim();
+    let text = text
+        .strip_prefix("```json")
+        .or_else(|| text.strip_prefix("```"))
+        .and_then(|fenced| fenced.strip_suffix("```"))
+        .unwrap_or(text);
+    serde_json::from_str(text.trim()).ok()
+}
+
+fn deserialize<T: DeserializeOwned>(output: &Value) -> std::result::Result<T, String> {
+    serde_path_to_error::deserialize(output).map_err(|e| match e.path().to_string().as_str() {
+        "." => e.inner().to_string(),
+        path => format!("{}: {}", path, e.inner()),
+    })
+}
@@ -4,14 +4,107 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 
-// Permission modes
-pub type PermissionMode = String;
+/// Defines an enum serialized as a plain string, keeping values this SDK does
+/// not know about in an `Other` variant so newer CLI values still round-trip.
+macro_rules! string_enum {
+    (
+        $(#[$meta:meta])*
+        pub enum $name:ident {
+            $($(#[$variant_meta:meta])* $variant:ident => $value:literal,)+
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $($(#[$variant_meta])* $variant,)+
+            /// A value not known to this version of the SDK.
+            ///
+            /// Construct through `From<&str>` so known values map to their variant.
+            Other(String),
+        }
+
+        impl $name {
+            /// The string sent over the wire for this value.
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $(Self::$variant => $value,)+
+                    Self::Other(value) => value,
+                }
+            }
+        }
+
+        impl From<&str> for $name {
+            fn from(value: &str) -> Self {
+                match value {
+                    $($value => Self::$variant,)+
+                    other => Self::Other(other.to_string()),
+                }
+            }
+        }
+
+        impl From<String> for $name {
+            fn from(value: String) -> Self {
+                match value.as_str() {
+                    $($value => Self::$variant,)+
+                    _ => Self::Other(value),
+                }
+            }
+        }
+
+        impl std::str::FromStr for $name {
+            type Err = std::convert::Infallible;
+
+            fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+                Ok(value.into())
+            }
+        }
+
+        impl PartialEq<str> for $name {
+            fn eq(&self, other: &str) -> bool {
+                self.as_str() == other
+            }
+        }
+
+        impl PartialEq<&str> for $name {
+            fn eq(&self, other: &&str) -> bool {
+                self.as_str() == *other
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.as_str())
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+                String::deserialize(deserializer).map(Self::from)
+            }
+        }
+    };
+}
+
+pub(crate) use string_enum;
 
-pub const PERMISSION_MODE_DEFAULT: &str = "default";
-pub const PERMISSION_MODE_ACCEPT_EDITS: &str = "acceptEdits";
-pub const PERMISSION_MODE_PLAN: &str = "plan";
-pub const PERMISSION_MODE_BYPASS: &str = "bypassPermissions";
+// Permission modes
+string_enum! {
+    /// How the CLI asks for permission before using tools.
+    pub enum PermissionMode {
+        Default => "default",
+        AcceptEdits => "acceptEdits",
+        Plan => "plan",
+        BypassPermissions => "bypassPermissions",
+    }
+}
 
 // Setting sources
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -23,21 +116,193 @@ pub enum SettingSource {
 }
 
 // System prompt preset
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(tag = "type")]
+/// The system prompt for a session.
+///
+/// `Text` and `File` replace the CLI's default prompt; `Preset` and
+/// `AppendFile` keep the default and add to it. Files are read when the CLI is
+/// started. Prompt text can contain `{var}` placeholders, filled from
+/// `ClaudeAgentOptions::system_prompt_vars` when that map is not empty.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "SystemPromptRepr", into = "SystemPromptRepr")]
 pub enum SystemPrompt {
-    #[serde(rename = "preset")]
+    /// A named preset, one of `SYSTEM_PROMPT_PRESETS`, with optional text appended.
+    Preset { preset: String, append: Option<String> },
+    Text(String),
+    /// Replace the default prompt with the contents of a file.
+    File(PathBuf),
+    /// Append the contents of a file to the default prompt.
+    AppendFile(PathBuf),
+}
+
+/// Preset system prompts built into the CLI.
+pub const SYSTEM_PROMPT_PRESETS: &[&str] = &["claude_code"];
+
+/// Serialized form of `SystemPrompt`: plain text, or an object tagged by `type`.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum SystemPromptRepr {
     Preset {
         preset: String,
-        #[serde(skip_serializing_if = "Option::is_none")]
+        #[serde(default, skip_serializing_if = "Option::is_none")]
         append: Option<String>,
     },
+    File {
+        path: PathBuf,
+    },
+    AppendFile {
+        path: PathBuf,
+    },
     #[serde(untagged)]
     Text(String),
 }
 
+impl From<SystemPromptRepr> for SystemPrompt {
+    fn from(repr: SystemPromptRepr) -> Self {
+        match repr {
+            SystemPromptRepr::Preset { preset, append } => Self::Preset { preset, append },
+            SystemPromptRepr::File { path } => Self::File(path),
+            SystemPromptRepr::AppendFile { path } => Self::AppendFile(path),
+            SystemPromptRepr::Text(text) => Self::Text(text),
+        }
+    }
+}
+
+impl From<SystemPrompt> for SystemPromptRepr {
+    fn from(prompt: SystemPrompt) -> Self {
+        match prompt {
+            SystemPrompt::Preset { preset, append } => Self::Preset { preset, append },
+            SystemPrompt::File(path) => Self::File { path },
+            SystemPrompt::AppendFile(path) => Self::AppendFile { path },
+            SystemPrompt::Text(text) => Self::Text(text),
+        }
+    }
+}
+
+/// A system prompt as passed to the CLI, with files read and templates rendered.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResolvedSystemPrompt {
+    /// Replaces the default prompt (`--system-prompt`).
+    pub replace: Option<String>,
+    /// Appended to the default prompt (`--append-system-prompt`).
+    pub append: Option<String>,
+}
+
+impl SystemPrompt {
+    /// Read any prompt file and render `{var}` placeholders from `vars`.
+    ///
+    /// Templates are only rendered when `vars` is not empty. Fails with
+    /// `InvalidConfig` for an unknown preset, an unreadable or empty file, or
+    /// a placeholder missing from `vars`.
+    pub fn resolve(&self, vars: &HashMap<String, String>) -> crate::errors::Result<ResolvedSystemPrompt> {
+        let render = |text: String| {
+            if vars.is_empty() {
+                Ok(text)
+            } else {
+                render_template(&text, vars)
+            }
+        };
+        Ok(match self {
+            Self::Preset { preset, append } => {
+                if !SYSTEM_PROMPT_PRESETS.contains(&preset.as_str()) {
+                    return Err(crate::errors::ClaudeSDKError::invalid_config(format!(
+                        "unknown system prompt preset '{}', expected one of: {}",
+                        preset,
+                        SYSTEM_PROMPT_PRESETS.join(", ")
+                    )));
+                }
+                ResolvedSystemPrompt {
+                    replace: None,
+                    append: append.clone().map(render).transpose()?,
+                }
+            }
+            Self::Text(text) => ResolvedSystemPrompt {
+                replace: Some(render(text.clone())?),
+                append: None,
+            },
+            Self::File(path) => ResolvedSystemPrompt {
+                replace: Some(render(read_prompt_file(path)?)?),
+                append: None,
+            },
+            Self::AppendFile(path) => ResolvedSystemPrompt {
+                replace: None,
+                append: Some(render(read_prompt_file(path)?)?),
+            },
+        })
+    }
+}
+
+fn read_prompt_file(path: &std::path::Path) -> crate::errors::Result<String> {
+    let text = std::fs::read_to_string(path).map_err(|e| {
+        crate::errors::ClaudeSDKError::invalid_config(format!("system prompt file {}: {}", path.display(), e))
+    })?;
+    if text.trim().is_empty() {
+        return Err(crate::errors::ClaudeSDKError::invalid_config(format!(
+            "system prompt file {} is empty",
+            path.display()
+        )));
+    }
+    Ok(text)
+}
+
+/// Replace `{name}` placeholders with values from `vars`.
+///
+/// Names are identifiers (letters, digits and `_`, not starting with a
+/// digit); other braces, such as those in JSON examples, are left as they
+/// are, and `{{` and `}}` produce literal braces. Every placeholder without a
+/// value is reported in the error.
+pub fn render_template(template: &str, vars: &HashMap<String, String>) -> crate::errors::Result<String> {
+    let mut rendered = String::with_capacity(template.len());
+    let mut missing: Vec<&str> = Vec::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find(['{', '}']) {
+        rendered.push_str(&rest[..start]);
+        let tail = &rest[start..];
+        if tail.starts_with("{{") || tail.starts_with("}}") {
+            rendered.push_str(&tail[..1]);
+            rest = &tail[2..];
+            continue;
+        }
+        let placeholder = tail.strip_prefix('{').and_then(|after| {
+            let end = after.find('}')?;
+            let name = &after[..end];
+            let is_identifier = name.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+                && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+            is_identifier.then_some(name)
+        });
+        match placeholder {
+            Some(name) => {
+                match vars.get(name) {
+                    Some(value) => rendered.push_str(value),
+                    None => {
+                        if !missing.contains(&name) {
+                            missing.push(name);
+                        }
+                    }
+                }
+                rest = &tail[name.len() + 2..];
+            }
+            None => {
+                rendered.push_str(&tail[..1]);
+                rest = &tail[1..];
+            }
+        }
+    }
+    rendered.push_str(rest);
+
+    if missing.is_empty() {
+        Ok(rendered)
+    } else {
+        Err(crate::errors::ClaudeSDKError::invalid_config(format!(
+            "no value for template variable{} {}",
+            if missing.len() == 1 { "" } else { "s" },
+            missing.iter().map(|name| format!("{{{}}}", name)).collect::<Vec<_>>().join(", ")
+        )))
+    }
+}
+
 // Agent definition
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AgentDefinition {
     pub description: String,
     pub prompt: String,
@@ -48,55 +313,80 @@ pub struct AgentDefinition {
 }
 
 // Permission types
-pub type PermissionUpdateDestination = String;
-pub type PermissionBehavior = String;
+string_enum! {
+    /// Where a permission update is persisted.
+    pub enum PermissionUpdateDestination {
+        UserSettings => "userSettings",
+        ProjectSettings => "projectSettings",
+        LocalSettings => "localSettings",
+        Session => "session",
+    }
+}
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+string_enum! {
+    /// Whether a permission rule allows, denies or asks about a tool.
+    pub enum PermissionBehavior {
+        Allow => "allow",
+        Deny => "deny",
+        Ask => "ask",
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct PermissionRuleValue {
+    #[serde(alias = "tool_name")]
     pub tool_name: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, alias = "rule_content", skip_serializing_if = "Option::is_none")]
     pub rule_content: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "camelCase")]
 pub enum PermissionUpdate {
     AddRules {
         rules: Vec<PermissionRuleValue>,
+        behavior: PermissionBehavior,
         #[serde(skip_serializing_if = "Option::is_none")]
-        destination: Option<String>,
+        destination: Option<PermissionUpdateDestination>,
     },
     ReplaceRules {
         rules: Vec<PermissionRuleValue>,
+        behavior: PermissionBehavior,
         #[serde(skip_serializing_if = "Option::is_none")]
-        destination: Option<String>,
+        destination: Option<PermissionUpdateDestination>,
     },
     RemoveRules {
         rules: Vec<PermissionRuleValue>,
+        behavior: PermissionBehavior,
         #[serde(skip_serializing_if = "Option::is_none")]
-        destination: Option<String>,
+        destination: Option<PermissionUpdateDestination>,
     },
     SetMode {
         mode: PermissionMode,
         #[serde(skip_serializing_if = "Option::is_none")]
-        destination: Option<String>,
+        destination: Option<PermissionUpdateDestination>,
     },
     AddDirectories {
         directories: Vec<String>,
         #[serde(skip_serializing_if = "Option::is_none")]
-        destination: Option<String>,
+        destination: Option<PermissionUpdateDestination>,
     },
     RemoveDirectories {
         directories: Vec<String>,
         #[serde(skip_serializing_if = "Option::is_none")]
-        destination: Option<String>,
+        destination: Option<PermissionUpdateDestination>,
     },
 }
 
 // Tool permission types
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct ToolPermissionContext {
+    /// Rule changes the CLI suggests offering, such as "always allow".
     pub suggestions: Vec<PermissionUpdate>,
+    /// The path outside the allowed directories that triggered the prompt.
+    pub blocked_path: Option<String>,
+    pub tool_use_id: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -112,24 +402,183 @@ pub enum PermissionResult {
 }
 
 // Hook types
-pub type HookEvent = String;
+string_enum! {
+    /// Points in the agent loop where hooks can run.
+    pub enum HookEvent {
+        PreToolUse => "PreToolUse",
+        PostToolUse => "PostToolUse",
+        UserPromptSubmit => "UserPromptSubmit",
+        Stop => "Stop",
+        SubagentStop => "SubagentStop",
+        PreCompact => "PreCompact",
+    }
+}
 
-pub const HOOK_PRE_TOOL_USE: &str = "PreToolUse";
-pub const HOOK_POST_TOOL_USE: &str = "PostToolUse";
-pub const HOOK_USER_PROMPT_SUBMIT: &str = "UserPromptSubmit";
-pub const HOOK_STOP: &str = "Stop";
-pub const HOOK_SUBAGENT_STOP: &str = "SubagentStop";
-pub const HOOK_PRE_COMPACT: &str = "PreCompact";
+/// Fields the CLI sends with every hook input.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BaseHookInput {
+    pub session_id: String,
+    pub transcript_path: String,
+    pub cwd: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub permission_mode: Option<PermissionMode>,
+}
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PreToolUseHookInput {
+    #[serde(flatten)]
+    pub base: BaseHookInput,
+    pub tool_name: String,
+    pub tool_input: serde_json::Value,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PostToolUseHookInput {
+    #[serde(flatten)]
+    pub base: BaseHookInput,
+    pub tool_name: String,
+    pub tool_input: serde_json::Value,
+    pub tool_response: serde_json::Value,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UserPromptSubmitHookInput {
+    #[serde(flatten)]
+    pub base: BaseHookInput,
+    pub prompt: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StopHookInput {
+    #[serde(flatten)]
+    pub base: BaseHookInput,
+    #[serde(default)]
+    pub stop_hook_active: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SubagentStopHookInput {
+    #[serde(flatten)]
+    pub base: BaseHookInput,
+    #[serde(default)]
+    pub stop_hook_active: bool,
+}
+
+string_enum! {
+    /// What started a compaction.
+    pub enum CompactTrigger {
+        Manual => "manual",
+        Auto => "auto",
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PreCompactHookInput {
+    #[serde(flatten)]
+    pub base: BaseHookInput,
+    pub trigger: CompactTrigger,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub custom_instructions: Option<String>,
+}
+
+/// Hook input for any event, tagged by `hook_event_name`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "hook_event_name")]
+pub enum HookInput {
+    PreToolUse(PreToolUseHookInput),
+    PostToolUse(PostToolUseHookInput),
+    UserPromptSubmit(UserPromptSubmitHookInput),
+    Stop(StopHookInput),
+    SubagentStop(SubagentStopHookInput),
+    PreCompact(PreCompactHookInput),
+}
+
+/// A typed hook input, tied to the event that produces it.
+pub trait TypedHookInput: serde::de::DeserializeOwned + Send + 'static {
+    /// The event whose hooks receive this input.
+    fn event() -> HookEvent;
+}
+
+impl TypedHookInput for PreToolUseHookInput {
+    fn event() -> HookEvent {
+        HookEvent::PreToolUse
+    }
+}
+
+impl TypedHookInput for PostToolUseHookInput {
+    fn event() -> HookEvent {
+        HookEvent::PostToolUse
+    }
+}
+
+impl TypedHookInput for UserPromptSubmitHookInput {
+    fn event() -> HookEvent {
+        HookEvent::UserPromptSubmit
+    }
+}
+
+impl TypedHookInput for StopHookInput {
+    fn event() -> HookEvent {
+        HookEvent::Stop
+    }
+}
+
+impl TypedHookInput for SubagentStopHookInput {
+    fn event() -> HookEvent {
+        HookEvent::SubagentStop
+    }
+}
+
+impl TypedHookInput for PreCompactHookInput {
+    fn event() -> HookEvent {
+        HookEvent::PreCompact
+    }
+}
+
+/// Event-specific hook output, tagged by `hookEventName`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "hookEventName")]
+pub enum HookSpecificOutput {
+    #[serde(rename_all = "camelCase")]
+    PreToolUse {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        permission_decision: Option<PermissionBehavior>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        permission_decision_reason: Option<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    PostToolUse {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        additional_context: Option<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    UserPromptSubmit {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        additional_context: Option<String>,
+    },
+}
+
+/// Output returned from a hook callback.
+///
+/// `continue_` is sent as `continue`; setting it to `false` stops the agent,
+/// with `stop_reason` shown to the user.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct HookJSONOutput {
+    #[serde(rename = "continue", skip_serializing_if = "Option::is_none")]
+    pub continue_: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suppress_output: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop_reason: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub decision: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub system_message: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub hook_specific_output: Option<serde_json::Value>,
+    pub reason: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hook_specific_output: Option<HookSpecificOutput>,
 }
 
 #[derive(Debug, Clone)]
@@ -138,13 +587,39 @@ pub struct HookContext {
 }
 
 // Hook callback type
-pub type HookCallback = Box<
+pub type HookCallback = Arc<
     dyn Fn(serde_json::Value, Option<String>, HookContext) -> futures::future::BoxFuture<'static, HookJSONOutput>
         + Send
         + Sync,
 >;
 
+/// Wrap a closure taking a typed input as a `HookCallback`.
+///
+/// Inputs that fail to parse are logged and answered with an empty output,
+/// which lets the CLI proceed as if the hook had not run.
+pub fn typed_hook<I, F, Fut>(callback: F) -> HookCallback
+where
+    I: TypedHookInput,
+    F: Fn(I, Option<String>, HookContext) -> Fut + Send + Sync + 'static,
+    Fut: std::future::Future<Output = HookJSONOutput> + Send + 'static,
+{
+    let callback = Arc::new(callback);
+    Arc::new(move |input, tool_use_id, context| {
+        let callback = callback.clone();
+        Box::pin(async move {
+            match serde_json::from_value::<I>(input) {
+                Ok(input) => callback(input, tool_use_id, context).await,
+                Err(e) => {
+                    tracing::warn!("Ignoring {} hook with unexpected input: {}", I::event(), e);
+                    HookJSONOutput::default()
+                }
+            }
+        })
+    })
+}
+
 // Hook matcher
+#[derive(Clone)]
 pub struct HookMatcher {
     pub matcher: Option<String>,
     pub hooks: Vec<HookCallback>,
@@ -159,16 +634,6 @@ impl std::fmt::Debug for HookMatcher {
     }
 }
 
-impl Clone for HookMatcher {
-    fn clone(&self) -> Self {
-        // Hooks cannot be cloned, so we create a new matcher without hooks
-        Self {
-            matcher: self.matcher.clone(),
-            hooks: Vec::new(),
-        }
-    }
-}
-
 // MCP Server configurations
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
@@ -203,13 +668,19 @@ pub enum McpServerConfig {
 }
 
 // Content block types
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(tag = "type")]
+// Only unrecognised `type` values become `Unknown`, as for `Message`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", remote = "Self")]
 pub enum ContentBlock {
     #[serde(rename = "text")]
     Text { text: String },
     #[serde(rename = "thinking")]
-    Thinking { thinking: String, signature: String },
+    Thinking {
+        thinking: String,
+        /// Empty in the `content_block_start` event; filled in by a signature delta.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        signature: Option<String>,
+    },
     #[serde(rename = "tool_use")]
     ToolUse {
         id: String,
@@ -224,35 +695,308 @@ pub enum ContentBlock {
         #[serde(skip_serializing_if = "Option::is_none")]
         is_error: Option<bool>,
     },
+    /// Thinking that was flagged by safety systems, returned encrypted.
+    #[serde(rename = "redacted_thinking")]
+    RedactedThinking { data: String },
+    /// A call to a tool the API runs itself, such as web search.
+    #[serde(rename = "server_tool_use")]
+    ServerToolUse {
+        id: String,
+        name: String,
+        input: serde_json::Value,
+    },
+    #[serde(rename = "web_search_tool_result")]
+    WebSearchToolResult {
+        tool_use_id: String,
+        content: serde_json::Value,
+    },
+    #[serde(rename = "image")]
+    Image { source: ImageSource },
+    /// A block type not known to this version of the SDK, kept as received.
+    ///
+    /// Blocks of a known type that fail to parse are an error, not `Unknown`.
+    #[serde(untagged, skip_deserializing)]
+    Unknown {
+        #[serde(flatten)]
+        raw: serde_json::Value,
+    },
+}
+
+impl ContentBlock {
+    /// The `type` values with a variant of their own.
+    const KNOWN_TYPES: &'static [&'static str] = &[
+        "text",
+        "thinking",
+        "tool_use",
+        "tool_result",
+        "redacted_thinking",
+        "server_tool_use",
+        "web_search_tool_result",
+        "image",
+    ];
+}
+
+impl Serialize for ContentBlock {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        ContentBlock::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ContentBlock {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        deserialize_known_type(deserializer, Self::KNOWN_TYPES, ContentBlock::deserialize, |raw| {
+            ContentBlock::Unknown { raw }
+        })
+    }
+}
+
+/// Where an image block's data comes from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", remote = "Self")]
+pub enum ImageSource {
+    Base64 { media_type: String, data: String },
+    Url { url: String },
+    /// A source type not known to this version of the SDK, kept as received.
+    #[serde(untagged, skip_deserializing)]
+    Unknown {
+        #[serde(flatten)]
+        raw: serde_json::Value,
+    },
+}
+
+impl ImageSource {
+    /// The `type` values with a variant of their own.
+    const KNOWN_TYPES: &'static [&'static str] = &["base64", "url"];
+}
+
+impl Serialize for ImageSource {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        ImageSource::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ImageSource {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        deserialize_known_type(deserializer, Self::KNOWN_TYPES, ImageSource::deserialize, |raw| {
+            ImageSource::Unknown { raw }
+        })
+    }
+}
+
+/// Deserialize a `type`-tagged value with `derived` when its `type` is one of
+/// `known`, and keep it as received with `unknown` otherwise, so that a
+/// malformed value of a known type is an error rather than silently unknown.
+fn deserialize_known_type<'de, D, T>(
+    deserializer: D,
+    known: &[&str],
+    derived: impl FnOnce(serde_json::Value) -> serde_json::Result<T>,
+    unknown: impl FnOnce(serde_json::Value) -> T,
+) -> std::result::Result<T, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let raw = serde_json::Value::deserialize(deserializer)?;
+    match raw.get("type").and_then(|t| t.as_str()) {
+        Some(value_type) if known.contains(&value_type) => derived(raw).map_err(serde::de::Error::custom),
+        _ => Ok(unknown(raw)),
+    }
+}
+
+// Usage types
+/// Token usage as reported by the API.
+///
+/// Counts that were not sent are `None`. Explicit nulls are kept in `extra` as
+/// for `AssistantMessageInner`, so usage serializes back as received.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(remote = "Self")]
+pub struct Usage {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_tokens: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_tokens: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_creation_input_tokens: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_read_input_tokens: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_tool_use: Option<ServerToolUse>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub service_tier: Option<String>,
+    /// Fields this SDK does not model, such as the `cache_creation` breakdown.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Usage {
+    /// All tokens processed, including cache reads and writes, with missing
+    /// counts read as zero.
+    pub fn total_tokens(&self) -> u64 {
+        [
+            self.input_tokens,
+            self.output_tokens,
+            self.cache_creation_input_tokens,
+            self.cache_read_input_tokens,
+        ]
+        .into_iter()
+        .flatten()
+        .sum()
+    }
+}
+
+impl Serialize for Usage {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        Usage::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Usage {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        deserialize_keeping_nulls(
+            deserializer,
+            &[
+                "input_tokens",
+                "output_tokens",
+                "cache_creation_input_tokens",
+                "cache_read_input_tokens",
+                "server_tool_use",
+                "service_tier",
+            ],
+            Usage::deserialize,
+            |usage| &mut usage.extra,
+        )
+    }
+}
+
+/// Server-side tool invocations counted in `Usage`, kept as sent like `Usage`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(remote = "Self")]
+pub struct ServerToolUse {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub web_search_requests: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub web_fetch_requests: Option<u64>,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Serialize for ServerToolUse {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        ServerToolUse::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ServerToolUse {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        deserialize_keeping_nulls(
+            deserializer,
+            &["web_search_requests", "web_fetch_requests"],
+            ServerToolUse::deserialize,
+            |usage| &mut usage.extra,
+        )
+    }
+}
+
+/// Usage and cost for one model, from the Result message's `modelUsage`,
+/// kept as sent like `Usage`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", remote = "Self")]
+pub struct ModelUsage {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_tokens: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_tokens: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_read_input_tokens: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_creation_input_tokens: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub web_search_requests: Option<u64>,
+    #[serde(default, rename = "costUSD", skip_serializing_if = "Option::is_none")]
+    pub cost_usd: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_window: Option<u64>,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Serialize for ModelUsage {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        ModelUsage::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ModelUsage {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        deserialize_keeping_nulls(
+            deserializer,
+            &[
+                "inputTokens",
+                "outputTokens",
+                "cacheReadInputTokens",
+                "cacheCreationInputTokens",
+                "webSearchRequests",
+                "costUSD",
+                "contextWindow",
+            ],
+            ModelUsage::deserialize,
+            |usage| &mut usage.extra,
+        )
+    }
+}
+
+fn null_as_zero<'de, D, T>(deserializer: D) -> std::result::Result<T, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Deserialize<'de> + Default,
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+string_enum! {
+    /// How a turn ended, from the Result message's `subtype`.
+    pub enum ResultSubtype {
+        Success => "success",
+        ErrorMaxTurns => "error_max_turns",
+        ErrorDuringExecution => "error_during_execution",
+    }
 }
 
 // Message types
+// `remote = "Self"` makes the derives inherent functions, wrapped by the impls
+// below so that only unrecognised `type` values become `Unknown`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(tag = "type")]
+#[serde(tag = "type", remote = "Self")]
 pub enum Message {
     #[serde(rename = "user")]
     User {
         #[serde(flatten)]
         message: UserMessageContent,
-        #[serde(skip_serializing_if = "Option::is_none")]
         parent_tool_use_id: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        session_id: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        uuid: Option<String>,
+        /// Fields this SDK does not model, kept so the message serializes as received.
+        #[serde(flatten)]
+        extra: serde_json::Map<String, serde_json::Value>,
     },
     #[serde(rename = "assistant")]
     Assistant {
         #[serde(flatten)]
         message: AssistantMessageContent,
-        #[serde(skip_serializing_if = "Option::is_none")]
         parent_tool_use_id: Option<String>,
-    },
-    #[serde(rename = "system")]
-    System {
-        subtype: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        session_id: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        uuid: Option<String>,
         #[serde(flatten)]
-        data: serde_json::Value,
+        extra: serde_json::Map<String, serde_json::Value>,
     },
+    #[serde(rename = "system")]
+    System(SystemMessage),
     #[serde(rename = "result")]
     Result {
-        subtype: String,
+        subtype: ResultSubtype,
         duration_ms: i64,
         duration_api_ms: i64,
         is_error: bool,
@@ -261,20 +1005,82 @@ pub enum Message {
         #[serde(skip_serializing_if = "Option::is_none")]
         total_cost_usd: Option<f64>,
         #[serde(skip_serializing_if = "Option::is_none")]
-        usage: Option<serde_json::Value>,
+        usage: Option<Usage>,
+        #[serde(rename = "modelUsage", skip_serializing_if = "Option::is_none")]
+        model_usage: Option<HashMap<String, ModelUsage>>,
         #[serde(skip_serializing_if = "Option::is_none")]
         result: Option<String>,
+        /// The final output as JSON, when `json_schema` was set.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        structured_output: Option<serde_json::Value>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        uuid: Option<String>,
+        #[serde(flatten)]
+        extra: serde_json::Map<String, serde_json::Value>,
     },
     #[serde(rename = "stream_event")]
     StreamEvent {
         uuid: String,
         session_id: String,
-        event: serde_json::Value,
-        #[serde(skip_serializing_if = "Option::is_none")]
+        event: StreamEvent,
         parent_tool_use_id: Option<String>,
+        #[serde(flatten)]
+        extra: serde_json::Map<String, serde_json::Value>,
+    },
+    /// Emitted by the SDK (not the CLI) after the CLI crashed and was respawned.
+    ///
+    /// Any turn in flight when the CLI exited is lost and must be re-sent.
+    #[serde(rename = "reconnected")]
+    Reconnected {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        session_id: Option<String>,
+        attempt: u32,
+    },
+    /// A message type not known to this version of the SDK, kept as received.
+    ///
+    /// Only produced when `strict_parsing` is off. Messages of a known type
+    /// that fail to parse are an error, not `Unknown`.
+    #[serde(untagged, skip_deserializing)]
+    Unknown {
+        #[serde(flatten)]
+        raw: serde_json::Value,
     },
 }
 
+impl Message {
+    /// The `type` values with a variant of their own.
+    const KNOWN_TYPES: &'static [&'static str] = &["user", "assistant", "system", "result", "stream_event", "reconnected"];
+
+    /// Optional Result fields; explicit nulls for these are kept in `extra`.
+    const RESULT_OPTIONAL_KEYS: &'static [&'static str] =
+        &["total_cost_usd", "usage", "modelUsage", "result", "structured_output", "uuid"];
+}
+
+impl Serialize for Message {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        Message::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Message {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        deserialize_known_type(
+            deserializer,
+            Self::KNOWN_TYPES,
+            |raw| match raw.get("type").and_then(|t| t.as_str()) {
+                Some("result") => deserialize_keeping_nulls(raw, Self::RESULT_OPTIONAL_KEYS, Message::deserialize, |message| {
+                    match message {
+                        Message::Result { extra, .. } => extra,
+                        _ => unreachable!("a result line deserializes to Message::Result"),
+                    }
+                }),
+                _ => Message::deserialize(raw),
+            },
+            |raw| Message::Unknown { raw },
+        )
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserMessageContent {
     pub message: UserMessageInner,
@@ -285,6 +1091,8 @@ pub struct UserMessageInner {
     pub role: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub content: Option<serde_json::Value>,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -292,17 +1100,438 @@ pub struct AssistantMessageContent {
     pub message: AssistantMessageInner,
 }
 
+/// The API message inside an assistant message.
+///
+/// Optional fields sent as explicit nulls are kept in `extra`, so the message
+/// serializes back with the same keys it was received with.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(remote = "Self")]
 pub struct AssistantMessageInner {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
     pub content: Vec<ContentBlock>,
     pub model: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop_reason: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop_sequence: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<Usage>,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Serialize for AssistantMessageInner {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        AssistantMessageInner::serialize(self, serializer)
+    }
 }
 
+impl<'de> Deserialize<'de> for AssistantMessageInner {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        deserialize_keeping_nulls(
+            deserializer,
+            &["id", "stop_reason", "stop_sequence", "usage"],
+            AssistantMessageInner::deserialize,
+            |message| &mut message.extra,
+        )
+    }
+}
+
+// Stream events
+/// A raw Anthropic API streaming event, sent with `include_partial_messages`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StreamEvent {
+    MessageStart {
+        message: StreamMessageStart,
+    },
+    ContentBlockStart {
+        index: usize,
+        content_block: ContentBlock,
+    },
+    ContentBlockDelta {
+        index: usize,
+        delta: ContentBlockDelta,
+    },
+    ContentBlockStop {
+        index: usize,
+    },
+    MessageDelta {
+        #[serde(default)]
+        delta: StreamMessageDelta,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        usage: Option<DeltaUsage>,
+    },
+    MessageStop,
+    /// An event type not known to this SDK, or a known one with an unexpected shape.
+    #[serde(untagged)]
+    Unknown(serde_json::Value),
+}
+
+/// The `message` of a `message_start` event.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StreamMessageStart {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub usage: Usage,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// The `delta` of a `message_delta` event. Explicit nulls are kept in `extra`
+/// as for `AssistantMessageInner`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(remote = "Self")]
+pub struct StreamMessageDelta {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop_reason: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop_sequence: Option<String>,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Serialize for StreamMessageDelta {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        StreamMessageDelta::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for StreamMessageDelta {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        deserialize_keeping_nulls(
+            deserializer,
+            &["stop_reason", "stop_sequence"],
+            StreamMessageDelta::deserialize,
+            |delta| &mut delta.extra,
+        )
+    }
+}
+
+/// Deserialize with `derived`, then put back the `keys` that were sent as
+/// explicit nulls into `extra`, since their `None` fields are not serialized.
+fn deserialize_keeping_nulls<'de, D, T>(
+    deserializer: D,
+    keys: &[&str],
+    derived: impl FnOnce(serde_json::Value) -> serde_json::Result<T>,
+    extra: impl FnOnce(&mut T) -> &mut serde_json::Map<String, serde_json::Value>,
+) -> std::result::Result<T, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value = serde_json::Value::deserialize(deserializer)?;
+    let nulls: Vec<String> = keys
+        .iter()
+        .filter(|key| value.get(**key).is_some_and(|v| v.is_null()))
+        .map(|key| key.to_string())
+        .collect();
+    let mut parsed = derived(value).map_err(serde::de::Error::custom)?;
+    let extra = extra(&mut parsed);
+    for key in nulls {
+        extra.insert(key, serde_json::Value::Null);
+    }
+    Ok(parsed)
+}
+
+/// The usage of a `message_delta` event.
+///
+/// Only `output_tokens` is always present; it is cumulative for the message.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeltaUsage {
+    #[serde(default, deserialize_with = "null_as_zero")]
+    pub output_tokens: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_tokens: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_creation_input_tokens: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_read_input_tokens: Option<u64>,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// An increment to one content block.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContentBlockDelta {
+    TextDelta { text: String },
+    ThinkingDelta { thinking: String },
+    /// A fragment of a tool call's input JSON; fragments are only valid JSON once joined.
+    InputJsonDelta { partial_json: String },
+    SignatureDelta { signature: String },
+    #[serde(untagged)]
+    Unknown(serde_json::Value),
+}
+
+// System messages
+/// A `system` message, typed by its `subtype`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "subtype")]
+pub enum SystemMessage {
+    /// Sent when the CLI starts a session, before the first assistant message.
+    #[serde(rename = "init")]
+    Init(Box<SystemInit>),
+    /// Sent when the conversation history was compacted.
+    #[serde(rename = "compact_boundary")]
+    CompactBoundary(CompactBoundary),
+    /// Any other subtype, or a known one that did not match its expected shape.
+    ///
+    /// `data` holds every field other than `type` and `subtype`.
+    #[serde(untagged)]
+    Other {
+        subtype: String,
+        #[serde(flatten)]
+        data: serde_json::Value,
+    },
+}
+
+impl SystemMessage {
+    /// The wire `subtype` of this message.
+    pub fn subtype(&self) -> &str {
+        match self {
+            Self::Init(_) => "init",
+            Self::CompactBoundary(_) => "compact_boundary",
+            Self::Other { subtype, .. } => subtype,
+        }
+    }
+}
+
+/// Session details reported by the CLI's `init` system message.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SystemInit {
+    pub session_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uuid: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(default, rename = "permissionMode", skip_serializing_if = "Option::is_none")]
+    pub permission_mode: Option<PermissionMode>,
+    #[serde(default)]
+    pub tools: Vec<String>,
+    #[serde(default)]
+    pub mcp_servers: Vec<McpServerStatus>,
+    #[serde(default)]
+    pub slash_commands: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agents: Option<Vec<String>>,
+    #[serde(default, rename = "apiKeySource", skip_serializing_if = "Option::is_none")]
+    pub api_key_source: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_style: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub claude_code_version: Option<String>,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl SystemInit {
+    /// Look up the connection status of an MCP server by name.
+    pub fn mcp_server(&self, name: &str) -> Option<&McpServerStatus> {
+        self.mcp_servers.iter().find(|server| server.name == name)
+    }
+
+    /// Whether `slash_commands` lists `name`, with or without a leading `/`.
+    pub fn has_slash_command(&self, name: &str) -> bool {
+        let name = name.strip_prefix('/').unwrap_or(name);
+        self.slash_commands.iter().any(|command| command == name)
+    }
+}
+
+string_enum! {
+    /// Connection state of an MCP server.
+    pub enum McpConnectionStatus {
+        Connected => "connected",
+        Failed => "failed",
+        NeedsAuth => "needs-auth",
+        Pending => "pending",
+        /// Turned off with `ClaudeSDKClient::toggle_mcp_server`.
+        Disabled => "disabled",
+    }
+}
+
+/// An MCP server as listed in the `init` message or by
+/// `ClaudeSDKClient::mcp_server_status`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct McpServerStatus {
+    pub name: String,
+    pub status: McpConnectionStatus,
+    /// The name and version the server reported when it connected.
+    #[serde(default, rename = "serverInfo", skip_serializing_if = "Option::is_none")]
+    pub server_info: Option<McpServerInfo>,
+    /// Why the server failed to connect.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct McpServerInfo {
+    pub name: String,
+    pub version: String,
+}
+
+/// Details of a `compact_boundary` system message.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompactBoundary {
+    pub session_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uuid: Option<String>,
+    pub compact_metadata: CompactMetadata,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompactMetadata {
+    pub trigger: CompactTrigger,
+    #[serde(default)]
+    pub pre_tokens: u64,
+}
+
+// Turn results
+/// Summary of one completed turn: the messages received and its Result.
+#[derive(Debug, Clone)]
+pub struct TurnResult {
+    pub messages: Vec<Message>,
+    /// Concatenated text blocks from the assistant messages.
+    pub text: String,
+    pub subtype: ResultSubtype,
+    pub is_error: bool,
+    pub num_turns: i32,
+    pub duration_ms: i64,
+    pub session_id: String,
+    pub total_cost_usd: Option<f64>,
+    pub usage: Option<Usage>,
+    pub model_usage: Option<HashMap<String, ModelUsage>>,
+    pub result: Option<String>,
+    /// The final output as JSON, when `json_schema` was set.
+    pub structured_output: Option<serde_json::Value>,
+}
+
+impl TurnResult {
+    /// Build a turn result from its messages, or `None` if they contain no Result.
+    pub fn from_messages(messages: Vec<Message>) -> Option<Self> {
+        let mut text = String::new();
+        for message in &messages {
+            if let Message::Assistant { message, .. } = message {
+                for block in &message.message.content {
+                    if let ContentBlock::Text { text: t } = block {
+                        text.push_str(t);
+                    }
+                }
+            }
+        }
+
+        let Some(Message::Result {
+            subtype,
+            is_error,
+            num_turns,
+            duration_ms,
+            session_id,
+            total_cost_usd,
+            usage,
+            model_usage,
+            result,
+            structured_output,
+            ..
+        }) = messages.iter().rev().find(|m| matches!(m, Message::Result { .. })).cloned()
+        else {
+            return None;
+        };
+
+        Some(Self {
+            messages,
+            text,
+            subtype,
+            is_error,
+            num_turns,
+            duration_ms,
+            session_id,
+            total_cost_usd,
+            usage,
+            model_usage,
+            result,
+            structured_output,
+        })
+    }
+}
+
+// Crash recovery
+/// Policy for respawning the CLI when it exits unexpectedly mid-session.
+///
+/// The CLI is restarted with `resume` set to the last known session ID, and
+/// waits between attempts grow exponentially from `initial_backoff` up to
+/// `max_backoff`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RecoveryPolicy {
+    pub max_retries: u32,
+    #[serde(rename = "initial_backoff_ms", with = "duration_ms")]
+    pub initial_backoff: Duration,
+    #[serde(rename = "max_backoff_ms", with = "duration_ms")]
+    pub max_backoff: Duration,
+}
+
+/// Serializes a `Duration` as whole milliseconds, as written in config files.
+mod duration_ms {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(duration.as_millis().try_into().unwrap_or(u64::MAX))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        u64::deserialize(deserializer).map(Duration::from_millis)
+    }
+}
+
+impl RecoveryPolicy {
+    /// Delay before the given reconnect attempt (1-based).
+    pub fn backoff_for(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial_backoff.saturating_mul(factor).min(self.max_backoff)
+    }
+}
+
+impl Default for RecoveryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(10),
+        }
+    }
+}
+
+// Callback types
+pub type StderrCallback = Arc<dyn Fn(String) + Send + Sync>;
+
+pub type CanUseToolCallback = Arc<
+    dyn Fn(String, serde_json::Value, ToolPermissionContext) -> futures::future::BoxFuture<'static, PermissionResult>
+        + Send
+        + Sync,
+>;
+
 // Claude Agent Options
-#[derive(Clone)]
+/// Options for a query or client session.
+///
+/// Everything except the callbacks and hooks can be read from and written to
+/// config files; see `ClaudeAgentOptions::from_file`.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct ClaudeAgentOptions {
     pub allowed_tools: Vec<String>,
     pub system_prompt: Option<SystemPrompt>,
+    /// Values for `{var}` placeholders in the system prompt.
+    pub system_prompt_vars: HashMap<String, String>,
     pub mcp_servers: HashMap<String, McpServerConfig>,
     pub permission_mode: Option<PermissionMode>,
     pub continue_conversation: bool,
@@ -312,56 +1541,76 @@ pub struct ClaudeAgentOptions {
     pub model: Option<String>,
     pub permission_prompt_tool_name: Option<String>,
     pub cwd: Option<PathBuf>,
-    pub settings: Option<String>,
+    /// Settings layered over the `setting_sources` files for this session.
+    pub settings: Option<crate::settings::SettingsArg>,
     pub add_dirs: Vec<PathBuf>,
     pub env: HashMap<String, String>,
+    #[serde(deserialize_with = "flag_values")]
     pub extra_args: HashMap<String, Option<String>>,
     pub max_buffer_size: Option<usize>,
-    pub stderr_callback: Option<Arc<dyn Fn(String) + Send + Sync>>,
-    pub can_use_tool: Option<
-        Arc<
-            dyn Fn(String, serde_json::Value, ToolPermissionContext) -> futures::future::BoxFuture<'static, PermissionResult>
-                + Send
-                + Sync,
-        >,
-    >,
+    #[serde(skip)]
+    pub stderr_callback: Option<StderrCallback>,
+    /// Decides on tool permission prompts. Only sessions that keep stdin open,
+    /// such as `ClaudeSDKClient`, can answer them; one-shot `query()` leaves
+    /// prompts to the CLI's own permission handling.
+    #[serde(skip)]
+    pub can_use_tool: Option<CanUseToolCallback>,
+    #[serde(skip)]
     pub hooks: HashMap<HookEvent, Vec<HookMatcher>>,
     pub user: Option<String>,
     pub include_partial_messages: bool,
     pub fork_session: bool,
     pub agents: HashMap<String, AgentDefinition>,
     pub setting_sources: Option<Vec<SettingSource>>,
+    pub cli_path: Option<PathBuf>,
+    pub recovery_policy: Option<RecoveryPolicy>,
+    /// Cost limit for the session, in USD.
+    ///
+    /// Cost is only reported in Result messages, so this is enforced between
+    /// turns and cannot stop a turn partway: the turn that crosses it runs to
+    /// completion, then its Result is replaced by `BudgetExceeded`. Use
+    /// `max_total_tokens` to interrupt a runaway turn.
+    pub max_budget_usd: Option<f64>,
+    /// Token limit for the session, counting input, output, cache creation and
+    /// cache read tokens. Checked client-side as usage is reported, so it can be
+    /// overshot by up to one streamed message (a whole turn without
+    /// `include_partial_messages`).
+    pub max_total_tokens: Option<u64>,
+    /// Budget for extended thinking, in tokens.
+    pub max_thinking_tokens: Option<u32>,
+    /// Model to switch to when the primary `model` is overloaded.
+    pub fallback_model: Option<String>,
+    /// JSON schema the final output must match; the result is reported in
+    /// the Result message's `structured_output`.
+    pub json_schema: Option<serde_json::Value>,
+    /// Fail on message and content block types this SDK does not know,
+    /// instead of yielding them as `Unknown`.
+    pub strict_parsing: bool,
 }
 
-impl Default for ClaudeAgentOptions {
-    fn default() -> Self {
-        Self {
-            allowed_tools: Vec::new(),
-            system_prompt: None,
-            mcp_servers: HashMap::new(),
-            permission_mode: None,
-            continue_conversation: false,
-            resume: None,
-            max_turns: None,
-            disallowed_tools: Vec::new(),
-            model: None,
-            permission_prompt_tool_name: None,
-            cwd: None,
-            settings: None,
-            add_dirs: Vec::new(),
-            env: HashMap::new(),
-            extra_args: HashMap::new(),
-            max_buffer_size: None,
-            stderr_callback: None,
-            can_use_tool: None,
-            hooks: HashMap::new(),
-            user: None,
-            include_partial_messages: false,
-            fork_session: false,
-            agents: HashMap::new(),
-            setting_sources: None,
-        }
+/// Reads `extra_args` values, where `null` or `true` stand for a flag without a
+/// value since TOML has no null.
+fn flag_values<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<HashMap<String, Option<String>>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum FlagValue {
+        Value(String),
+        Present(Option<bool>),
     }
+
+    HashMap::<String, FlagValue>::deserialize(deserializer)?
+        .into_iter()
+        .map(|(flag, value)| match value {
+            FlagValue::Value(value) => Ok((flag, Some(value))),
+            FlagValue::Present(None | Some(true)) => Ok((flag, None)),
+            FlagValue::Present(Some(false)) => Err(serde::de::Error::custom(format!(
+                "flag '{}' must be a string, true or null",
+                flag
+            ))),
+        })
+        .collect()
 }
 
 impl std::fmt::Debug for ClaudeAgentOptions {
@@ -369,6 +1618,7 @@ impl std::fmt::Debug for ClaudeAgentOptions {
         f.debug_struct("ClaudeAgentOptions")
             .field("allowed_tools", &self.allowed_tools)
             .field("system_prompt", &self.system_prompt)
+            .field("system_prompt_vars", &self.system_prompt_vars)
             .field("mcp_servers", &self.mcp_servers)
             .field("permission_mode", &self.permission_mode)
             .field("continue_conversation", &self.continue_conversation)
@@ -391,10 +1641,38 @@ impl std::fmt::Debug for ClaudeAgentOptions {
             .field("fork_session", &self.fork_session)
             .field("agents", &self.agents)
             .field("setting_sources", &self.setting_sources)
+            .field("cli_path", &self.cli_path)
+            .field("recovery_policy", &self.recovery_policy)
+            .field("max_budget_usd", &self.max_budget_usd)
+            .field("max_total_tokens", &self.max_total_tokens)
+            .field("max_thinking_tokens", &self.max_thinking_tokens)
+            .field("fallback_model", &self.fallback_model)
+            .field("json_schema", &self.json_schema)
+            .field("strict_parsing", &self.strict_parsing)
             .finish()
     }
 }
 
+impl ClaudeAgentOptions {
+    /// Register a hook whose closure receives the typed input for its event.
+    ///
+    /// The event is taken from the input type, so
+    /// `|input: PreToolUseHookInput, tool_use_id, ctx| async move { .. }`
+    /// registers a PreToolUse hook. `matcher` filters by tool name.
+    pub fn add_hook<I, F, Fut>(&mut self, matcher: Option<&str>, callback: F) -> &mut Self
+    where
+        I: TypedHookInput,
+        F: Fn(I, Option<String>, HookContext) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = HookJSONOutput> + Send + 'static,
+    {
+        self.hooks.entry(I::event()).or_default().push(HookMatcher {
+            matcher: matcher.map(str::to_string),
+            hooks: vec![typed_hook(callback)],
+        });
+        self
+    }
+}
+
 // SDK Control Protocol types
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
@@ -426,7 +1704,7 @@ pub enum SDKControlRequestType {
         hooks: Option<serde_json::Value>,
     },
     #[serde(rename = "set_permission_mode")]
-    SetPermissionMode { mode: String },
+    SetPermissionMode { mode: PermissionMode },
     #[serde(rename = "hook_callback")]
     HookCallback {
         callback_id: String,
@@ -0,0 +1,247 @@
+#!/usr/bin/env bash
+# Minimal stand-in for the Claude Code CLI, speaking the stream-json protocol.
+#
+# It acknowledges every control request and answers each user message with an
+# init message (first turn only), an assistant text block and a result. The
+# assistant text reports how the session was started so tests can assert on it.
+# Like the real CLI, results report the cumulative cost of the process and the
+# usage of the turn alone: each turn costs $0.001 and uses 10 input plus 5
+# output tokens. With
+# --include-partial-messages, message_start/message_delta stream events carrying
+# that usage precede the assistant message.
+#
+# The init message lists "compact" plus a slash command for each
+# .claude/commands/*.md file in the working directory. When a prompt starts with
+# "/", the assistant text ends with "command=<the prompt>".
+#
+# set_model changes the model reported in later turns. mcp_status reports one
+# server, "fake-mcp", which mcp_toggle disables and enables and mcp_reconnect
+# reconnects; other server names are an error.
+#
+# If the initialize request registers hooks, each turn first sends a
+# hook_callback request for the first callback ID with a UserPromptSubmit input,
+# and the assistant text ends with "hook=<the SDK's control response>".
+#
+# Environment knobs:
+#   FAKE_CLAUDE_CRASH_ON_TURN=N    exit with status 1 on turn N of a fresh session
+#   FAKE_CLAUDE_CRASH_ON_RESUME=1  exit with status 1 immediately when resuming
+#   FAKE_CLAUDE_TURN_DELAY=SECS    sleep before answering each user message
+#   FAKE_CLAUDE_LOG=PATH           append "start PID" / "end PID" around each turn
+#   FAKE_CLAUDE_EXTRA_MESSAGE=JSON emit this line before each assistant message
+#   FAKE_CLAUDE_STRUCTURED_OUTPUT=JSON
+#                                  with --json-schema, report this as the result's
+#                                  structured_output (default {"ok":true})
+#   FAKE_CLAUDE_STRUCTURED_OUTPUT_N=JSON
+#                                  the same for turn N only, overriding the above
+#   FAKE_CLAUDE_ARGS_LOG=PATH      write the command-line arguments here, one per line
+#   FAKE_CLAUDE_PLAN=TEXT          in plan mode with --permission-prompt-tool stdio,
+#                                  ask to use ExitPlanMode with this plan, appending
+#                                  each denial's message to it, up to 3 times; the
+#                                  assistant text ends with "mode=<mode> executed=<plan>"
+#   FAKE_CLAUDE_PERMISSION_PATHS="A B"
+#                                  with --permission-prompt-tool stdio, ask to use
+#                                  Write on each path at once, then wait for every
+#                                  answer; the assistant text ends with
+#                                  "permissions=A:<behavior>,B:<behavior>"
+#   FAKE_CLAUDE_UNSUPPORTED="A B"  answer control requests with these subtypes
+#                                  with an "Unsupported control request subtype" error
+
+if [ -n "$FAKE_CLAUDE_ARGS_LOG" ]; then
+    printf '%s\n' "$@" > "$FAKE_CLAUDE_ARGS_LOG"
+fi
+
+resume=""
+fork="false"
+model="none"
+fallback="none"
+thinking="none"
+schema=""
+partial="false"
+mode="default"
+prompt_tool=""
+while [ $# -gt 0 ]; do
+    case "$1" in
+        --resume) resume="$2"; shift ;;
+        --fork-session) fork="true" ;;
+        --model) model="$2"; shift ;;
+        --fallback-model) fallback="$2"; shift ;;
+        --max-thinking-tokens) thinking="$2"; shift ;;
+        --json-schema) schema="$2"; shift ;;
+        --include-partial-messages) partial="true" ;;
+        --permission-mode) mode="$2"; shift ;;
+        --permission-prompt-tool) prompt_tool="$2"; shift ;;
+    esac
+    shift
+done
+
+if [ -n "$resume" ] && [ -n "$FAKE_CLAUDE_CRASH_ON_RESUME" ]; then
+    exit 1
+fi
+
+if [ -n "$resume" ] && [ "$fork" = "false" ]; then
+    session_id="$resume"
+else
+    session_id="session-$$-$RANDOM"
+fi
+
+turn=0
+hook_id=""
+mcp_state="connected"
+while IFS= read -r line; do
+    case "$line" in
+        *'"type":"control_request"'*)
+            request_id=$(printf '%s' "$line" | sed -n 's/.*"request_id":"\([^"]*\)".*/\1/p')
+            subtype=$(printf '%s' "$line" | sed -n 's/.*"subtype":"\([^"]*\)".*/\1/p')
+            server=$(printf '%s' "$line" | sed -n 's/.*"serverName":"\([^"]*\)".*/\1/p')
+            response='{}'
+            error=""
+            case " $FAKE_CLAUDE_UNSUPPORTED " in
+                *" $subtype "*) error="Unsupported control request subtype: $subtype" ;;
+            esac
+            case "$error:$subtype" in
+                :initialize)
+                    hook_id=$(printf '%s' "$line" | sed -n 's/.*"hookCallbackIds":\["\([^"]*\)".*/\1/p')
+                    ;;
+                :set_permission_mode)
+                    mode=$(printf '%s' "$line" | sed -n 's/.*"mode":"\([^"]*\)".*/\1/p')
+                    ;;
+                :set_model)
+                    model=$(printf '%s' "$line" | sed -n 's/.*"model":"\([^"]*\)".*/\1/p')
+                    model="${model:-default}"
+                    ;;
+                :mcp_status)
+                    response="{\"mcpServers\":[{\"name\":\"fake-mcp\",\"status\":\"$mcp_state\",\"serverInfo\":{\"name\":\"fake\",\"version\":\"1.0.0\"}}]}"
+                    ;;
+                :mcp_reconnect | :mcp_toggle)
+                    if [ "$server" != "fake-mcp" ]; then
+                        error="Server not found: $server"
+                    else
+                        case "$line" in
+                            *'"enabled":false'*) mcp_state="disabled" ;;
+                            *) mcp_state="connected" ;;
+                        esac
+                    fi
+                    ;;
+            esac
+            if [ -n "$error" ]; then
+                printf '{"type":"control_response","response":{"subtype":"error","request_id":"%s","error":"%s"}}\n' "$request_id" "$error"
+            else
+                printf '{"type":"control_response","response":{"subtype":"success","request_id":"%s","response":%s}}\n' "$request_id" "$response"
+            fi
+            ;;
+        *'"type":"user"'*)
+            turn=$((turn + 1))
+            if [ -z "$resume" ] && [ "$turn" = "$FAKE_CLAUDE_CRASH_ON_TURN" ]; then
+                exit 1
+            fi
+            if [ -n "$FAKE_CLAUDE_LOG" ]; then
+                echo "start $$" >> "$FAKE_CLAUDE_LOG"
+            fi
+            if [ -n "$FAKE_CLAUDE_TURN_DELAY" ]; then
+                sleep "$FAKE_CLAUDE_TURN_DELAY"
+            fi
+            hook=""
+            if [ -n "$hook_id" ]; then
+                prompt=$(printf '%s' "$line" | sed -n 's/.*"content":"\([^"]*\)".*/\1/p')
+                printf '{"type":"control_request","request_id":"hook_req_%s","request":{"subtype":"hook_callback","callback_id":"%s","tool_use_id":null,"input":{"hook_event_name":"UserPromptSubmit","session_id":"%s","transcript_path":"/tmp/transcript.jsonl","cwd":"%s","prompt":"%s"}}}\n' \
+                    "$turn" "$hook_id" "$session_id" "$PWD" "$prompt"
+                while IFS= read -r reply; do
+                    case "$reply" in
+                        *'"type":"control_response"'*) break ;;
+                    esac
+                done
+                hook=" hook=$(printf '%s' "$reply" | sed 's/\\/\\\\/g; s/"/\\"/g')"
+            fi
+            if [ "$turn" -eq 1 ]; then
+                commands='"compact"'
+                for file in .claude/commands/*.md; do
+                    if [ -f "$file" ]; then
+                        commands="$commands,\"$(basename "$file" .md)\""
+                    fi
+                done
+                printf '{"type":"system","subtype":"init","session_id":"%s","cwd":"%s","model":"fake-model","permissionMode":"default","tools":["Read"],"mcp_servers":[{"name":"fake-mcp","status":"connected"}],"slash_commands":[%s]}\n' \
+                    "$session_id" "$PWD" "$commands"
+            fi
+            content=$(printf '%s' "$line" | sed -n 's/.*"content":"\([^"]*\)".*/\1/p')
+            plan_note=""
+            if [ -n "$FAKE_CLAUDE_PLAN" ] && [ "$mode" = "plan" ] && [ "$prompt_tool" = "stdio" ]; then
+                plan="$FAKE_CLAUDE_PLAN"
+                attempt=0
+                while [ "$attempt" -lt 3 ] && [ "$mode" = "plan" ]; do
+                    attempt=$((attempt + 1))
+                    printf '{"type":"control_request","request_id":"perm_req_%s_%s","request":{"subtype":"can_use_tool","tool_name":"ExitPlanMode","input":{"plan":"%s"},"permission_suggestions":[]}}\n' \
+                        "$turn" "$attempt" "$plan"
+                    while IFS= read -r reply; do
+                        case "$reply" in
+                            *'"type":"control_response"'*) break ;;
+                        esac
+                    done
+                    case "$reply" in
+                        *'"behavior":"allow"'*)
+                            plan=$(printf '%s' "$reply" | sed -n 's/.*"plan":"\([^"]*\)".*/\1/p')
+                            new_mode=$(printf '%s' "$reply" | sed -n 's/.*"type":"setMode".*"mode":"\([^"]*\)".*/\1/p; s/.*"mode":"\([^"]*\)".*"type":"setMode".*/\1/p' | head -n 1)
+                            mode="${new_mode:-default}"
+                            plan_note=" executed=$plan"
+                            ;;
+                        *)
+                            feedback=$(printf '%s' "$reply" | sed -n 's/.*"message":"\([^"]*\)".*/\1/p')
+                            plan="$plan + $feedback"
+                            ;;
+                    esac
+                done
+                plan_note=" mode=$mode$plan_note"
+            fi
+            permissions_note=""
+            if [ -n "$FAKE_CLAUDE_PERMISSION_PATHS" ] && [ "$prompt_tool" = "stdio" ]; then
+                count=0
+                for path in $FAKE_CLAUDE_PERMISSION_PATHS; do
+                    count=$((count + 1))
+                    printf '{"type":"control_request","request_id":"write_req_%s_%s","request":{"subtype":"can_use_tool","tool_name":"Write","input":{"file_path":"%s","content":"x"},"tool_use_id":"toolu_%s_%s","blocked_path":"%s","permission_suggestions":[{"type":"addRules","rules":[{"toolName":"Write","ruleContent":"%s"}],"behavior":"allow","destination":"session"}]}}\n' \
+                        "$turn" "$count" "$path" "$turn" "$count" "$path" "$path"
+                done
+                answered=0
+                while [ "$answered" -lt "$count" ] && IFS= read -r reply; do
+                    case "$reply" in
+                        *'"type":"control_response"'*)
+                            answered=$((answered + 1))
+                            index=$(printf '%s' "$reply" | sed -n 's/.*"request_id":"write_req_[0-9]*_\([0-9]*\)".*/\1/p')
+                            behavior=$(printf '%s' "$reply" | sed -n 's/.*"behavior":"\([a-z]*\)".*/\1/p')
+                            eval "decision_$index=\$behavior"
+                            ;;
+                    esac
+                done
+                index=0
+                for path in $FAKE_CLAUDE_PERMISSION_PATHS; do
+                    index=$((index + 1))
+                    permissions_note="$permissions_note,$path:$(eval "printf '%s' \"\$decision_$index\"")"
+                done
+                permissions_note=" permissions=${permissions_note#,}"
+            fi
+            command=""
+            case "$content" in
+                /*) command=" command=$content" ;;
+            esac
+            if [ "$partial" = "true" ]; then
+                printf '{"type":"stream_event","uuid":"u%s-1","session_id":"%s","event":{"type":"message_start","message":{"usage":{"input_tokens":10,"output_tokens":1}}}}\n' \
+                    "$turn" "$session_id"
+                printf '{"type":"stream_event","uuid":"u%s-2","session_id":"%s","event":{"type":"message_delta","delta":{"stop_reason":"end_turn"},"usage":{"output_tokens":5}}}\n' \
+                    "$turn" "$session_id"
+            fi
+            if [ -n "$FAKE_CLAUDE_EXTRA_MESSAGE" ]; then
+                printf '%s\n' "$FAKE_CLAUDE_EXTRA_MESSAGE"
+            fi
+            printf '{"type":"assistant","message":{"model":"fake-model","content":[{"type":"text","text":"turn=%s resume=%s fork=%s model=%s fallback=%s thinking=%s cwd=%s%s%s%s%s"}]},"parent_tool_use_id":null,"session_id":"%s"}\n' \
+                "$turn" "${resume:-none}" "$fork" "$model" "$fallback" "$thinking" "$PWD" "$hook" "$command" "$plan_note" "$permissions_note" "$session_id"
+            if [ -n "$FAKE_CLAUDE_LOG" ]; then
+                echo "end $$" >> "$FAKE_CLAUDE_LOG"
+            fi
+            structured=""
+            if [ -n "$schema" ]; then
+                output=$(eval "printf '%s' \"\$FAKE_CLAUDE_STRUCTURED_OUTPUT_$turn\"")
+                structured=",\"structured_output\":${output:-${FAKE_CLAUDE_STRUCTURED_OUTPUT:-{\"ok\":true\}}}"
+            fi
+            printf '{"type":"result","subtype":"success","duration_ms":10,"duration_api_ms":5,"is_error":false,"num_turns":%s,"session_id":"%s","total_cost_usd":0.%03d,"usage":{"input_tokens":%s,"output_tokens":%s}%s}\n' \
+                "$turn" "$session_id" "$turn" 10 5 "$structured"
+            ;;
+    esac
+done
@@ -1,9 +1,8 @@
 //! Simple integration tests without full Query lifecycle
 //! These tests verify the components work together without spawning background tasks
 
-use claude_agent_sdk::errors::Result;
 use claude_agent_sdk::message_parser::parse_message;
-use claude_agent_sdk::types::{ClaudeAgentOptions, ContentBlock, Message};
+use claude_agent_sdk::types::{ClaudeAgentOptions, ContentBlock, Message, PermissionMode};
 use serde_json::json;
 
 #[test]
@@ -66,7 +65,7 @@ fn test_parse_complete_workflow() {
 fn test_options_with_tools() {
     let options = ClaudeAgentOptions {
         allowed_tools: vec!["Read".to_string(), "Write".to_string(), "Bash".to_string()],
-        permission_mode: Some("acceptEdits".to_string()),
+        permission_mode: Some(PermissionMode::AcceptEdits),
         max_turns: Some(10),
         ..Default::default()
     };
@@ -75,7 +74,7 @@ fn test_options_with_tools() {
     assert!(options.allowed_tools.contains(&"Read".to_string()));
     assert!(options.allowed_tools.contains(&"Write".to_string()));
     assert!(options.allowed_tools.contains(&"Bash".to_string()));
-    assert_eq!(options.permission_mode.as_ref().unwrap(), "acceptEdits");
+    assert_eq!(options.permission_mode, Some(PermissionMode::AcceptEdits));
 }
 
 #[test]
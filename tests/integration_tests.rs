@@ -3,32 +3,41 @@
 //! Note: These tests use mock transports and don't require the Claude CLI to be installed.
 //! For end-to-end tests with the actual CLI, see the examples directory.
 
-use claude_agent_sdk::errors::Result;
 use claude_agent_sdk::message_parser::parse_message;
 use claude_agent_sdk::query::Query;
-use claude_agent_sdk::types::{ClaudeAgentOptions, ContentBlock, Message};
+use claude_agent_sdk::types::{ClaudeAgentOptions, ContentBlock, Message, PermissionMode};
 use claude_agent_sdk::transport::Transport;
 use async_trait::async_trait;
 use futures::stream::{Stream, StreamExt};
 use serde_json::{json, Value};
 use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex as StdMutex};
+use tokio::sync::mpsc;
 
-// Include mock transport from test_transport
+// Include mock transport from test_transport, extended to acknowledge control requests
 pub struct MockTransport {
     messages: Arc<StdMutex<Vec<Value>>>,
     written_data: Arc<StdMutex<Vec<String>>>,
     connected: Arc<StdMutex<bool>>,
     ready: Arc<StdMutex<bool>>,
+    control_tx: mpsc::UnboundedSender<Value>,
+    control_rx: Option<mpsc::UnboundedReceiver<Value>>,
+    unanswered: Arc<AtomicUsize>,
 }
 
 impl MockTransport {
     pub fn new(messages: Vec<Value>) -> Self {
+        let (control_tx, control_rx) = mpsc::unbounded_channel();
         Self {
             messages: Arc::new(StdMutex::new(messages)),
             written_data: Arc::new(StdMutex::new(Vec::new())),
             connected: Arc::new(StdMutex::new(false)),
             ready: Arc::new(StdMutex::new(false)),
+            control_tx,
+            control_rx: Some(control_rx),
+            unanswered: Arc::new(AtomicUsize::new(0)),
         }
     }
 }
@@ -42,13 +51,45 @@ impl Transport for MockTransport {
     }
 
     async fn write(&mut self, data: String) -> claude_agent_sdk::errors::Result<()> {
+        if let Ok(value) = serde_json::from_str::<Value>(&data) {
+            if value["type"] == "control_request" {
+                self.unanswered.fetch_add(1, Ordering::SeqCst);
+                let _ = self.control_tx.send(json!({
+                    "type": "control_response",
+                    "response": {
+                        "subtype": "success",
+                        "request_id": value["request_id"],
+                        "response": {}
+                    }
+                }));
+            }
+        }
         self.written_data.lock().unwrap().push(data);
         Ok(())
     }
 
-    fn read_messages(&mut self) -> Pin<Box<dyn Stream<Item = claude_agent_sdk::errors::Result<Value>> + Send + '_>> {
-        let messages = self.messages.lock().unwrap().clone();
-        Box::pin(futures::stream::iter(messages.into_iter().map(Ok)))
+    fn read_messages(&mut self) -> Pin<Box<dyn Stream<Item = claude_agent_sdk::errors::Result<Value>> + Send + 'static>> {
+        let messages = self.messages.lock().unwrap().clone().into_iter();
+        let control_rx = self.control_rx.take().expect("read_messages called twice");
+        let unanswered = self.unanswered.clone();
+
+        // Control responses take priority; the stream ends once the script is
+        // exhausted and every control request has been answered.
+        Box::pin(futures::stream::unfold(
+            (messages, control_rx, unanswered),
+            |(mut messages, mut control_rx, unanswered)| async move {
+                let next = match control_rx.try_recv() {
+                    Ok(response) => Some(response),
+                    Err(_) => match messages.next() {
+                        Some(message) => return Some((Ok(message), (messages, control_rx, unanswered))),
+                        None if unanswered.load(Ordering::SeqCst) == 0 => None,
+                        None => control_rx.recv().await,
+                    },
+                }?;
+                unanswered.fetch_sub(1, Ordering::SeqCst);
+                Some((Ok(next), (messages, control_rx, unanswered)))
+            },
+        ))
     }
 
     async fn close(&mut self) -> claude_agent_sdk::errors::Result<()> {
@@ -351,13 +392,13 @@ async fn test_system_message_handling() {
 async fn test_options_configuration() {
     let options = ClaudeAgentOptions {
         allowed_tools: vec!["Read".to_string(), "Write".to_string()],
-        permission_mode: Some("acceptEdits".to_string()),
+        permission_mode: Some(PermissionMode::AcceptEdits),
         max_turns: Some(5),
         ..Default::default()
     };
 
     assert_eq!(options.allowed_tools.len(), 2);
-    assert_eq!(options.permission_mode.as_ref().unwrap(), "acceptEdits");
+    assert_eq!(options.permission_mode, Some(PermissionMode::AcceptEdits));
     assert_eq!(options.max_turns, Some(5));
 }
 
@@ -383,3 +424,75 @@ async fn test_concurrent_message_processing() {
     assert_eq!(results.len(), 4);
     assert!(results.iter().all(|r| r.is_ok()));
 }
+
+#[tokio::test]
+async fn test_permission_responses_use_cli_format() {
+    use claude_agent_sdk::types::{
+        CanUseToolCallback, PermissionResult, PermissionUpdate, PermissionUpdateDestination,
+    };
+
+    let request = |id: &str, tool: &str| {
+        json!({
+            "type": "control_request",
+            "request_id": id,
+            "request": {"subtype": "can_use_tool", "tool_name": tool, "input": {"command": "ls"}, "permission_suggestions": []}
+        })
+    };
+    let transport = MockTransport::new(vec![request("allow_plain", "Bash"), request("allow_mode", "Edit"), request("deny", "Write")]);
+    let written = transport.written_data.clone();
+    let mut boxed_transport = Box::new(transport) as Box<dyn claude_agent_sdk::transport::Transport>;
+    boxed_transport.connect().await.unwrap();
+
+    let callback: CanUseToolCallback = Arc::new(|tool_name, _input, _context| {
+        Box::pin(async move {
+            match tool_name.as_str() {
+                "Bash" => PermissionResult::Allow {
+                    updated_input: None,
+                    updated_permissions: None,
+                },
+                "Edit" => PermissionResult::Allow {
+                    updated_input: Some(json!({"command": "pwd"})),
+                    updated_permissions: Some(vec![PermissionUpdate::SetMode {
+                        mode: PermissionMode::AcceptEdits,
+                        destination: Some(PermissionUpdateDestination::Session),
+                    }]),
+                },
+                _ => PermissionResult::Deny {
+                    message: "read-only".to_string(),
+                    interrupt: true,
+                },
+            }
+        })
+    });
+    let mut query = Query::new(boxed_transport, true, Some(callback), None);
+    query.start().await.unwrap();
+    let _: Vec<_> = query.receive_messages().collect().await;
+
+    // Responses are written from spawned tasks, so wait for all three
+    let mut responses = HashMap::new();
+    for _ in 0..100 {
+        responses = written
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+            .filter(|value| value["type"] == "control_response")
+            .map(|value| (value["response"]["request_id"].as_str().unwrap().to_string(), value["response"]["response"].clone()))
+            .collect();
+        if responses.len() == 3 {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+
+    assert_eq!(responses["allow_plain"], json!({"behavior": "allow", "updatedInput": {"command": "ls"}}));
+    assert_eq!(
+        responses["allow_mode"],
+        json!({
+            "behavior": "allow",
+            "updatedInput": {"command": "pwd"},
+            "updatedPermissions": [{"type": "setMode", "mode": "acceptEdits", "destination": "session"}]
+        })
+    );
+    assert_eq!(responses["deny"], json!({"behavior": "deny", "message": "read-only", "interrupt": true}));
+}
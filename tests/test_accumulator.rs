@@ -0,0 +1,136 @@
+//! Tests for building partial assistant messages from stream events
+
+use claude_agent_sdk::message_parser::parse_message;
+use claude_agent_sdk::{ContentBlock, Message, MessageAccumulator, PartialContentBlock};
+use serde_json::{json, Value};
+
+fn stream_event(event: Value) -> Message {
+    parse_message(json!({
+        "type": "stream_event",
+        "uuid": "u1",
+        "session_id": "s1",
+        "event": event
+    }))
+    .unwrap()
+}
+
+fn push_all(acc: &mut MessageAccumulator, events: Vec<Value>) {
+    for event in events {
+        assert!(acc.push_message(&stream_event(event)));
+    }
+}
+
+#[test]
+fn test_accumulates_text_and_thinking() {
+    let mut acc = MessageAccumulator::new();
+    push_all(
+        &mut acc,
+        vec![
+            json!({"type": "message_start", "message": {"id": "msg_1", "model": "claude-sonnet-4-5", "usage": {"input_tokens": 12, "output_tokens": 1}}}),
+            json!({"type": "content_block_start", "index": 0, "content_block": {"type": "thinking", "thinking": ""}}),
+            json!({"type": "content_block_delta", "index": 0, "delta": {"type": "thinking_delta", "thinking": "Let me "}}),
+            json!({"type": "content_block_delta", "index": 0, "delta": {"type": "thinking_delta", "thinking": "think."}}),
+            json!({"type": "content_block_delta", "index": 0, "delta": {"type": "signature_delta", "signature": "sig"}}),
+            json!({"type": "content_block_stop", "index": 0}),
+            json!({"type": "content_block_start", "index": 1, "content_block": {"type": "text", "text": ""}}),
+            json!({"type": "content_block_delta", "index": 1, "delta": {"type": "text_delta", "text": "Hello, "}}),
+        ],
+    );
+
+    assert_eq!(acc.message_id(), Some("msg_1"));
+    assert_eq!(acc.model(), Some("claude-sonnet-4-5"));
+    assert_eq!(acc.text(), "Hello, ");
+    assert!(!acc.is_complete());
+
+    push_all(
+        &mut acc,
+        vec![
+            json!({"type": "content_block_delta", "index": 1, "delta": {"type": "text_delta", "text": "world"}}),
+            json!({"type": "content_block_stop", "index": 1}),
+            json!({"type": "message_delta", "delta": {"stop_reason": "end_turn"}, "usage": {"output_tokens": 9}}),
+            json!({"type": "message_stop"}),
+        ],
+    );
+
+    assert_eq!(acc.text(), "Hello, world");
+    assert_eq!(acc.stop_reason(), Some("end_turn"));
+    assert_eq!(acc.usage().input_tokens, Some(12));
+    assert_eq!(acc.usage().output_tokens, Some(9));
+    assert!(acc.is_complete());
+    assert_eq!(
+        acc.content(),
+        vec![
+            ContentBlock::Thinking {
+                thinking: "Let me think.".to_string(),
+                signature: Some("sig".to_string()),
+            },
+            ContentBlock::Text {
+                text: "Hello, world".to_string(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_tool_input_is_parsed_while_streaming() {
+    let mut acc = MessageAccumulator::new();
+    push_all(
+        &mut acc,
+        vec![
+            json!({"type": "message_start", "message": {"usage": {}}}),
+            json!({"type": "content_block_start", "index": 0, "content_block": {"type": "tool_use", "id": "tool_1", "name": "Read", "input": {}}}),
+        ],
+    );
+    assert_eq!(acc.blocks()[0].partial_input(), Some(json!({})));
+
+    let deltas = [r#"{"file_"#, r#"path": "/tmp/no"#, r#"tes.md", "limit""#, r#": 20}"#];
+    let expected = [
+        json!({}),
+        json!({"file_path": "/tmp/no"}),
+        json!({"file_path": "/tmp/notes.md"}),
+        json!({"file_path": "/tmp/notes.md", "limit": 20}),
+    ];
+    for (delta, expected) in deltas.iter().zip(expected) {
+        push_all(
+            &mut acc,
+            vec![json!({"type": "content_block_delta", "index": 0, "delta": {"type": "input_json_delta", "partial_json": delta}})],
+        );
+        assert_eq!(acc.blocks()[0].partial_input(), Some(expected));
+    }
+
+    match &acc.blocks()[0] {
+        PartialContentBlock::ToolUse { id, name, input_json } => {
+            assert_eq!(id, "tool_1");
+            assert_eq!(name, "Read");
+            assert_eq!(input_json, r#"{"file_path": "/tmp/notes.md", "limit": 20}"#);
+        }
+        other => panic!("Expected ToolUse block, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_message_start_resets_and_subagent_events_are_ignored() {
+    let mut acc = MessageAccumulator::new();
+    push_all(
+        &mut acc,
+        vec![
+            json!({"type": "message_start", "message": {"usage": {}}}),
+            json!({"type": "content_block_start", "index": 0, "content_block": {"type": "text", "text": "first"}}),
+        ],
+    );
+    assert_eq!(acc.text(), "first");
+
+    let subagent = parse_message(json!({
+        "type": "stream_event",
+        "uuid": "u2",
+        "session_id": "s1",
+        "parent_tool_use_id": "tool_1",
+        "event": {"type": "content_block_delta", "index": 0, "delta": {"type": "text_delta", "text": " nested"}}
+    }))
+    .unwrap();
+    assert!(!acc.push_message(&subagent));
+    assert_eq!(acc.text(), "first");
+
+    push_all(&mut acc, vec![json!({"type": "message_start", "message": {"usage": {}}})]);
+    assert!(acc.blocks().is_empty());
+}
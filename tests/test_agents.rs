@@ -0,0 +1,131 @@
+//! Tests for loading and writing markdown agent definitions
+
+use claude_agent_sdk::types::AgentDefinition;
+use claude_agent_sdk::{load_agents_dir, write_agents_dir, ClaudeAgentOptions, ClaudeSDKError};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+const REVIEWER: &str = "---
+name: code-reviewer
+description: \"Reviews code: style, bugs and tests\"
+tools: Read, Grep, Glob
+model: sonnet
+color: blue
+---
+
+You are a careful code reviewer.
+
+Point out bugs before style.
+";
+
+fn error_message(error: ClaudeSDKError) -> String {
+    match error {
+        ClaudeSDKError::InvalidConfig(message) => message,
+        other => panic!("expected InvalidConfig, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_parse_agent_markdown() {
+    let (name, agent) = AgentDefinition::parse_markdown(REVIEWER, Path::new("reviewer.md")).unwrap();
+    assert_eq!(name, "code-reviewer");
+    assert_eq!(agent.description, "Reviews code: style, bugs and tests");
+    assert_eq!(agent.tools.as_deref().unwrap(), ["Read", "Grep", "Glob"]);
+    assert_eq!(agent.model.as_deref(), Some("sonnet"));
+    assert_eq!(agent.prompt, "You are a careful code reviewer.\n\nPoint out bugs before style.");
+
+    let listed = "---\nname: tester\ndescription: Runs tests\ntools:\n  - Bash\n  - mcp__ci__rerun\n---\nRun the tests.\n";
+    let (_, agent) = AgentDefinition::parse_markdown(listed, Path::new("tester.md")).unwrap();
+    assert_eq!(agent.tools.as_deref().unwrap(), ["Bash", "mcp__ci__rerun"]);
+    assert_eq!(agent.model, None);
+}
+
+#[test]
+fn test_agent_markdown_round_trips() {
+    let (name, agent) = AgentDefinition::parse_markdown(REVIEWER, Path::new("reviewer.md")).unwrap();
+    let markdown = agent.to_markdown(&name);
+    assert!(markdown.starts_with("---\nname: code-reviewer\ndescription: \"Reviews code: style, bugs and tests\"\n"));
+    assert!(markdown.contains("tools: Read, Grep, Glob\nmodel: sonnet\n---\n\nYou are"));
+    assert_eq!(AgentDefinition::parse_markdown(&markdown, Path::new("x.md")).unwrap(), (name, agent));
+}
+
+#[test]
+fn test_invalid_agent_files_are_reported() {
+    let missing = AgentDefinition::parse_markdown("Just a prompt", Path::new("a.md")).unwrap_err();
+    assert_eq!(error_message(missing), "a.md: missing --- frontmatter block");
+
+    let no_description = AgentDefinition::parse_markdown("---\nname: a\n---\nPrompt", Path::new("a.md")).unwrap_err();
+    assert!(error_message(no_description).contains("missing field `description`"));
+
+    let bad = "---\nname: Bad Name\ndescription: x\ntools: Read, Tele port\nmodel: gpt-4\n---\nPrompt";
+    let message = error_message(AgentDefinition::parse_markdown(bad, Path::new("bad.md")).unwrap_err());
+    assert!(message.starts_with("bad.md: agent name 'Bad Name' must be lowercase"), "{}", message);
+    assert!(message.contains("invalid tool name 'Tele port'"), "{}", message);
+    assert!(message.contains("unknown model 'gpt-4'"), "{}", message);
+
+    // Agents take tool names, and a rule's commas do not split it into several
+    let rule = "---\nname: a\ndescription: x\ntools: Read, Bash(npm test:*, npm run lint)\n---\nPrompt";
+    let message = error_message(AgentDefinition::parse_markdown(rule, Path::new("a.md")).unwrap_err());
+    assert_eq!(message, "a.md: invalid tool name 'Bash(npm test:*, npm run lint)'");
+}
+
+#[test]
+fn test_tools_missing_from_builtin_list_still_load() {
+    let text = "---\nname: a\ndescription: x\ntools: LS, Skill, Teleport, mcp__ci__rerun\n---\nPrompt";
+    let (_, agent) = AgentDefinition::parse_markdown(text, Path::new("a.md")).unwrap();
+    assert_eq!(agent.unknown_tools(), ["Teleport"]);
+
+    let mut options = ClaudeAgentOptions::default();
+    options.agents.insert("a".to_string(), agent);
+    assert!(options.problems().is_empty(), "{:?}", options.problems());
+}
+
+#[test]
+fn test_load_agents_dir() {
+    let dir = tempfile::tempdir().unwrap();
+    assert!(load_agents_dir(dir.path().join("missing")).unwrap().is_empty());
+
+    fs::write(dir.path().join("reviewer.md"), REVIEWER).unwrap();
+    fs::write(dir.path().join("planner.md"), "---\nname: planner\ndescription: Plans work\n---\nPlan.").unwrap();
+    fs::write(dir.path().join("notes.txt"), "not an agent").unwrap();
+    let agents = load_agents_dir(dir.path()).unwrap();
+    let mut names: Vec<_> = agents.keys().collect();
+    names.sort();
+    assert_eq!(names, ["code-reviewer", "planner"]);
+
+    fs::write(dir.path().join("reviewer2.md"), REVIEWER).unwrap();
+    fs::write(dir.path().join("broken.md"), "---\nname: broken\n---\n").unwrap();
+    let message = error_message(load_agents_dir(dir.path()).unwrap_err());
+    assert!(message.starts_with("2 problems in "), "{}", message);
+    assert!(message.contains("broken.md: missing field `description`"), "{}", message);
+    assert!(message.contains("agent 'code-reviewer' is defined in both"), "{}", message);
+}
+
+#[test]
+fn test_write_agents_dir_and_load_into_options() {
+    let dir = tempfile::tempdir().unwrap();
+    let agents_dir = dir.path().join(".claude/agents");
+    let mut agents = HashMap::new();
+    agents.insert(
+        "doc-writer".to_string(),
+        AgentDefinition {
+            description: "Writes documentation".to_string(),
+            prompt: "Write clear docs.".to_string(),
+            tools: Some(vec!["Read".to_string(), "Write".to_string()]),
+            model: Some("haiku".to_string()),
+        },
+    );
+    let paths = write_agents_dir(&agents, &agents_dir).unwrap();
+    assert_eq!(paths, [agents_dir.join("doc-writer.md")]);
+
+    let mut options = ClaudeAgentOptions::default();
+    options.load_agents_dir(&agents_dir).unwrap();
+    assert_eq!(options.agents, agents);
+
+    let message = error_message(options.load_agents_dir(&agents_dir).unwrap_err());
+    assert!(message.ends_with("agents already defined: doc-writer"), "{}", message);
+
+    options.agents.get_mut("doc-writer").unwrap().model = Some("gpt-4".to_string());
+    assert!(options.problems()[0].starts_with("agent 'doc-writer': unknown model 'gpt-4'"));
+}
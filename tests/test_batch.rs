@@ -0,0 +1,167 @@
+//! Tests for the claude-batch binary against a fake CLI script
+
+#![cfg(unix)]
+
+use claude_agent_sdk::batch::{read_results, BatchResult};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+fn fake_cli() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/fake_claude.sh")
+}
+
+fn run_batch(input: &Path, output: &Path, extra: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_claude-batch"))
+        .arg(input)
+        .arg(output)
+        .arg("--cli-path")
+        .arg(fake_cli())
+        .args(extra)
+        .output()
+        .unwrap()
+}
+
+/// Parse result lines, skipping any truncated line left by a killed run.
+fn result_lines(path: &Path) -> Vec<BatchResult> {
+    std::fs::read_to_string(path)
+        .unwrap()
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+#[test]
+fn test_batch_writes_one_result_per_job() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("jobs.jsonl");
+    let output = dir.path().join("results.jsonl");
+    std::fs::write(
+        &input,
+        concat!(
+            r#"{"id": "a", "prompt": "first"}"#,
+            "\n",
+            r#"{"id": "b", "prompt": "second", "model": "batch-model"}"#,
+            "\n",
+            r#"{"prompt": "third"}"#,
+            "\n",
+        ),
+    )
+    .unwrap();
+
+    let result = run_batch(&input, &output, &["--concurrency", "2"]);
+    assert!(result.status.success(), "{}", String::from_utf8_lossy(&result.stderr));
+
+    let results = read_results(&output).unwrap();
+    assert_eq!(results.len(), 3);
+    for id in ["a", "b", "3"] {
+        let r = &results[id];
+        assert!(r.text.as_deref().unwrap().starts_with("turn=1"));
+        assert_eq!(r.total_cost_usd, Some(0.001));
+        assert_eq!(r.usage.as_ref().unwrap().output_tokens, Some(5));
+        assert_eq!(r.duration_ms, Some(10));
+        assert!(r.session_id.as_deref().unwrap().starts_with("session-"));
+        assert!(r.error.is_none());
+    }
+    assert!(results["b"].text.as_deref().unwrap().contains("model=batch-model"));
+}
+
+#[test]
+fn test_batch_records_job_errors() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("jobs.jsonl");
+    let output = dir.path().join("results.jsonl");
+    std::fs::write(
+        &input,
+        concat!(
+            r#"{"id": "ok", "prompt": "fine"}"#,
+            "\n",
+            r#"{"id": "crash", "prompt": "boom", "env": {"FAKE_CLAUDE_CRASH_ON_TURN": "1"}}"#,
+            "\n",
+        ),
+    )
+    .unwrap();
+
+    let result = run_batch(&input, &output, &[]);
+    assert!(result.status.success());
+    assert!(String::from_utf8_lossy(&result.stderr).contains("1 succeeded, 1 failed, 0 skipped"));
+
+    let results = read_results(&output).unwrap();
+    assert!(results["ok"].error.is_none());
+    assert!(results["crash"].error.is_some());
+    assert!(results["crash"].text.is_none());
+}
+
+#[test]
+fn test_batch_resumes_from_existing_output() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("jobs.jsonl");
+    let output = dir.path().join("results.jsonl");
+    std::fs::write(
+        &input,
+        concat!(
+            r#"{"id": "done", "prompt": "already ran"}"#,
+            "\n",
+            r#"{"id": "failed", "prompt": "ran and failed"}"#,
+            "\n",
+            r#"{"id": "new", "prompt": "not yet"}"#,
+            "\n",
+        ),
+    )
+    .unwrap();
+    // A previous run recorded two jobs and was killed while writing a third
+    std::fs::write(
+        &output,
+        concat!(
+            r#"{"id":"done","text":"old","total_cost_usd":0.5,"usage":null,"duration_ms":1,"session_id":"s","error":null}"#,
+            "\n",
+            r#"{"id":"failed","text":null,"total_cost_usd":null,"usage":null,"duration_ms":null,"session_id":null,"error":"boom"}"#,
+            "\n",
+            r#"{"id":"new","te"#,
+        ),
+    )
+    .unwrap();
+
+    let result = run_batch(&input, &output, &[]);
+    assert!(result.status.success());
+    let lines = result_lines(&output);
+    assert_eq!(lines.iter().map(|r| r.id.as_str()).collect::<Vec<_>>(), ["done", "failed", "new"]);
+    assert_eq!(lines[0].text.as_deref(), Some("old"));
+
+    // Only jobs with errors are re-run with --retry-errors; the latest line wins
+    let result = run_batch(&input, &output, &["--retry-errors"]);
+    assert!(String::from_utf8_lossy(&result.stderr).contains("1 succeeded, 0 failed, 2 skipped"));
+    let results = read_results(&output).unwrap();
+    assert!(results["failed"].error.is_none());
+    assert_eq!(results["done"].text.as_deref(), Some("old"));
+}
+
+#[test]
+fn test_batch_rejects_invalid_jobs() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("jobs.jsonl");
+    let output = dir.path().join("results.jsonl");
+    std::fs::write(&input, "{\"prompt\": \"ok\"}\n{\"no_prompt\": true}\n").unwrap();
+
+    let result = run_batch(&input, &output, &[]);
+    assert!(!result.status.success());
+    assert!(String::from_utf8_lossy(&result.stderr).contains("jobs.jsonl:2:"));
+    assert!(!output.exists());
+}
+
+#[test]
+fn test_batch_rejects_unknown_job_keys() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("jobs.jsonl");
+    let output = dir.path().join("results.jsonl");
+    std::fs::write(&input, "{\"prompt\": \"ok\", \"model\": \"m\"}\n{\"prompt\": \"p\", \"permision_mode\": \"plan\"}\n").unwrap();
+
+    let result = run_batch(&input, &output, &[]);
+    assert!(!result.status.success());
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(stderr.contains("jobs.jsonl:2: unknown field `permision_mode`"), "{}", stderr);
+    assert!(!output.exists());
+
+    let job: claude_agent_sdk::batch::BatchJob =
+        serde_json::from_str(r#"{"prompt": "p", "permission_mode": "plan", "env": {"A": "1"}}"#).unwrap();
+    assert_eq!(job.overrides.env["A"], "1");
+}
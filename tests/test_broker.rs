@@ -0,0 +1,175 @@
+//! Tests for PermissionBroker, directly and against a fake CLI script
+
+use claude_agent_sdk::types::{PermissionRuleValue, PermissionUpdate, ToolPermissionContext};
+use claude_agent_sdk::{BuiltinToolInput, PermissionBroker, PermissionResult};
+use serde_json::json;
+use std::time::Duration;
+
+fn context(tool_use_id: &str) -> ToolPermissionContext {
+    ToolPermissionContext {
+        suggestions: Vec::new(),
+        blocked_path: Some("/outside".to_string()),
+        tool_use_id: Some(tool_use_id.to_string()),
+    }
+}
+
+fn is_deny_with(result: &PermissionResult, expected: &str) -> bool {
+    matches!(result, PermissionResult::Deny { message, .. } if message == expected)
+}
+
+#[tokio::test]
+async fn test_concurrent_approvals_answered_out_of_order() {
+    let (broker, mut approvals) = PermissionBroker::new();
+    let callback = broker.callback();
+
+    let first = tokio::spawn(callback("Bash".to_string(), json!({"command": "ls"}), context("toolu_1")));
+    let second = tokio::spawn(callback("Read".to_string(), json!({"file_path": "/outside/a"}), context("toolu_2")));
+
+    let mut pending = vec![approvals.recv().await.unwrap(), approvals.recv().await.unwrap()];
+    pending.sort_by_key(|approval| approval.tool_use_id.clone());
+    let read = pending.pop().unwrap();
+    let bash = pending.pop().unwrap();
+    assert_ne!(bash.id, read.id);
+    assert_eq!(bash.blocked_path.as_deref(), Some("/outside"));
+    assert!(matches!(bash.input, BuiltinToolInput::Bash(ref input) if input.command == "ls"));
+    assert_eq!(read.raw_input["file_path"], "/outside/a");
+
+    // Answer the second prompt first; neither blocks the other
+    assert!(read.allow_with_input(json!({"file_path": "/outside/b"})));
+    let result = second.await.unwrap();
+    assert!(matches!(result, PermissionResult::Allow { updated_input: Some(ref input), .. } if input["file_path"] == "/outside/b"));
+
+    assert!(bash.is_open());
+    assert!(bash.deny("not now"));
+    assert!(is_deny_with(&first.await.unwrap(), "not now"));
+}
+
+#[tokio::test]
+async fn test_timeout_and_dropped_approvals_use_default_decision() {
+    let (broker, mut approvals) = PermissionBroker::new();
+    let callback = broker
+        .timeout(Duration::from_millis(50))
+        .default_decision(PermissionResult::Deny {
+            message: "timed out".to_string(),
+            interrupt: true,
+        })
+        .callback();
+
+    let timed_out = tokio::spawn(callback("Bash".to_string(), json!({"command": "ls"}), context("toolu_1")));
+    let approval = approvals.recv().await.unwrap();
+    let result = timed_out.await.unwrap();
+    assert!(matches!(result, PermissionResult::Deny { ref message, interrupt: true } if message == "timed out"));
+    assert!(!approval.is_open());
+    assert!(!approval.allow());
+
+    let dropped = tokio::spawn(callback("Bash".to_string(), json!({"command": "ls"}), context("toolu_2")));
+    drop(approvals.recv().await.unwrap());
+    assert!(is_deny_with(&dropped.await.unwrap(), "timed out"));
+
+    drop(approvals);
+    let result = callback("Bash".to_string(), json!({"command": "ls"}), context("toolu_3")).await;
+    assert!(is_deny_with(&result, "timed out"));
+}
+
+#[tokio::test]
+async fn test_allow_with_suggestions() {
+    let (broker, mut approvals) = PermissionBroker::new();
+    let suggestion = PermissionUpdate::AddRules {
+        rules: vec![PermissionRuleValue {
+            tool_name: "Bash".to_string(),
+            rule_content: Some("ls:*".to_string()),
+        }],
+        behavior: claude_agent_sdk::types::PermissionBehavior::Allow,
+        destination: None,
+    };
+    let mut context = context("toolu_1");
+    context.suggestions = vec![suggestion.clone()];
+
+    let pending = tokio::spawn(broker.callback()("Bash".to_string(), json!({"command": "ls"}), context));
+    assert!(approvals.recv().await.unwrap().allow_with_suggestions());
+    let result = pending.await.unwrap();
+    assert!(matches!(result, PermissionResult::Allow { updated_permissions: Some(ref updates), .. } if *updates == [suggestion]));
+}
+
+#[test]
+fn test_rule_values_use_cli_field_names() {
+    let rule = PermissionRuleValue {
+        tool_name: "Write".to_string(),
+        rule_content: Some("/tmp/**".to_string()),
+    };
+    assert_eq!(serde_json::to_value(&rule).unwrap(), json!({"toolName": "Write", "ruleContent": "/tmp/**"}));
+    let legacy: PermissionRuleValue = serde_json::from_value(json!({"tool_name": "Write"})).unwrap();
+    assert_eq!(legacy.tool_name, "Write");
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_broker_answers_concurrent_cli_prompts() {
+    use claude_agent_sdk::{ClaudeAgentOptions, ClaudeSDKClient, ContentBlock, Message};
+    use futures::StreamExt;
+    use std::path::PathBuf;
+
+    let (broker, mut approvals) = PermissionBroker::new();
+    let mut options = ClaudeAgentOptions {
+        cli_path: Some(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/fake_claude.sh")),
+        can_use_tool: Some(broker.callback()),
+        ..Default::default()
+    };
+    options.env.insert("FAKE_CLAUDE_PERMISSION_PATHS".to_string(), "/a /b".to_string());
+
+    // Collect both prompts before answering either, then answer in reverse
+    let ui = tokio::spawn(async move {
+        let first = approvals.recv().await.unwrap();
+        let second = approvals.recv().await.unwrap();
+        let seen: Vec<_> = [&first, &second]
+            .iter()
+            .map(|approval| {
+                let BuiltinToolInput::Write(ref input) = approval.input else {
+                    panic!("expected Write input, got {:?}", approval.input);
+                };
+                assert_eq!(approval.blocked_path.as_deref(), Some(input.file_path.as_str()));
+                assert!(matches!(
+                    approval.suggestions[..],
+                    [PermissionUpdate::AddRules { ref rules, .. }] if rules[0].rule_content.as_deref() == Some(input.file_path.as_str())
+                ));
+                (input.file_path.clone(), approval.tool_use_id.clone().unwrap())
+            })
+            .collect();
+        for approval in [second, first] {
+            if approval.raw_input["file_path"] == "/a" {
+                approval.allow();
+            } else {
+                approval.deny("read-only");
+            }
+        }
+        seen
+    });
+
+    let mut client = ClaudeSDKClient::new(options);
+    client.connect().await.unwrap();
+    client.query("Write both files".to_string()).await.unwrap();
+    let mut text = String::new();
+    let mut stream = client.receive_response();
+    while let Some(message) = stream.next().await {
+        if let Message::Assistant { message, .. } = message.unwrap() {
+            for block in message.message.content {
+                if let ContentBlock::Text { text: t } = block {
+                    text.push_str(&t);
+                }
+            }
+        }
+    }
+    drop(stream);
+    assert!(text.ends_with("permissions=/a:allow,/b:deny"), "{}", text);
+
+    let mut seen = ui.await.unwrap();
+    seen.sort();
+    assert_eq!(
+        seen,
+        [
+            ("/a".to_string(), "toolu_1_1".to_string()),
+            ("/b".to_string(), "toolu_1_2".to_string())
+        ]
+    );
+    client.disconnect().await.unwrap();
+}
@@ -0,0 +1,135 @@
+//! Tests for budget tracking
+
+use claude_agent_sdk::{BudgetTracker, Message};
+use serde_json::json;
+
+fn result(cost: f64, input: u64, output: u64) -> Message {
+    result_with_usage(cost, json!({"input_tokens": input, "output_tokens": output}))
+}
+
+fn result_with_usage(cost: f64, usage: serde_json::Value) -> Message {
+    serde_json::from_value(json!({
+        "type": "result",
+        "subtype": "success",
+        "duration_ms": 10,
+        "duration_api_ms": 5,
+        "is_error": false,
+        "num_turns": 1,
+        "session_id": "s",
+        "total_cost_usd": cost,
+        "usage": usage
+    }))
+    .unwrap()
+}
+
+fn stream_event(event: serde_json::Value) -> Message {
+    serde_json::from_value(json!({
+        "type": "stream_event",
+        "uuid": "u",
+        "session_id": "s",
+        "event": event
+    }))
+    .unwrap()
+}
+
+#[test]
+fn test_results_report_cumulative_cost() {
+    let mut tracker = BudgetTracker::new(Some(0.05), None);
+    tracker.record(&result(0.02, 100, 50));
+    tracker.record(&result(0.04, 100, 50));
+
+    let totals = tracker.totals();
+    assert!((totals.total_cost_usd - 0.04).abs() < 1e-9);
+    assert!(!tracker.is_exceeded());
+
+    tracker.record(&result(0.06, 100, 50));
+    assert!(tracker.is_exceeded());
+}
+
+#[test]
+fn test_per_turn_usage_is_summed_across_turns() {
+    let mut tracker = BudgetTracker::new(None, Some(400));
+    tracker.record(&result(0.01, 100, 50));
+    tracker.record(&result(0.02, 80, 40));
+    assert_eq!(tracker.totals().total_tokens, 270);
+    assert!(!tracker.is_exceeded());
+
+    // A turn smaller than the last must not lower the total
+    tracker.record(&result(0.03, 120, 60));
+    assert_eq!(tracker.totals().total_tokens, 450);
+    assert!(tracker.is_exceeded());
+}
+
+#[test]
+fn test_cache_tokens_count_toward_limit() {
+    let mut tracker = BudgetTracker::new(None, Some(1000));
+    tracker.record(&result_with_usage(
+        0.01,
+        json!({
+            "input_tokens": 10,
+            "cache_creation_input_tokens": 200,
+            "cache_read_input_tokens": 800,
+            "output_tokens": 5
+        }),
+    ));
+    assert_eq!(tracker.totals().total_tokens, 1015);
+    assert!(tracker.is_exceeded());
+}
+
+#[test]
+fn test_stream_events_estimate_tokens_within_turn() {
+    let mut tracker = BudgetTracker::new(None, Some(1000));
+    tracker.record(&result(0.01, 100, 50));
+
+    tracker.record(&stream_event(json!({
+        "type": "message_start",
+        "message": {"usage": {"input_tokens": 400, "cache_read_input_tokens": 100, "output_tokens": 1}}
+    })));
+    assert_eq!(tracker.totals().total_tokens, 651);
+
+    tracker.record(&stream_event(json!({"type": "message_delta", "usage": {"output_tokens": 300}})));
+    assert_eq!(tracker.totals().total_tokens, 950);
+    assert!(!tracker.is_exceeded());
+
+    tracker.record(&stream_event(json!({
+        "type": "message_start",
+        "message": {"usage": {"input_tokens": 100}}
+    })));
+    assert_eq!(tracker.totals().total_tokens, 1050);
+    assert!(tracker.is_exceeded());
+
+    // The result replaces the estimate with the CLI's own count for the turn
+    tracker.record(&result(0.02, 550, 300));
+    assert_eq!(tracker.totals().total_tokens, 1000);
+    assert!(!tracker.is_exceeded());
+}
+
+#[test]
+fn test_totals_carry_over_new_process() {
+    let mut tracker = BudgetTracker::new(Some(0.05), None);
+    tracker.record(&result(0.03, 100, 50));
+    tracker.start_new_process();
+    tracker.record(&result(0.01, 10, 5));
+
+    let totals = tracker.totals();
+    assert!((totals.total_cost_usd - 0.04).abs() < 1e-9);
+    assert_eq!(totals.total_tokens, 165);
+}
+
+#[test]
+fn test_cost_limit_is_only_checked_on_results() {
+    let mut tracker = BudgetTracker::new(Some(0.01), None);
+    tracker.record(&result(0.005, 100, 50));
+
+    // Stream events carry tokens but no cost, however large the turn gets
+    tracker.record(&stream_event(json!({
+        "type": "message_start",
+        "message": {"usage": {"input_tokens": 500_000, "output_tokens": 1}}
+    })));
+    tracker.record(&stream_event(json!({"type": "message_delta", "usage": {"output_tokens": 100_000}})));
+    assert!((tracker.totals().total_cost_usd - 0.005).abs() < 1e-9);
+    assert!(!tracker.is_exceeded());
+
+    tracker.record(&result(0.5, 500_000, 100_000));
+    assert!(tracker.is_exceeded());
+}
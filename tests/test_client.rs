@@ -0,0 +1,507 @@
+//! Tests for ClaudeSDKClient against a fake CLI script
+
+#![cfg(unix)]
+
+use claude_agent_sdk::types::{
+    HookSpecificOutput, McpConnectionStatus, RecoveryPolicy, SystemPrompt, TurnResult, UserPromptSubmitHookInput,
+};
+use claude_agent_sdk::{
+    ClaudeAgentOptions, ClaudeSDKClient, ClaudeSDKError, CommandDefinition, ContentBlock, HookJSONOutput, Message,
+    Settings, SlashCommand,
+};
+use futures::StreamExt;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::Duration;
+
+fn fake_cli_options() -> ClaudeAgentOptions {
+    ClaudeAgentOptions {
+        cli_path: Some(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/fake_claude.sh")),
+        ..Default::default()
+    }
+}
+
+fn crashing_options(env: &[(&str, &str)]) -> ClaudeAgentOptions {
+    let mut options = fake_cli_options();
+    options.recovery_policy = Some(RecoveryPolicy {
+        max_retries: 2,
+        initial_backoff: Duration::from_millis(10),
+        max_backoff: Duration::from_millis(20),
+    });
+    for (key, value) in env {
+        options.env.insert(key.to_string(), value.to_string());
+    }
+    options
+}
+
+/// Send a prompt and return the assistant text of the response.
+async fn ask(client: &mut ClaudeSDKClient, prompt: &str) -> String {
+    client.query(prompt.to_string()).await.unwrap();
+
+    let mut text = String::new();
+    let mut saw_result = false;
+    let mut stream = client.receive_response();
+    while let Some(message) = stream.next().await {
+        match message.unwrap() {
+            Message::Assistant { message, .. } => {
+                for block in message.message.content {
+                    if let ContentBlock::Text { text: t } = block {
+                        text.push_str(&t);
+                    }
+                }
+            }
+            Message::Result { .. } => saw_result = true,
+            _ => {}
+        }
+    }
+    assert!(saw_result, "receive_response should yield the Result message");
+    text
+}
+
+#[tokio::test]
+async fn test_client_tracks_session_id() {
+    let mut client = ClaudeSDKClient::new(fake_cli_options());
+    client.connect().await.unwrap();
+    assert!(client.session_id().is_none());
+
+    ask(&mut client, "hello").await;
+    let session_id = client.session_id().unwrap();
+    assert!(session_id.starts_with("session-"));
+
+    client.disconnect().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_connect_fails_on_unreadable_system_prompt_file() {
+    let mut options = fake_cli_options();
+    options.system_prompt = Some(SystemPrompt::File(PathBuf::from("/nonexistent/prompt.md")));
+    let mut client = ClaudeSDKClient::new(options);
+
+    let err = client.connect().await.unwrap_err();
+    assert!(matches!(err, ClaudeSDKError::InvalidConfig(ref message) if message.contains("/nonexistent/prompt.md")));
+}
+
+#[tokio::test]
+async fn test_fallback_model_and_thinking_budget_are_passed() {
+    let mut options = fake_cli_options();
+    options.model = Some("primary-model".to_string());
+    options.fallback_model = Some("backup-model".to_string());
+    options.max_thinking_tokens = Some(4096);
+    let mut client = ClaudeSDKClient::new(options);
+    client.connect().await.unwrap();
+
+    let text = ask(&mut client, "hello").await;
+    assert!(text.contains("model=primary-model fallback=backup-model thinking=4096"), "{}", text);
+
+    client.disconnect().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_inline_settings_are_written_to_a_temp_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let args_log = dir.path().join("args");
+    let mut options = fake_cli_options();
+    options.env.insert("FAKE_CLAUDE_ARGS_LOG".to_string(), args_log.to_string_lossy().to_string());
+    let mut settings = Settings::default();
+    settings.permissions.allow.push("Bash(ls:*)".to_string());
+    options.settings = Some(settings.clone().into());
+    let mut client = ClaudeSDKClient::new(options);
+    client.connect().await.unwrap();
+    ask(&mut client, "hello").await;
+
+    let args = std::fs::read_to_string(&args_log).unwrap();
+    let args: Vec<&str> = args.lines().collect();
+    let path = PathBuf::from(args[args.iter().position(|arg| *arg == "--settings").unwrap() + 1]);
+    assert_eq!(Settings::from_file(&path).unwrap(), settings);
+    use std::os::unix::fs::PermissionsExt;
+    assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o077, 0);
+
+    client.disconnect().await.unwrap();
+    assert!(!path.exists());
+}
+
+#[tokio::test]
+async fn test_slash_commands_are_discovered_and_sent() {
+    let dir = tempfile::tempdir().unwrap();
+    CommandDefinition::new("review", "Review $ARGUMENTS.")
+        .write_markdown(dir.path().join(".claude/commands"))
+        .unwrap();
+    let mut options = fake_cli_options();
+    options.cwd = Some(dir.path().to_path_buf());
+    let mut client = ClaudeSDKClient::new(options);
+    client.connect().await.unwrap();
+    assert_eq!(client.slash_commands(), None);
+
+    ask(&mut client, "hello").await;
+    assert_eq!(client.slash_commands().unwrap(), ["compact", "review"]);
+
+    client.send_slash_command(&SlashCommand::new("review").with_args("src/lib.rs")).await.unwrap();
+    let mut text = String::new();
+    let mut stream = client.receive_response();
+    while let Some(message) = stream.next().await {
+        if let Message::Assistant { message, .. } = message.unwrap() {
+            if let ContentBlock::Text { text: t } = &message.message.content[0] {
+                text.push_str(t);
+            }
+        }
+    }
+    drop(stream);
+    assert!(text.ends_with("command=/review src/lib.rs"), "{}", text);
+
+    let error = client.send_slash_command(&SlashCommand::new("deploy")).await.unwrap_err();
+    assert!(error.to_string().contains("unknown slash command /deploy; available: /compact, /review"), "{}", error);
+
+    client.disconnect().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_set_model_switches_later_turns() {
+    let mut options = fake_cli_options();
+    options.model = Some("planning-model".to_string());
+    let mut client = ClaudeSDKClient::new(options);
+    client.connect().await.unwrap();
+    assert!(ask(&mut client, "plan").await.contains("model=planning-model"));
+
+    client.set_model(Some("cheap-model".to_string())).await.unwrap();
+    assert!(ask(&mut client, "implement").await.contains("model=cheap-model"));
+
+    client.set_model(None).await.unwrap();
+    assert!(ask(&mut client, "review").await.contains("model=default"));
+
+    client.disconnect().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_mcp_server_controls() {
+    let mut client = ClaudeSDKClient::new(fake_cli_options());
+    client.connect().await.unwrap();
+
+    let servers = client.mcp_server_status().await.unwrap();
+    assert_eq!(servers.len(), 1);
+    assert_eq!(servers[0].name, "fake-mcp");
+    assert_eq!(servers[0].status, McpConnectionStatus::Connected);
+    assert_eq!(servers[0].server_info.as_ref().unwrap().version, "1.0.0");
+
+    client.toggle_mcp_server("fake-mcp", false).await.unwrap();
+    assert_eq!(client.mcp_server_status().await.unwrap()[0].status, McpConnectionStatus::Disabled);
+    client.reconnect_mcp_server("fake-mcp").await.unwrap();
+    assert_eq!(client.mcp_server_status().await.unwrap()[0].status, McpConnectionStatus::Connected);
+
+    let error = client.reconnect_mcp_server("missing").await.unwrap_err();
+    assert!(matches!(error, ClaudeSDKError::ControlProtocol(ref message) if message == "Server not found: missing"));
+
+    client.disconnect().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_unsupported_control_requests_have_their_own_error() {
+    let mut options = fake_cli_options();
+    options.env.insert("FAKE_CLAUDE_UNSUPPORTED".to_string(), "set_model mcp_status".to_string());
+    let mut client = ClaudeSDKClient::new(options);
+    client.connect().await.unwrap();
+
+    match client.set_model(Some("cheap-model".to_string())).await.unwrap_err() {
+        ClaudeSDKError::Unsupported { request, message } => {
+            assert_eq!(request, "set_model");
+            assert_eq!(message, "Unsupported control request subtype: set_model");
+        }
+        other => panic!("expected Unsupported, got {:?}", other),
+    }
+    assert!(matches!(
+        client.mcp_server_status().await.unwrap_err(),
+        ClaudeSDKError::Unsupported { ref request, .. } if request == "mcp_status"
+    ));
+    assert!(ask(&mut client, "hello").await.contains("model=none"));
+
+    client.disconnect().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_structured_output_is_reported_on_result() {
+    let mut options = fake_cli_options();
+    options.env.insert(
+        "FAKE_CLAUDE_STRUCTURED_OUTPUT".to_string(),
+        r#"{"answer":4,"steps":["add"]}"#.to_string(),
+    );
+    options.json_schema = Some(serde_json::json!({
+        "type": "object",
+        "properties": {"answer": {"type": "integer"}},
+        "required": ["answer"]
+    }));
+    let mut client = ClaudeSDKClient::new(options);
+    client.connect().await.unwrap();
+    client.query("What is 2 + 2?".to_string()).await.unwrap();
+
+    let mut messages = Vec::new();
+    let mut stream = client.receive_response();
+    while let Some(message) = stream.next().await {
+        messages.push(message.unwrap());
+    }
+    drop(stream);
+
+    let turn = TurnResult::from_messages(messages).unwrap();
+    assert_eq!(turn.structured_output, Some(serde_json::json!({"answer": 4, "steps": ["add"]})));
+
+    client.disconnect().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_receive_response_per_turn() {
+    let mut client = ClaudeSDKClient::new(fake_cli_options());
+    client.connect().await.unwrap();
+
+    assert!(ask(&mut client, "first").await.starts_with("turn=1"));
+    assert!(ask(&mut client, "second").await.starts_with("turn=2"));
+
+    client.disconnect().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_server_info_from_init_message() {
+    let mut client = ClaudeSDKClient::new(fake_cli_options());
+    client.connect().await.unwrap();
+    assert!(client.server_info().is_none());
+
+    ask(&mut client, "hello").await;
+    let info = client.server_info().unwrap();
+    assert_eq!(info.session_id, client.session_id().unwrap());
+    assert_eq!(info.model.as_deref(), Some("fake-model"));
+    assert_eq!(info.tools, vec!["Read"]);
+    assert_eq!(info.mcp_server("fake-mcp").unwrap().status, McpConnectionStatus::Connected);
+
+    client.disconnect().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_fork_runs_independently() {
+    let mut parent = ClaudeSDKClient::new(fake_cli_options());
+    parent.connect().await.unwrap();
+    ask(&mut parent, "plan something").await;
+    let parent_session = parent.session_id().unwrap();
+
+    let mut fork = parent.fork().await.unwrap();
+    let fork_text = ask(&mut fork, "try plan B").await;
+    assert!(fork_text.starts_with(&format!("turn=1 resume={} fork=true ", parent_session)));
+    assert_ne!(fork.session_id().unwrap(), parent_session);
+
+    // The parent session is unaffected by the fork
+    assert!(ask(&mut parent, "continue plan A").await.starts_with("turn=2 resume=none fork=false "));
+    assert_eq!(parent.session_id().unwrap(), parent_session);
+
+    fork.disconnect().await.unwrap();
+    parent.disconnect().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_fork_requires_session() {
+    let mut client = ClaudeSDKClient::new(fake_cli_options());
+    client.connect().await.unwrap();
+
+    assert!(client.fork().await.is_err());
+
+    client.disconnect().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_recovery_resumes_session_after_crash() {
+    let mut client = ClaudeSDKClient::new(crashing_options(&[("FAKE_CLAUDE_CRASH_ON_TURN", "2")]));
+    client.connect().await.unwrap();
+    ask(&mut client, "first").await;
+    let session_id = client.session_id().unwrap();
+
+    client.query("second".to_string()).await.unwrap();
+    let messages: Vec<_> = client.receive_response().collect().await;
+    assert_eq!(messages.len(), 1);
+    match &messages[0] {
+        Ok(Message::Reconnected { session_id: resumed, attempt }) => {
+            assert_eq!(resumed.as_deref(), Some(session_id.as_str()));
+            assert_eq!(*attempt, 1);
+        }
+        other => panic!("Expected Reconnected, got {:?}", other),
+    }
+
+    // The respawned CLI resumes the same session
+    let text = ask(&mut client, "second again").await;
+    assert!(text.starts_with(&format!("turn=1 resume={} fork=false ", session_id)));
+    assert_eq!(client.session_id().unwrap(), session_id);
+
+    client.disconnect().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_recovery_gives_up_after_max_retries() {
+    let mut client = ClaudeSDKClient::new(crashing_options(&[
+        ("FAKE_CLAUDE_CRASH_ON_TURN", "2"),
+        ("FAKE_CLAUDE_CRASH_ON_RESUME", "1"),
+    ]));
+    client.connect().await.unwrap();
+    ask(&mut client, "first").await;
+
+    client.query("second".to_string()).await.unwrap();
+    let messages: Vec<_> = client.receive_messages().collect().await;
+    assert_eq!(messages.len(), 1);
+    let error = messages[0].as_ref().unwrap_err().to_string();
+    assert!(error.contains("after 2 attempts"), "unexpected error: {}", error);
+}
+
+#[tokio::test]
+async fn test_crash_without_recovery_policy_is_an_error() {
+    let mut options = fake_cli_options();
+    options.env.insert("FAKE_CLAUDE_CRASH_ON_TURN".to_string(), "1".to_string());
+    let mut client = ClaudeSDKClient::new(options);
+    client.connect().await.unwrap();
+
+    client.query("first".to_string()).await.unwrap();
+    let messages: Vec<_> = client.receive_messages().collect().await;
+    assert_eq!(messages.len(), 1);
+    assert!(messages[0].is_err());
+}
+
+#[tokio::test]
+async fn test_disconnect_does_not_trigger_recovery() {
+    let mut client = ClaudeSDKClient::new(crashing_options(&[]));
+    client.connect().await.unwrap();
+    ask(&mut client, "first").await;
+
+    client.disconnect().await.unwrap();
+    assert!(client.receive_messages().next().await.is_none());
+}
+
+#[tokio::test]
+async fn test_unknown_messages_are_yielded_unless_strict() {
+    let extra = r#"{"type":"rate_limit_event","retry_after":5}"#;
+
+    let mut options = fake_cli_options();
+    options.env.insert("FAKE_CLAUDE_EXTRA_MESSAGE".to_string(), extra.to_string());
+
+    let mut client = ClaudeSDKClient::new(options.clone());
+    client.connect().await.unwrap();
+    client.query("hello".to_string()).await.unwrap();
+    let messages: Vec<_> = client.receive_response().collect().await;
+    assert!(messages
+        .iter()
+        .any(|m| matches!(m, Ok(Message::Unknown { raw }) if raw["type"] == "rate_limit_event")));
+    assert!(matches!(messages.last(), Some(Ok(Message::Result { .. }))));
+    client.disconnect().await.unwrap();
+
+    options.strict_parsing = true;
+    let mut client = ClaudeSDKClient::new(options);
+    client.connect().await.unwrap();
+    client.query("hello".to_string()).await.unwrap();
+    let messages: Vec<_> = client.receive_response().collect().await;
+    assert!(messages.iter().any(|m| matches!(m, Err(ClaudeSDKError::MessageParse { .. }))));
+    client.disconnect().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_cost_budget_ends_session() {
+    let mut options = fake_cli_options();
+    options.max_budget_usd = Some(0.0025);
+    let mut client = ClaudeSDKClient::new(options);
+    client.connect().await.unwrap();
+
+    ask(&mut client, "first").await;
+    ask(&mut client, "second").await;
+    assert_eq!(client.spend().total_tokens, 30);
+
+    client.query("third".to_string()).await.unwrap();
+    let messages: Vec<_> = client.receive_response().collect().await;
+    match messages.last().unwrap() {
+        Err(ClaudeSDKError::BudgetExceeded {
+            total_cost_usd,
+            total_tokens,
+        }) => {
+            assert!((total_cost_usd - 0.003).abs() < 1e-9);
+            assert_eq!(*total_tokens, 45);
+        }
+        other => panic!("expected BudgetExceeded, got {:?}", other),
+    }
+    assert!(!messages.iter().any(|m| matches!(m, Ok(Message::Result { .. }))));
+
+    let err = client.query("fourth".to_string()).await.unwrap_err();
+    assert!(matches!(err, ClaudeSDKError::BudgetExceeded { .. }));
+
+    client.disconnect().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_token_budget_counts_stream_events_mid_turn() {
+    let mut options = fake_cli_options();
+    options.include_partial_messages = true;
+    options.max_total_tokens = Some(12);
+    let mut client = ClaudeSDKClient::new(options);
+    client.connect().await.unwrap();
+
+    client.query("hello".to_string()).await.unwrap();
+    let mut stream = client.receive_messages();
+    let mut seen = Vec::new();
+    let error = loop {
+        match stream.next().await.unwrap() {
+            Ok(message) => seen.push(message),
+            Err(e) => break e,
+        }
+    };
+    assert!(stream.next().await.is_none());
+    drop(stream);
+
+    assert!(seen.iter().any(|m| matches!(m, Message::StreamEvent { .. })));
+    assert!(matches!(error, ClaudeSDKError::BudgetExceeded { total_tokens: 15, .. }));
+
+    client.disconnect().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_typed_hooks_are_dispatched() {
+    let prompts = Arc::new(StdMutex::new(Vec::new()));
+    let seen = prompts.clone();
+
+    let mut options = fake_cli_options();
+    options.add_hook(None, move |input: UserPromptSubmitHookInput, _tool_use_id, _ctx| {
+        seen.lock().unwrap().push(input.prompt);
+        async move {
+            HookJSONOutput {
+                hook_specific_output: Some(HookSpecificOutput::UserPromptSubmit {
+                    additional_context: Some("be brief".to_string()),
+                }),
+                ..Default::default()
+            }
+        }
+    });
+    let mut client = ClaudeSDKClient::new(options);
+    client.connect().await.unwrap();
+
+    let text = ask(&mut client, "hello hooks").await;
+    assert_eq!(*prompts.lock().unwrap(), vec!["hello hooks".to_string()]);
+    assert!(text.contains(r#""hookEventName":"UserPromptSubmit""#), "unexpected text: {}", text);
+    assert!(text.contains(r#""additionalContext":"be brief""#), "unexpected text: {}", text);
+
+    client.disconnect().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_one_shot_query_with_can_use_tool_completes() {
+    let dir = tempfile::tempdir().unwrap();
+    let args_log = dir.path().join("args");
+    let mut options = fake_cli_options();
+    options.env.insert("FAKE_CLAUDE_ARGS_LOG".to_string(), args_log.to_string_lossy().to_string());
+    options.env.insert("FAKE_CLAUDE_PERMISSION_PATHS".to_string(), "/a".to_string());
+    options.can_use_tool = Some(Arc::new(|_tool, _input, _context| {
+        Box::pin(async {
+            claude_agent_sdk::PermissionResult::Deny {
+                message: "unreachable".to_string(),
+                interrupt: false,
+            }
+        })
+    }));
+
+    // stdin closes after the prompt, so the CLI must not ask over the control protocol
+    let messages: Vec<_> = tokio::time::timeout(Duration::from_secs(10), async {
+        claude_agent_sdk::query("hello".to_string(), options).await.unwrap().collect::<Vec<_>>().await
+    })
+    .await
+    .expect("query() should not wait on permission prompts");
+    assert!(matches!(messages.last(), Some(Ok(Message::Result { is_error: false, .. }))), "{:?}", messages);
+
+    let args = std::fs::read_to_string(&args_log).unwrap();
+    assert!(!args.lines().any(|arg| arg == "--permission-prompt-tool"), "{}", args);
+}
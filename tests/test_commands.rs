@@ -0,0 +1,96 @@
+//! Tests for slash command invocations and command files
+
+use claude_agent_sdk::{load_commands_dir, ClaudeSDKError, CommandDefinition, SlashCommand};
+use std::fs;
+use std::path::Path;
+
+const COMMIT: &str = "---
+allowed-tools: Bash(git add:*), Bash(git status:*), Bash(git commit:*)
+description: Create a git commit
+argument-hint: \"[message]\"
+---
+
+Commit the staged changes with message: $ARGUMENTS
+";
+
+#[test]
+fn test_slash_command_prompt() {
+    let command = SlashCommand::new("/review").with_args("src/lib.rs");
+    assert_eq!(command.name, "review");
+    assert_eq!(command.to_prompt(), "/review src/lib.rs");
+    assert_eq!(SlashCommand::new("compact").to_prompt(), "/compact");
+
+    assert_eq!(
+        SlashCommand::parse("  /review  src/lib.rs --strict"),
+        Some(SlashCommand::new("review").with_args("src/lib.rs --strict"))
+    );
+    assert_eq!(SlashCommand::parse("/compact"), Some(SlashCommand::new("compact")));
+    assert_eq!(SlashCommand::parse("review this"), None);
+    assert_eq!(SlashCommand::parse("/ nothing"), None);
+}
+
+#[test]
+fn test_parse_command_markdown() {
+    let command = CommandDefinition::parse_markdown("commit", COMMIT, Path::new("commit.md")).unwrap();
+    assert_eq!(command.description.as_deref(), Some("Create a git commit"));
+    assert_eq!(command.allowed_tools, ["Bash(git add:*)", "Bash(git status:*)", "Bash(git commit:*)"]);
+    assert_eq!(command.argument_hint.as_deref(), Some("[message]"));
+    assert_eq!(command.prompt, "Commit the staged changes with message: $ARGUMENTS");
+
+    let rules = "---\nallowed-tools: Bash(git add:*, git commit:*), Read\n---\nCommit.\n";
+    let rules = CommandDefinition::parse_markdown("commit", rules, Path::new("commit.md")).unwrap();
+    assert_eq!(rules.allowed_tools, ["Bash(git add:*, git commit:*)", "Read"]);
+
+    let plain = CommandDefinition::parse_markdown("explain", "Explain $1 in $2 words.\n", Path::new("explain.md")).unwrap();
+    assert_eq!(plain, CommandDefinition::new("explain", "Explain $1 in $2 words."));
+    assert_eq!(plain.to_markdown(), "Explain $1 in $2 words.\n");
+
+    let markdown = command.to_markdown();
+    assert!(markdown.contains("argument-hint: \"[message]\"\n"), "{}", markdown);
+    assert_eq!(CommandDefinition::parse_markdown("commit", &markdown, Path::new("commit.md")).unwrap(), command);
+}
+
+#[test]
+fn test_render_command_arguments() {
+    let command = CommandDefinition::new("fix", "Fix issue #$1 with priority $2 ($ARGUMENTS). Costs $5, not $0 or $x.");
+    assert_eq!(
+        command.render("123 high"),
+        "Fix issue #123 with priority high (123 high). Costs , not $0 or $x."
+    );
+    assert_eq!(command.invocation("123 high").to_prompt(), "/fix 123 high");
+}
+
+#[test]
+fn test_command_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let commands_dir = dir.path().join(".claude/commands");
+    assert!(load_commands_dir(&commands_dir).unwrap().is_empty());
+
+    let mut review = CommandDefinition::new("review", "Review $ARGUMENTS for bugs.");
+    review.allowed_tools = vec!["Read".to_string(), "Grep".to_string()];
+    review.model = Some("claude-sonnet-4-5".to_string());
+    assert_eq!(review.write_markdown(&commands_dir).unwrap(), commands_dir.join("review.md"));
+    fs::write(commands_dir.join("commit.md"), COMMIT).unwrap();
+    fs::write(commands_dir.join("README.txt"), "not a command").unwrap();
+
+    let commands = load_commands_dir(&commands_dir).unwrap();
+    let names: Vec<_> = commands.iter().map(|command| command.name.as_str()).collect();
+    assert_eq!(names, ["commit", "review"]);
+    assert_eq!(commands[1], review);
+
+    // Tools missing from the built-in list may be newer CLI tools, so they load
+    fs::write(commands_dir.join("deploy.md"), "---\nallowed-tools: Teleport(prod:*), Skill, Read\n---\nDeploy.").unwrap();
+    let commands = load_commands_dir(&commands_dir).unwrap();
+    assert_eq!(commands[1].name, "deploy");
+    assert_eq!(commands[1].unknown_tools(), ["Teleport(prod:*)"]);
+
+    fs::write(commands_dir.join("bad.md"), "---\nallowed-tools: Tele port\n---\n").unwrap();
+    let message = match load_commands_dir(&commands_dir).unwrap_err() {
+        ClaudeSDKError::InvalidConfig(message) => message,
+        other => panic!("expected InvalidConfig, got {:?}", other),
+    };
+    assert!(message.contains("bad.md: prompt is empty; invalid tool name in 'Tele port'"), "{}", message);
+
+    let invalid = CommandDefinition::new("two words", "Prompt");
+    assert!(invalid.write_markdown(&commands_dir).is_err());
+}
@@ -0,0 +1,288 @@
+//! Tests for rendering the CLI invocation without starting it
+
+use claude_agent_sdk::transport::subprocess::REDACTED;
+use claude_agent_sdk::types::{McpServerConfig, SystemPrompt};
+use claude_agent_sdk::{query_dry_run, ClaudeAgentOptions, ClaudeSDKError, PermissionMode, Settings};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+fn options() -> ClaudeAgentOptions {
+    ClaudeAgentOptions {
+        cli_path: Some(PathBuf::from("/opt/claude/bin/claude")),
+        ..Default::default()
+    }
+}
+
+fn flag_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args.iter().position(|arg| arg == flag).map(|i| args[i + 1].as_str())
+}
+
+#[test]
+fn test_invocation_reflects_options() {
+    let dir = tempfile::tempdir().unwrap();
+    let invocation = ClaudeAgentOptions {
+        allowed_tools: vec!["Read".to_string(), "Grep".to_string()],
+        permission_mode: Some(PermissionMode::AcceptEdits),
+        system_prompt: Some(SystemPrompt::Text("Be brief.".to_string())),
+        max_turns: Some(3),
+        cwd: Some(dir.path().to_path_buf()),
+        env: HashMap::from([("DEBUG".to_string(), "1".to_string())]),
+        ..options()
+    }
+    .to_invocation()
+    .unwrap();
+
+    assert_eq!(invocation.program, PathBuf::from("/opt/claude/bin/claude"));
+    assert_eq!(flag_value(&invocation.args, "--allowedTools"), Some("Read,Grep"));
+    assert_eq!(flag_value(&invocation.args, "--permission-mode"), Some("acceptEdits"));
+    assert_eq!(flag_value(&invocation.args, "--system-prompt"), Some("Be brief."));
+    assert_eq!(flag_value(&invocation.args, "--max-turns"), Some("3"));
+    assert_eq!(flag_value(&invocation.args, "--input-format"), Some("stream-json"));
+    assert_eq!(invocation.cwd.as_deref(), Some(dir.path()));
+    assert_eq!(invocation.env["DEBUG"], "1");
+    assert_eq!(invocation.env["CLAUDE_CODE_ENTRYPOINT"], "sdk-rust");
+    assert!(invocation.stdin.is_empty());
+}
+
+#[test]
+fn test_invocation_redacts_secrets() {
+    let invocation = ClaudeAgentOptions {
+        env: HashMap::from([
+            ("ANTHROPIC_API_KEY".to_string(), "sk-ant-123".to_string()),
+            ("GITHUB_TOKEN".to_string(), "ghp_456".to_string()),
+            ("LOG_LEVEL".to_string(), "debug".to_string()),
+            ("DATABASE_URL".to_string(), "postgres://user:pw@host/db".to_string()),
+        ]),
+        mcp_servers: HashMap::from([
+            (
+                "docs".to_string(),
+                McpServerConfig::HTTP {
+                    url: "https://docs.example.com/mcp".to_string(),
+                    headers: Some(HashMap::from([
+                        ("Authorization".to_string(), "Bearer abc".to_string()),
+                        ("accept".to_string(), "application/json".to_string()),
+                        ("X-Custom".to_string(), "tok_789".to_string()),
+                    ])),
+                },
+            ),
+            (
+                "db".to_string(),
+                McpServerConfig::Stdio {
+                    command: "db-mcp".to_string(),
+                    args: None,
+                    env: Some(HashMap::from([("DB_PASSWORD".to_string(), "hunter2".to_string())])),
+                },
+            ),
+        ]),
+        ..options()
+    }
+    .to_invocation()
+    .unwrap();
+
+    assert_eq!(invocation.env["ANTHROPIC_API_KEY"], REDACTED);
+    assert_eq!(invocation.env["GITHUB_TOKEN"], REDACTED);
+    assert_eq!(invocation.env["LOG_LEVEL"], "debug");
+    assert_eq!(invocation.env["DATABASE_URL"], REDACTED);
+    assert_eq!(invocation.env["CLAUDE_CODE_ENTRYPOINT"], "sdk-rust");
+
+    let config: Value = serde_json::from_str(flag_value(&invocation.args, "--mcp-config").unwrap()).unwrap();
+    let servers = &config["mcpServers"];
+    assert_eq!(servers["docs"]["headers"]["Authorization"], REDACTED);
+    assert_eq!(servers["docs"]["headers"]["accept"], "application/json");
+    assert_eq!(servers["docs"]["headers"]["X-Custom"], REDACTED);
+    assert_eq!(servers["docs"]["url"], "https://docs.example.com/mcp");
+    assert_eq!(servers["db"]["env"]["DB_PASSWORD"], REDACTED);
+
+    let rendered = invocation.to_string();
+    assert!(!rendered.contains("sk-ant-123") && !rendered.contains("Bearer abc") && !rendered.contains("hunter2"));
+    assert!(!rendered.contains("user:pw") && !rendered.contains("tok_789"), "{}", rendered);
+}
+
+#[test]
+fn test_query_dry_run() {
+    let invocation = query_dry_run("What's up?", &options()).unwrap();
+
+    assert!(invocation.args.contains(&"--print".to_string()));
+    assert_eq!(invocation.stdin.len(), 1);
+    let prompt: Value = serde_json::from_str(&invocation.stdin[0]).unwrap();
+    assert_eq!(prompt["message"]["content"], "What's up?");
+    assert!(invocation.to_string().starts_with("CLAUDE_AGENT_SDK_VERSION="));
+
+    let invalid = ClaudeAgentOptions {
+        max_turns: Some(0),
+        ..options()
+    };
+    assert!(matches!(query_dry_run("hi", &invalid), Err(ClaudeSDKError::InvalidConfig(_))));
+}
+
+#[test]
+fn test_display_quotes_arguments() {
+    let invocation = ClaudeAgentOptions {
+        system_prompt: Some(SystemPrompt::Text("Don't panic".to_string())),
+        ..options()
+    }
+    .to_invocation()
+    .unwrap();
+
+    assert!(invocation.to_string().contains(r#"--system-prompt 'Don'\''t panic'"#), "{}", invocation);
+}
+
+#[test]
+fn test_preset_system_prompt() {
+    let invocation = ClaudeAgentOptions {
+        system_prompt: Some(SystemPrompt::Preset {
+            preset: "claude_code".to_string(),
+            append: Some("Answer in {language}.".to_string()),
+        }),
+        system_prompt_vars: HashMap::from([("language".to_string(), "French".to_string())]),
+        ..options()
+    }
+    .to_invocation()
+    .unwrap();
+    assert_eq!(flag_value(&invocation.args, "--system-prompt"), None);
+    assert_eq!(flag_value(&invocation.args, "--append-system-prompt"), Some("Answer in French."));
+
+    let unknown = ClaudeAgentOptions {
+        system_prompt: Some(SystemPrompt::Preset {
+            preset: "pirate".to_string(),
+            append: None,
+        }),
+        ..options()
+    };
+    let err = unknown.to_invocation().unwrap_err();
+    assert!(err.to_string().contains("unknown system prompt preset 'pirate'"), "{}", err);
+    assert_eq!(unknown.problems(), ["unknown system prompt preset 'pirate'"]);
+}
+
+#[test]
+fn test_system_prompt_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("prompt.md");
+    std::fs::write(&path, "You review {project} code. Reply as {\"verdict\": ...}.").unwrap();
+
+    let with_vars = |system_prompt| ClaudeAgentOptions {
+        system_prompt: Some(system_prompt),
+        system_prompt_vars: HashMap::from([("project".to_string(), "Rust".to_string())]),
+        ..options()
+    };
+
+    let invocation = with_vars(SystemPrompt::File(path.clone())).to_invocation().unwrap();
+    assert_eq!(
+        flag_value(&invocation.args, "--system-prompt"),
+        Some("You review Rust code. Reply as {\"verdict\": ...}.")
+    );
+
+    let invocation = with_vars(SystemPrompt::AppendFile(path.clone())).to_invocation().unwrap();
+    assert_eq!(flag_value(&invocation.args, "--system-prompt"), None);
+    assert!(flag_value(&invocation.args, "--append-system-prompt").unwrap().starts_with("You review Rust"));
+
+    // Without vars the file is passed as written
+    let invocation = ClaudeAgentOptions {
+        system_prompt: Some(SystemPrompt::File(path.clone())),
+        ..options()
+    }
+    .to_invocation()
+    .unwrap();
+    assert!(flag_value(&invocation.args, "--system-prompt").unwrap().contains("{project}"));
+
+    let missing = ClaudeAgentOptions {
+        system_prompt: Some(SystemPrompt::File(dir.path().join("missing.md"))),
+        ..options()
+    };
+    assert!(matches!(missing.to_invocation(), Err(ClaudeSDKError::InvalidConfig(_))));
+    assert_eq!(missing.problems().len(), 1);
+
+    std::fs::write(&path, "  \n").unwrap();
+    let err = with_vars(SystemPrompt::File(path.clone())).to_invocation().unwrap_err();
+    assert!(err.to_string().contains("is empty"), "{}", err);
+}
+
+#[test]
+fn test_missing_template_variables_are_reported() {
+    let err = ClaudeAgentOptions {
+        system_prompt: Some(SystemPrompt::Text("{greeting}, {name}! {greeting} again.".to_string())),
+        system_prompt_vars: HashMap::from([("unused".to_string(), "x".to_string())]),
+        ..options()
+    }
+    .to_invocation()
+    .unwrap_err();
+    assert!(err.to_string().contains("no value for template variables {greeting}, {name}"), "{}", err);
+}
+
+#[test]
+fn test_thinking_fallback_and_schema_flags() {
+    let schema = serde_json::json!({"type": "object", "properties": {"answer": {"type": "string"}}});
+    let options = ClaudeAgentOptions {
+        model: Some("claude-opus-4-1".to_string()),
+        fallback_model: Some("claude-sonnet-4-5".to_string()),
+        max_thinking_tokens: Some(8000),
+        json_schema: Some(schema.clone()),
+        ..options()
+    };
+    let invocation = options.to_invocation().unwrap();
+
+    assert_eq!(flag_value(&invocation.args, "--fallback-model"), Some("claude-sonnet-4-5"));
+    assert_eq!(flag_value(&invocation.args, "--max-thinking-tokens"), Some("8000"));
+    let passed: Value = serde_json::from_str(flag_value(&invocation.args, "--json-schema").unwrap()).unwrap();
+    assert_eq!(passed, schema);
+
+    let conflicting = ClaudeAgentOptions {
+        fallback_model: options.model.clone(),
+        json_schema: Some(Value::String("not a schema".to_string())),
+        ..options
+    };
+    assert_eq!(
+        conflicting.problems(),
+        ["fallback_model must differ from model", "json_schema must be a JSON object"]
+    );
+}
+
+#[test]
+fn test_inline_settings_are_shown_as_redacted_json() {
+    let mut settings = Settings::default();
+    settings.permissions.deny.push("Read(./.env)".to_string());
+    settings.env.insert("ANTHROPIC_API_KEY".to_string(), "sk-secret".to_string());
+    settings.env.insert("DEBUG".to_string(), "1".to_string());
+    let options = ClaudeAgentOptions {
+        settings: Some(settings.into()),
+        ..options()
+    };
+    let invocation = options.to_invocation().unwrap();
+
+    let passed: Value = serde_json::from_str(flag_value(&invocation.args, "--settings").unwrap()).unwrap();
+    assert_eq!(passed["permissions"]["deny"][0], "Read(./.env)");
+    assert_eq!(passed["env"]["ANTHROPIC_API_KEY"], REDACTED);
+    assert_eq!(passed["env"]["DEBUG"], "1");
+
+    let options = ClaudeAgentOptions {
+        settings: Some("/etc/claude/settings.json".into()),
+        ..options
+    };
+    let invocation = options.to_invocation().unwrap();
+    assert_eq!(flag_value(&invocation.args, "--settings"), Some("/etc/claude/settings.json"));
+}
+
+#[test]
+fn test_can_use_tool_answers_prompts_over_stdio() {
+    let mut options = options();
+    assert_eq!(flag_value(&options.to_invocation().unwrap().args, "--permission-prompt-tool"), None);
+
+    options.can_use_tool = Some(std::sync::Arc::new(|_tool, _input, _context| {
+        Box::pin(async {
+            claude_agent_sdk::PermissionResult::Allow {
+                updated_input: None,
+                updated_permissions: None,
+            }
+        })
+    }));
+    assert_eq!(flag_value(&options.to_invocation().unwrap().args, "--permission-prompt-tool"), Some("stdio"));
+    let one_shot = query_dry_run("hello", &options).unwrap();
+    assert_eq!(flag_value(&one_shot.args, "--permission-prompt-tool"), None);
+
+    options.permission_prompt_tool_name = Some("mcp__approvals__prompt".to_string());
+    assert_eq!(
+        flag_value(&options.to_invocation().unwrap().args, "--permission-prompt-tool"),
+        Some("mcp__approvals__prompt")
+    );
+}
@@ -1,7 +1,10 @@
 //! Tests for message parser
 
-use claude_agent_sdk::message_parser::parse_message;
-use claude_agent_sdk::types::{ContentBlock, Message};
+use claude_agent_sdk::message_parser::{parse_message, parse_message_strict};
+use claude_agent_sdk::types::{
+    CompactTrigger, ContentBlock, ContentBlockDelta, ImageSource, McpConnectionStatus, Message, PermissionMode,
+    ResultSubtype, StreamEvent, SystemMessage,
+};
 use serde_json::json;
 
 #[test]
@@ -54,7 +57,7 @@ fn test_parse_thinking_block() {
             match &message.message.content[0] {
                 ContentBlock::Thinking { thinking, signature } => {
                     assert_eq!(thinking, "Let me think about this...");
-                    assert_eq!(signature, "sig123");
+                    assert_eq!(signature.as_deref(), Some("sig123"));
                 }
                 _ => panic!("Expected ThinkingBlock"),
             }
@@ -149,6 +152,7 @@ fn test_parse_user_message() {
         Message::User {
             message,
             parent_tool_use_id,
+            ..
         } => {
             assert_eq!(message.message.role, "user");
             assert_eq!(
@@ -171,7 +175,7 @@ fn test_parse_system_message() {
 
     let result = parse_message(data).unwrap();
     match result {
-        Message::System { subtype, data } => {
+        Message::System(SystemMessage::Other { subtype, data }) => {
             assert_eq!(subtype, "info");
             assert_eq!(data["message"], "System notification");
         }
@@ -179,6 +183,67 @@ fn test_parse_system_message() {
     }
 }
 
+#[test]
+fn test_parse_system_init_message() {
+    let data = json!({
+        "type": "system",
+        "subtype": "init",
+        "session_id": "session_123",
+        "cwd": "/work",
+        "model": "claude-sonnet-4-5",
+        "permissionMode": "acceptEdits",
+        "apiKeySource": "none",
+        "tools": ["Read", "Bash"],
+        "mcp_servers": [
+            {"name": "calc", "status": "connected"},
+            {"name": "search", "status": "failed"}
+        ],
+        "slash_commands": ["compact", "review"]
+    });
+
+    match parse_message(data).unwrap() {
+        Message::System(SystemMessage::Init(init)) => {
+            assert_eq!(init.session_id, "session_123");
+            assert_eq!(init.permission_mode, Some(PermissionMode::AcceptEdits));
+            assert_eq!(init.tools, vec!["Read", "Bash"]);
+            assert_eq!(init.slash_commands, vec!["compact", "review"]);
+            assert_eq!(init.mcp_server("calc").unwrap().status, McpConnectionStatus::Connected);
+            assert_eq!(init.mcp_server("search").unwrap().status, McpConnectionStatus::Failed);
+            assert!(init.mcp_server("missing").is_none());
+        }
+        other => panic!("Expected init message, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_parse_compact_boundary_message() {
+    let data = json!({
+        "type": "system",
+        "subtype": "compact_boundary",
+        "session_id": "session_123",
+        "uuid": "uuid_1",
+        "compact_metadata": {"trigger": "auto", "pre_tokens": 150000}
+    });
+
+    match parse_message(data).unwrap() {
+        Message::System(SystemMessage::CompactBoundary(boundary)) => {
+            assert_eq!(boundary.compact_metadata.trigger, CompactTrigger::Auto);
+            assert_eq!(boundary.compact_metadata.pre_tokens, 150000);
+        }
+        other => panic!("Expected compact_boundary message, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_parse_malformed_init_falls_back_to_raw() {
+    let data = json!({"type": "system", "subtype": "init", "tools": "not a list"});
+
+    match parse_message(data).unwrap() {
+        Message::System(message @ SystemMessage::Other { .. }) => assert_eq!(message.subtype(), "init"),
+        other => panic!("Expected raw system message, got {:?}", other),
+    }
+}
+
 #[test]
 fn test_parse_result_message() {
     let data = json!({
@@ -222,6 +287,119 @@ fn test_parse_result_message() {
     }
 }
 
+#[test]
+fn test_parse_result_usage_breakdown() {
+    let data = json!({
+        "type": "result",
+        "subtype": "error_max_turns",
+        "duration_ms": 1500,
+        "duration_api_ms": 1000,
+        "is_error": true,
+        "num_turns": 10,
+        "session_id": "session_123",
+        "usage": {
+            "input_tokens": 100,
+            "output_tokens": 50,
+            "cache_creation_input_tokens": null,
+            "cache_read_input_tokens": 2000,
+            "server_tool_use": {"web_search_requests": 2},
+            "service_tier": "standard"
+        },
+        "modelUsage": {
+            "claude-sonnet-4-5": {
+                "inputTokens": 90,
+                "outputTokens": 45,
+                "cacheReadInputTokens": 2000,
+                "cacheCreationInputTokens": 0,
+                "webSearchRequests": 2,
+                "costUSD": 0.02,
+                "contextWindow": 200000
+            },
+            "claude-haiku-4-5": {"inputTokens": 10, "outputTokens": 5, "cacheReadInputTokens": null, "costUSD": 0.001},
+            "claude-opus-4-1": {"inputTokens": null, "outputTokens": null, "webSearchRequests": null, "costUSD": null}
+        }
+    });
+
+    match parse_message(data).unwrap() {
+        Message::Result {
+            subtype,
+            usage,
+            model_usage,
+            ..
+        } => {
+            assert_eq!(subtype, ResultSubtype::ErrorMaxTurns);
+            let usage = usage.unwrap();
+            assert_eq!(usage.cache_creation_input_tokens, None);
+            assert_eq!(usage.total_tokens(), 2150);
+            assert_eq!(usage.server_tool_use.unwrap().web_search_requests, Some(2));
+            assert_eq!(usage.service_tier.as_deref(), Some("standard"));
+
+            let model_usage = model_usage.unwrap();
+            assert_eq!(model_usage.len(), 3);
+            let sonnet = &model_usage["claude-sonnet-4-5"];
+            assert_eq!(sonnet.cache_read_input_tokens, Some(2000));
+            assert_eq!(sonnet.context_window, Some(200000));
+            assert_eq!(model_usage["claude-haiku-4-5"].cost_usd, Some(0.001));
+            assert_eq!(model_usage["claude-haiku-4-5"].cache_read_input_tokens, None);
+            let opus = &model_usage["claude-opus-4-1"];
+            assert_eq!((opus.input_tokens, opus.output_tokens, opus.web_search_requests), (None, None, None));
+            assert_eq!(opus.cost_usd, None);
+        }
+        _ => panic!("Expected Result message"),
+    }
+}
+
+#[test]
+fn test_parse_result_with_structured_output() {
+    let data = json!({
+        "type": "result",
+        "subtype": "success",
+        "duration_ms": 900,
+        "duration_api_ms": 800,
+        "is_error": false,
+        "num_turns": 1,
+        "session_id": "session-1",
+        "result": "{\"answer\": \"4\"}",
+        "structured_output": {"answer": "4"}
+    });
+
+    let message = parse_message(data.clone()).unwrap();
+    match &message {
+        Message::Result {
+            structured_output,
+            extra,
+            ..
+        } => {
+            assert_eq!(structured_output, &Some(json!({"answer": "4"})));
+            assert!(extra.is_empty());
+        }
+        _ => panic!("Expected Result message"),
+    }
+    assert_eq!(serde_json::to_value(&message).unwrap(), data);
+}
+
+#[test]
+fn test_parse_result_with_malformed_usage() {
+    let data = json!({
+        "type": "result",
+        "subtype": "success",
+        "duration_ms": 1,
+        "duration_api_ms": 1,
+        "is_error": false,
+        "num_turns": 1,
+        "session_id": "session_123",
+        "usage": "not an object"
+    });
+
+    match parse_message(data).unwrap() {
+        Message::Result { subtype, usage, .. } => {
+            assert_eq!(subtype, ResultSubtype::Success);
+            assert!(usage.is_none());
+        }
+        _ => panic!("Expected Result message"),
+    }
+}
+
 #[test]
 fn test_parse_stream_event() {
     let data = json!({
@@ -243,16 +421,40 @@ fn test_parse_stream_event() {
             session_id,
             event,
             parent_tool_use_id,
+            ..
         } => {
             assert_eq!(uuid, "event_123");
             assert_eq!(session_id, "session_456");
-            assert_eq!(event["type"], "content_block_delta");
+            assert_eq!(
+                event,
+                StreamEvent::ContentBlockDelta {
+                    index: 0,
+                    delta: ContentBlockDelta::TextDelta {
+                        text: "Hello".to_string()
+                    },
+                }
+            );
             assert_eq!(parent_tool_use_id, None);
         }
         _ => panic!("Expected StreamEvent message"),
     }
 }
 
+#[test]
+fn test_parse_unknown_stream_event_keeps_raw_value() {
+    let data = json!({
+        "type": "stream_event",
+        "uuid": "event_123",
+        "session_id": "session_456",
+        "event": {"type": "ping"}
+    });
+
+    match parse_message(data).unwrap() {
+        Message::StreamEvent { event, .. } => assert_eq!(event, StreamEvent::Unknown(json!({"type": "ping"}))),
+        _ => panic!("Expected StreamEvent message"),
+    }
+}
+
 #[test]
 fn test_parse_multiple_content_blocks() {
     let data = json!({
@@ -306,8 +508,13 @@ fn test_parse_invalid_message_type() {
         "data": "test"
     });
 
-    let result = parse_message(data);
+    let result = parse_message_strict(data.clone());
     assert!(result.is_err());
+
+    match parse_message(data.clone()).unwrap() {
+        Message::Unknown { raw } => assert_eq!(raw, data),
+        other => panic!("Expected Unknown message, got {:?}", other),
+    }
 }
 
 #[test]
@@ -339,6 +546,68 @@ fn test_parse_invalid_content_block_type() {
         }
     });
 
-    let result = parse_message(data);
+    let result = parse_message_strict(data.clone());
     assert!(result.is_err());
+
+    match parse_message(data).unwrap() {
+        Message::Assistant { message, .. } => assert_eq!(
+            message.message.content,
+            vec![ContentBlock::Unknown {
+                raw: json!({"type": "invalid_block", "data": "test"})
+            }]
+        ),
+        other => panic!("Expected Assistant message, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_parse_documented_extra_block_types() {
+    let data = json!({
+        "type": "assistant",
+        "message": {
+            "content": [
+                {"type": "redacted_thinking", "data": "opaque"},
+                {"type": "server_tool_use", "id": "srv_1", "name": "web_search", "input": {"query": "rust"}},
+                {"type": "web_search_tool_result", "tool_use_id": "srv_1", "content": [{"type": "web_search_result", "url": "https://www.rust-lang.org"}]},
+                {"type": "image", "source": {"type": "base64", "media_type": "image/png", "data": "iVBOR"}},
+                {"type": "image", "source": {"type": "url", "url": "https://example.com/a.png"}}
+            ],
+            "model": "claude-sonnet-4"
+        }
+    });
+
+    match parse_message_strict(data).unwrap() {
+        Message::Assistant { message, .. } => {
+            let content = message.message.content;
+            assert_eq!(content[0], ContentBlock::RedactedThinking { data: "opaque".to_string() });
+            assert!(matches!(&content[1], ContentBlock::ServerToolUse { name, input, .. } if name == "web_search" && input["query"] == "rust"));
+            assert!(matches!(&content[2], ContentBlock::WebSearchToolResult { tool_use_id, .. } if tool_use_id == "srv_1"));
+            assert!(matches!(&content[3], ContentBlock::Image { source: ImageSource::Base64 { media_type, .. } } if media_type == "image/png"));
+            assert!(matches!(&content[4], ContentBlock::Image { source: ImageSource::Url { .. } }));
+        }
+        other => panic!("Expected Assistant message, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_parse_malformed_known_block_is_an_error() {
+    let data = json!({
+        "type": "assistant",
+        "message": {
+            "content": [{"type": "redacted_thinking"}],
+            "model": "claude-sonnet-4"
+        }
+    });
+
+    assert!(parse_message(data).is_err());
+}
+
+#[test]
+fn test_unknown_variants_serialize_as_received() {
+    let raw = json!({"type": "rate_limit_event", "retry_after": 5});
+    let message = parse_message(raw.clone()).unwrap();
+    assert_eq!(serde_json::to_value(&message).unwrap(), raw);
+
+    let block: ContentBlock = serde_json::from_value(json!({"type": "document", "title": "a.pdf"})).unwrap();
+    assert_eq!(serde_json::to_value(&block).unwrap(), json!({"type": "document", "title": "a.pdf"}));
 }
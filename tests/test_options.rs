@@ -0,0 +1,221 @@
+//! Tests for the options builder and validation
+
+use claude_agent_sdk::types::{McpServerConfig, PreToolUseHookInput, RecoveryPolicy};
+use claude_agent_sdk::{
+    ClaudeAgentOptions, ClaudeSDKError, HookEvent, HookJSONOutput, PermissionMode, PermissionResult, SettingSource,
+    SystemPrompt,
+};
+use std::time::Duration;
+
+#[test]
+fn test_builder_sets_fields() {
+    let dir = tempfile::tempdir().unwrap();
+    let options = ClaudeAgentOptions::builder()
+        .allowed_tools(["Read", "Grep"])
+        .allowed_tools(vec!["Glob".to_string()])
+        .permission_mode(PermissionMode::AcceptEdits)
+        .max_turns(3)
+        .cwd(dir.path())
+        .env("DEBUG", "1")
+        .model("claude-sonnet-4-5")
+        .can_use_tool(|_tool, _input, _ctx| async {
+            PermissionResult::Allow {
+                updated_input: None,
+                updated_permissions: None,
+            }
+        })
+        .hook(Some("Bash"), |_input: PreToolUseHookInput, _id, _ctx| async { HookJSONOutput::default() })
+        .build()
+        .unwrap();
+
+    assert_eq!(options.allowed_tools, ["Read", "Grep", "Glob"]);
+    assert_eq!(options.permission_mode, Some(PermissionMode::AcceptEdits));
+    assert_eq!(options.max_turns, Some(3));
+    assert_eq!(options.cwd.as_deref(), Some(dir.path()));
+    assert_eq!(options.env["DEBUG"], "1");
+    assert!(options.can_use_tool.is_some());
+    assert_eq!(options.hooks[&HookEvent::PreToolUse].len(), 1);
+}
+
+#[test]
+fn test_build_lists_every_problem() {
+    let err = ClaudeAgentOptions::builder()
+        .continue_conversation(true)
+        .resume("session-1")
+        .allowed_tools(["Bash", "Read"])
+        .disallowed_tools(["Bash"])
+        .cwd("/definitely/not/a/real/dir")
+        .max_turns(0)
+        .build()
+        .unwrap_err();
+
+    let ClaudeSDKError::InvalidConfig(message) = err else {
+        panic!("expected InvalidConfig, got {:?}", err);
+    };
+    assert!(message.starts_with("4 problems: "), "{}", message);
+    assert!(message.contains("continue_conversation and resume"));
+    assert!(message.contains("tool 'Bash' is both allowed and disallowed"));
+    assert!(message.contains("/definitely/not/a/real/dir"));
+    assert!(message.contains("max_turns must be positive, got 0"));
+}
+
+#[test]
+fn test_sdk_mcp_servers_from_config_files_are_valid() {
+    // An SDK server loaded from a config file never has an instance
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("agent.toml");
+    std::fs::write(&path, "[mcp_servers.calc]\ntype = \"sdk\"\nname = \"calc\"\n").unwrap();
+
+    let options = ClaudeAgentOptions::from_file(&path).unwrap();
+    assert!(matches!(options.mcp_servers["calc"], McpServerConfig::SDK { instance: None, .. }));
+    assert!(options.validate().is_ok(), "{:?}", options.problems());
+}
+
+#[test]
+fn test_fork_session_requires_resume() {
+    let options = ClaudeAgentOptions {
+        fork_session: true,
+        ..Default::default()
+    };
+    assert_eq!(options.problems(), ["fork_session requires resume"]);
+    assert!(options.validate().is_err());
+
+    let options = ClaudeAgentOptions::builder().resume("session-1").fork_session(true).build();
+    assert!(options.is_ok());
+}
+
+#[test]
+fn test_default_options_are_valid() {
+    assert!(ClaudeAgentOptions::default().problems().is_empty());
+    assert!(ClaudeAgentOptions::builder().build().is_ok());
+}
+
+#[test]
+fn test_from_toml_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("agent.toml");
+    std::fs::write(
+        &path,
+        r#"
+allowed_tools = ["Read", "Grep"]
+permission_mode = "acceptEdits"
+max_turns = 4
+system_prompt = { type = "preset", preset = "claude_code", append = "Be brief." }
+setting_sources = ["project"]
+
+[env]
+DEBUG = "1"
+
+[extra_args]
+verbose = true
+output-style = "concise"
+
+[mcp_servers.docs]
+type = "http"
+url = "https://docs.example.com/mcp"
+
+[agents.reviewer]
+description = "Reviews code"
+prompt = "You review code."
+tools = ["Read"]
+
+[recovery_policy]
+max_retries = 5
+initial_backoff_ms = 250
+"#,
+    )
+    .unwrap();
+
+    let options = ClaudeAgentOptions::from_file(&path).unwrap();
+    assert_eq!(options.allowed_tools, ["Read", "Grep"]);
+    assert_eq!(options.permission_mode, Some(PermissionMode::AcceptEdits));
+    assert_eq!(options.max_turns, Some(4));
+    assert!(matches!(options.system_prompt, Some(SystemPrompt::Preset { ref preset, .. }) if preset == "claude_code"));
+    assert_eq!(options.setting_sources, Some(vec![SettingSource::Project]));
+    assert_eq!(options.env["DEBUG"], "1");
+    assert_eq!(options.extra_args["verbose"], None);
+    assert_eq!(options.extra_args["output-style"].as_deref(), Some("concise"));
+    assert!(matches!(options.mcp_servers["docs"], McpServerConfig::HTTP { ref url, .. } if url.ends_with("/mcp")));
+    assert_eq!(options.agents["reviewer"].tools, Some(vec!["Read".to_string()]));
+    let policy = options.recovery_policy.unwrap();
+    assert_eq!(policy.max_retries, 5);
+    assert_eq!(policy.initial_backoff, Duration::from_millis(250));
+    assert_eq!(policy.max_backoff, RecoveryPolicy::default().max_backoff);
+}
+
+#[test]
+fn test_from_json_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("agent.json");
+    std::fs::write(&path, r#"{"model": "claude-sonnet-4-5", "max_budget_usd": 1.5, "extra_args": {"debug": null}}"#)
+        .unwrap();
+
+    let options = ClaudeAgentOptions::from_file(&path).unwrap();
+    assert_eq!(options.model.as_deref(), Some("claude-sonnet-4-5"));
+    assert_eq!(options.max_budget_usd, Some(1.5));
+    assert_eq!(options.extra_args["debug"], None);
+}
+
+#[test]
+fn test_config_errors_report_key_path() {
+    let err = ClaudeAgentOptions::from_toml_str("[agents.reviewer]\ndescription = 3\nprompt = \"p\"\n").unwrap_err();
+    assert!(err.to_string().contains("agents.reviewer.description: invalid type"), "{}", err);
+
+    let err = ClaudeAgentOptions::from_json_str(r#"{"recovery_policy": {"max_retries": "many"}}"#).unwrap_err();
+    assert!(err.to_string().contains("recovery_policy.max_retries: invalid type"), "{}", err);
+
+    let err = ClaudeAgentOptions::from_json_str(r#"{"max_turn": 3}"#).unwrap_err();
+    assert!(err.to_string().contains("unknown field `max_turn`"), "{}", err);
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("agent.json");
+    std::fs::write(&path, r#"{"max_turns": "three"}"#).unwrap();
+    let err = ClaudeAgentOptions::from_file(&path).unwrap_err();
+    assert!(err.to_string().contains(&format!("{}: max_turns: invalid type", path.display())), "{}", err);
+
+    let err = ClaudeAgentOptions::from_file(dir.path().join("agent.yaml")).unwrap_err();
+    assert!(matches!(err, ClaudeSDKError::InvalidConfig(_)), "{:?}", err);
+    let err = ClaudeAgentOptions::from_file(dir.path().join("missing.toml")).unwrap_err();
+    assert!(matches!(err, ClaudeSDKError::IO(_)), "{:?}", err);
+}
+
+#[test]
+fn test_env_overrides_layer_on_file_options() {
+    let mut options = ClaudeAgentOptions::from_toml_str("model = \"claude-sonnet-4-5\"\nmax_turns = 4\n").unwrap();
+    options.can_use_tool = ClaudeAgentOptions::builder()
+        .can_use_tool(|_tool, _input, _ctx| async {
+            PermissionResult::Deny {
+                message: "no".to_string(),
+                interrupt: false,
+            }
+        })
+        .build()
+        .unwrap()
+        .can_use_tool;
+
+    options
+        .apply_env_vars([
+            ("CLAUDE_SDK_MAX_TURNS", "8"),
+            ("CLAUDE_SDK_ALLOWED_TOOLS", "Read, Grep"),
+            ("CLAUDE_SDK_ENV__DEBUG", "1"),
+            ("CLAUDE_SDK_RECOVERY_POLICY__max_retries", "2"),
+            ("CLAUDE_SDK_RESUME", "12345"),
+            ("CLAUDE_SDK_STRICT_PARSING", "true"),
+            ("HOME", "/home/dev"),
+        ])
+        .unwrap();
+
+    assert_eq!(options.model.as_deref(), Some("claude-sonnet-4-5"));
+    assert_eq!(options.max_turns, Some(8));
+    assert_eq!(options.allowed_tools, ["Read", "Grep"]);
+    assert_eq!(options.env["DEBUG"], "1");
+    assert_eq!(options.recovery_policy.unwrap().max_retries, 2);
+    assert_eq!(options.resume.as_deref(), Some("12345"));
+    assert!(options.strict_parsing);
+    assert!(options.can_use_tool.is_some());
+
+    let err = ClaudeAgentOptions::default()
+        .apply_env_vars([("CLAUDE_SDK_MAX_TURNS", "lots")])
+        .unwrap_err();
+    assert!(err.to_string().contains("CLAUDE_SDK_MAX_TURNS: max_turns: invalid type"), "{}", err);
+}
@@ -9,10 +9,7 @@ fn test_permission_result_allow() {
         updated_input: None,
         updated_permissions: None,
     };
-    match result {
-        PermissionResult::Allow { .. } => assert!(true),
-        _ => panic!("Expected Allow variant"),
-    }
+    assert!(matches!(result, PermissionResult::Allow { .. }), "Expected Allow variant");
 }
 
 #[test]
@@ -44,7 +41,7 @@ fn test_permission_result_deny() {
     match result {
         PermissionResult::Deny { message, interrupt } => {
             assert_eq!(message, "Access denied");
-            assert_eq!(interrupt, false);
+            assert!(!interrupt);
         }
         _ => panic!("Expected Deny variant"),
     }
@@ -59,7 +56,7 @@ fn test_permission_result_deny_with_interrupt() {
     match result {
         PermissionResult::Deny { message, interrupt } => {
             assert_eq!(message, "Critical error");
-            assert_eq!(interrupt, true);
+            assert!(interrupt);
         }
         _ => panic!("Expected Deny variant"),
     }
@@ -69,6 +66,7 @@ fn test_permission_result_deny_with_interrupt() {
 fn test_tool_permission_context_creation() {
     let context = ToolPermissionContext {
         suggestions: vec![],
+        ..Default::default()
     };
     assert_eq!(context.suggestions.len(), 0);
 }
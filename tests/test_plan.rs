@@ -0,0 +1,129 @@
+//! Tests for PlanWorkflow against a fake CLI script
+
+#![cfg(unix)]
+
+use claude_agent_sdk::types::{PermissionUpdate, ToolPermissionContext};
+use claude_agent_sdk::{ClaudeAgentOptions, PermissionMode, PermissionResult, PlanDecision, PlanWorkflow};
+use serde_json::json;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex as StdMutex};
+
+fn fake_cli_options(plan: &str) -> ClaudeAgentOptions {
+    let mut options = ClaudeAgentOptions {
+        cli_path: Some(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/fake_claude.sh")),
+        ..Default::default()
+    };
+    options.env.insert("FAKE_CLAUDE_PLAN".to_string(), plan.to_string());
+    options
+}
+
+fn context() -> ToolPermissionContext {
+    ToolPermissionContext::default()
+}
+
+#[tokio::test]
+async fn test_approved_plan_switches_to_execution_mode() {
+    let workflow = PlanWorkflow::new(fake_cli_options("Add tests"), |_plan: String| async { PlanDecision::Approve });
+    let mut client = workflow.connect().await.unwrap();
+
+    let run = workflow.run(&mut client, "Add input validation").await.unwrap();
+    assert_eq!(run.proposals, ["Add tests"]);
+    assert_eq!(run.approved_plan.as_deref(), Some("Add tests"));
+    assert!(run.result.text.ends_with("mode=acceptEdits executed=Add tests"), "{}", run.result.text);
+
+    // Later turns run in the execution mode, so no plan is proposed
+    let run = workflow.run(&mut client, "Now update the docs").await.unwrap();
+    assert!(run.proposals.is_empty());
+    assert_eq!(run.approved_plan, None);
+
+    client.disconnect().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_rejected_plan_is_revised_then_edited() {
+    let seen = Arc::new(StdMutex::new(Vec::new()));
+    let decisions = seen.clone();
+    let workflow = PlanWorkflow::new(fake_cli_options("Draft"), move |plan: String| {
+        let decisions = decisions.clone();
+        async move {
+            let mut seen = decisions.lock().unwrap();
+            seen.push(plan);
+            if seen.len() == 1 {
+                PlanDecision::Reject {
+                    feedback: "add tests".to_string(),
+                }
+            } else {
+                PlanDecision::Edit {
+                    plan: "Final plan".to_string(),
+                }
+            }
+        }
+    })
+    .execution_mode(PermissionMode::BypassPermissions);
+    let mut client = workflow.connect().await.unwrap();
+
+    let run = workflow.run(&mut client, "Refactor the parser").await.unwrap();
+    assert_eq!(run.proposals, ["Draft", "Draft + add tests"]);
+    assert_eq!(*seen.lock().unwrap(), run.proposals);
+    assert_eq!(run.approved_plan.as_deref(), Some("Final plan"));
+    assert!(run.result.text.ends_with("mode=bypassPermissions executed=Final plan"), "{}", run.result.text);
+
+    client.disconnect().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_unapproved_plan_stays_in_plan_mode() {
+    let workflow = PlanWorkflow::new(fake_cli_options("Draft"), |_plan: String| async {
+        PlanDecision::Reject {
+            feedback: "no".to_string(),
+        }
+    });
+    let mut client = workflow.connect().await.unwrap();
+
+    let run = workflow.run(&mut client, "Refactor").await.unwrap();
+    assert_eq!(run.proposals.len(), 3);
+    assert_eq!(run.approved_plan, None);
+    assert!(run.result.text.ends_with("mode=plan"), "{}", run.result.text);
+
+    client.disconnect().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_workflow_options() {
+    let workflow = PlanWorkflow::new(fake_cli_options("Plan"), |_plan: String| async { PlanDecision::Approve });
+    let options = workflow.options();
+    assert_eq!(options.permission_mode, Some(PermissionMode::Plan));
+    let args = options.to_invocation().unwrap().args;
+    assert!(args.windows(2).any(|pair| pair == ["--permission-prompt-tool", "stdio"]), "{:?}", args);
+
+    let callback = options.can_use_tool.unwrap();
+    let result = callback("ExitPlanMode".to_string(), json!({"plan": "Plan"}), context()).await;
+    let PermissionResult::Allow {
+        updated_input,
+        updated_permissions,
+    } = result
+    else {
+        panic!("expected the plan to be allowed");
+    };
+    assert_eq!(updated_input.unwrap()["plan"], "Plan");
+    assert!(matches!(
+        updated_permissions.unwrap()[0],
+        PermissionUpdate::SetMode { ref mode, .. } if *mode == PermissionMode::AcceptEdits
+    ));
+
+    let result = callback("Bash".to_string(), json!({"command": "ls"}), context()).await;
+    assert!(matches!(result, PermissionResult::Deny { .. }));
+
+    let mut base = fake_cli_options("Plan");
+    base.can_use_tool = Some(Arc::new(|_tool, _input, _context| {
+        Box::pin(async {
+            PermissionResult::Allow {
+                updated_input: None,
+                updated_permissions: None,
+            }
+        })
+    }));
+    let callback = PlanWorkflow::new(base, |_plan: String| async { PlanDecision::Approve }).options().can_use_tool.unwrap();
+    let result = callback("Bash".to_string(), json!({"command": "ls"}), context()).await;
+    assert!(matches!(result, PermissionResult::Allow { .. }));
+}
@@ -0,0 +1,144 @@
+//! Tests for QueryPool against a fake CLI script
+
+#![cfg(unix)]
+
+use claude_agent_sdk::{ClaudeAgentOptions, ClaudeSDKError, JobOutcome, JobOverrides, PoolJob, QueryPool};
+use futures::StreamExt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+fn fake_cli_options(env: &[(&str, &str)]) -> ClaudeAgentOptions {
+    let mut options = ClaudeAgentOptions {
+        cli_path: Some(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/fake_claude.sh")),
+        ..Default::default()
+    };
+    for (key, value) in env {
+        options.env.insert(key.to_string(), value.to_string());
+    }
+    options
+}
+
+fn read_log(path: &Path) -> Vec<(String, String)> {
+    std::fs::read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| line.split_once(' '))
+        .map(|(event, pid)| (event.to_string(), pid.to_string()))
+        .collect()
+}
+
+#[tokio::test]
+async fn test_pool_caps_concurrency() {
+    let dir = tempfile::tempdir().unwrap();
+    let log = dir.path().join("turns.log");
+    let options = fake_cli_options(&[
+        ("FAKE_CLAUDE_TURN_DELAY", "0.2"),
+        ("FAKE_CLAUDE_LOG", log.to_str().unwrap()),
+    ]);
+
+    let pool = QueryPool::new(options, 2);
+    let mut ids = Vec::new();
+    for i in 0..5 {
+        ids.push(pool.submit(format!("prompt {}", i)).unwrap());
+    }
+    pool.close();
+
+    let outcomes: Vec<JobOutcome> = pool.results().collect().await;
+    let mut finished: Vec<_> = outcomes.iter().map(|o| o.job_id).collect();
+    finished.sort();
+    assert_eq!(finished, ids);
+    for outcome in &outcomes {
+        let turn = outcome.result.as_ref().unwrap();
+        assert!(turn.text.starts_with("turn=1"));
+        assert_eq!(turn.total_cost_usd, Some(0.001));
+    }
+
+    let mut active = 0;
+    let mut max_active = 0;
+    for (event, _) in read_log(&log) {
+        active += if event == "start" { 1 } else { -1 };
+        max_active = max_active.max(active);
+    }
+    assert_eq!(max_active, 2);
+}
+
+#[tokio::test]
+async fn test_pool_runs_higher_priority_first() {
+    let pool = QueryPool::new(fake_cli_options(&[("FAKE_CLAUDE_TURN_DELAY", "0.1")]), 1);
+    let first = pool.submit_job(PoolJob { priority: 10, ..PoolJob::new("first") }).unwrap();
+    let low = pool.submit_job(PoolJob { priority: 0, ..PoolJob::new("low") }).unwrap();
+    let high = pool.submit_job(PoolJob { priority: 5, ..PoolJob::new("high") }).unwrap();
+    pool.close();
+
+    let order: Vec<_> = pool.results().map(|o| o.job_id).collect().await;
+    assert_eq!(order, vec![first, high, low]);
+}
+
+#[tokio::test]
+async fn test_pool_applies_job_overrides() {
+    let dir = tempfile::tempdir().unwrap();
+    let cwd = dir.path().canonicalize().unwrap();
+
+    let pool = QueryPool::new(fake_cli_options(&[]), 2);
+    pool.submit_job(PoolJob {
+        overrides: JobOverrides {
+            cwd: Some(cwd.clone()),
+            model: Some("override-model".to_string()),
+            ..Default::default()
+        },
+        ..PoolJob::new("hello")
+    })
+    .unwrap();
+    pool.close();
+
+    let outcomes: Vec<_> = pool.results().collect().await;
+    assert_eq!(outcomes.len(), 1);
+    let text = &outcomes[0].result.as_ref().unwrap().text;
+    assert!(text.contains("model=override-model"), "unexpected text: {}", text);
+    assert!(text.contains(&format!("cwd={}", cwd.display())), "unexpected text: {}", text);
+}
+
+#[tokio::test]
+async fn test_pool_cancel_shuts_down_children() {
+    let dir = tempfile::tempdir().unwrap();
+    let log = dir.path().join("turns.log");
+    let options = fake_cli_options(&[
+        ("FAKE_CLAUDE_TURN_DELAY", "5"),
+        ("FAKE_CLAUDE_LOG", log.to_str().unwrap()),
+    ]);
+
+    let pool = QueryPool::new(options, 2);
+    for i in 0..4 {
+        pool.submit(format!("prompt {}", i)).unwrap();
+    }
+    let results = pool.results();
+
+    // Wait until both slots are busy with a turn
+    for _ in 0..200 {
+        if read_log(&log).len() >= 2 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(25)).await;
+    }
+    assert_eq!(pool.queued(), 2);
+
+    tokio::time::timeout(Duration::from_secs(10), pool.cancel()).await.unwrap();
+
+    let outcomes: Vec<_> = results.collect().await;
+    assert_eq!(outcomes.len(), 4);
+    assert!(outcomes
+        .iter()
+        .all(|o| matches!(o.result, Err(ClaudeSDKError::Cancelled(_)))));
+
+    for (_, pid) in read_log(&log) {
+        let alive = std::process::Command::new("kill")
+            .args(["-0", &pid])
+            .stderr(std::process::Stdio::null())
+            .status()
+            .unwrap()
+            .success();
+        assert!(!alive, "CLI process {} is still running", pid);
+    }
+
+    assert!(pool.submit("too late").is_err());
+}
@@ -0,0 +1,198 @@
+//! Round-trip tests: messages serialize back to the CLI's stream-json lines
+
+use claude_agent_sdk::message_parser::{parse_message, parse_message_strict};
+use claude_agent_sdk::{ContentBlock, Message, StreamEvent, SystemMessage};
+use serde_json::Value;
+
+const SESSION: &str = include_str!("fixtures/transcripts/session.jsonl");
+
+fn lines() -> Vec<Value> {
+    SESSION
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+#[test]
+fn test_parsed_messages_serialize_as_received() {
+    for line in lines() {
+        let message = parse_message(line.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&message).unwrap(), line, "{:?}", message);
+    }
+}
+
+#[test]
+fn test_deserialized_messages_serialize_as_received() {
+    for line in lines() {
+        let message: Message = serde_json::from_value(line.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&message).unwrap(), line, "{:?}", message);
+    }
+}
+
+#[test]
+fn test_roundtrip_keeps_typed_fields() {
+    let messages: Vec<Message> = lines().into_iter().map(|line| parse_message(line).unwrap()).collect();
+
+    let Message::Assistant {
+        message,
+        session_id,
+        uuid,
+        ..
+    } = &messages[10]
+    else {
+        panic!("expected assistant message");
+    };
+    let inner = &message.message;
+    assert_eq!(inner.id.as_deref(), Some("msg_01XFDUDYJgAACzvnptvVoYEL"));
+    assert_eq!(inner.stop_reason, None);
+    assert_eq!(inner.usage.as_ref().unwrap().cache_read_input_tokens, Some(11620));
+    assert!(inner.usage.as_ref().unwrap().extra.contains_key("cache_creation"));
+    assert!(matches!(inner.content[1], ContentBlock::ToolUse { .. }));
+    assert_eq!(session_id.as_deref(), Some("5f1c2a7e-8a1b-4c3d-9e0f-1a2b3c4d5e6f"));
+    assert_eq!(uuid.as_deref(), Some("28d9e0f1-a2b3-4c4d-9e6f-708192a3b4c5"));
+
+    let Message::StreamEvent {
+        event: StreamEvent::MessageDelta { usage, .. },
+        ..
+    } = &messages[8]
+    else {
+        panic!("expected message_delta");
+    };
+    let usage = usage.as_ref().unwrap();
+    assert_eq!(usage.output_tokens, 87);
+    assert_eq!(usage.input_tokens, None);
+
+    let Message::User { extra, .. } = &messages[11] else {
+        panic!("expected user message");
+    };
+    assert!(extra.contains_key("tool_use_result"));
+
+    assert!(matches!(&messages[15], Message::System(SystemMessage::Other { subtype, .. }) if subtype == "status"));
+    assert!(matches!(messages[16], Message::Unknown { .. }));
+
+    let Message::Result { uuid, extra, .. } = &messages[17] else {
+        panic!("expected result");
+    };
+    assert_eq!(uuid.as_deref(), Some("9f46f708-192a-43b4-c5d6-e7f8091a2b3c"));
+    assert_eq!(extra["permission_denials"][0]["tool_name"], "Write");
+}
+
+#[test]
+fn test_strict_parsing_accepts_documented_lines() {
+    for line in lines().into_iter().filter(|line| line["type"] != "tool_progress") {
+        parse_message_strict(line).unwrap();
+    }
+}
+
+#[test]
+fn test_deserializing_malformed_known_types_fails() {
+    for line in [
+        serde_json::json!({"type": "assistant", "message": {"content": "not a list"}, "parent_tool_use_id": null}),
+        serde_json::json!({"type": "assistant"}),
+        serde_json::json!({"type": "result", "subtype": "success"}),
+    ] {
+        let result = serde_json::from_value::<Message>(line.clone());
+        assert!(result.is_err(), "{} parsed as {:?}", line, result);
+    }
+
+    let unknown = serde_json::json!({"type": "telemetry", "data": [1, 2]});
+    let message: Message = serde_json::from_value(unknown.clone()).unwrap();
+    assert!(matches!(message, Message::Unknown { ref raw } if *raw == unknown));
+    assert_eq!(serde_json::to_value(&message).unwrap(), unknown);
+}
+
+#[test]
+fn test_deserializing_malformed_known_blocks_fails() {
+    let assistant = |block: serde_json::Value| {
+        serde_json::json!({
+            "type": "assistant",
+            "message": {"content": [block], "model": "claude-sonnet-4-5"},
+            "parent_tool_use_id": null
+        })
+    };
+    for block in [
+        serde_json::json!({"type": "text"}),
+        serde_json::json!({"type": "tool_use", "id": "toolu_1"}),
+        serde_json::json!({"type": "image", "source": {"type": "base64", "media_type": "image/png"}}),
+    ] {
+        let result = serde_json::from_value::<Message>(assistant(block.clone()));
+        assert!(result.is_err(), "{} parsed as {:?}", block, result);
+        assert!(parse_message(assistant(block)).is_err());
+    }
+
+    let unknown_block = serde_json::json!({"type": "citation", "cited_text": "x"});
+    let unknown_source = serde_json::json!({"type": "image", "source": {"type": "file", "file_id": "f_1"}});
+    for block in [unknown_block, unknown_source] {
+        let line = assistant(block);
+        let message: Message = serde_json::from_value(line.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&message).unwrap(), line);
+        let parsed = parse_message(line.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&parsed).unwrap(), line);
+    }
+}
+
+#[test]
+fn test_optional_assistant_fields_roundtrip_as_sent() {
+    let minimal = serde_json::json!({
+        "type": "assistant",
+        "message": {"content": [{"type": "thinking", "thinking": "hmm"}], "model": "claude-sonnet-4-5"},
+        "parent_tool_use_id": null
+    });
+    let full = serde_json::json!({
+        "type": "assistant",
+        "message": {
+            "content": [{"type": "thinking", "thinking": "", "signature": ""}, {"type": "text", "text": "done"}],
+            "model": "claude-sonnet-4-5",
+            "stop_reason": "stop_sequence",
+            "stop_sequence": "END"
+        },
+        "parent_tool_use_id": null
+    });
+
+    let mut nulls = minimal.clone();
+    nulls["message"]["stop_reason"] = Value::Null;
+    nulls["message"]["stop_sequence"] = Value::Null;
+
+    for line in [minimal, full, nulls] {
+        let message: Message = serde_json::from_value(line.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&message).unwrap(), line, "{:?}", message);
+        let parsed = parse_message(line.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&parsed).unwrap(), line, "{:?}", parsed);
+    }
+}
+
+#[test]
+fn test_result_and_usage_fields_roundtrip_as_sent() {
+    let minimal = serde_json::json!({
+        "type": "result",
+        "subtype": "success",
+        "duration_ms": 10,
+        "duration_api_ms": 5,
+        "is_error": false,
+        "num_turns": 1,
+        "session_id": "s"
+    });
+    let mut nulls = minimal.clone();
+    for key in ["total_cost_usd", "result", "usage", "modelUsage", "structured_output", "uuid"] {
+        nulls[key] = Value::Null;
+    }
+    let mut sparse_usage = minimal.clone();
+    sparse_usage["usage"] = serde_json::json!({
+        "input_tokens": 4,
+        "output_tokens": null,
+        "server_tool_use": {"web_search_requests": null}
+    });
+    sparse_usage["modelUsage"] = serde_json::json!({
+        "claude-haiku-4-5": {"inputTokens": 4, "cacheReadInputTokens": null, "costUSD": null}
+    });
+    let mut zeros = minimal.clone();
+    zeros["usage"] = serde_json::json!({"input_tokens": 0, "output_tokens": 0, "cache_read_input_tokens": 0});
+
+    for line in [minimal, nulls, sparse_usage, zeros] {
+        let message: Message = serde_json::from_value(line.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&message).unwrap(), line);
+        let parsed = parse_message(line.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&parsed).unwrap(), line);
+    }
+}
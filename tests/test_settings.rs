@@ -0,0 +1,166 @@
+//! Tests for typed settings and merging settings sources
+
+use claude_agent_sdk::{
+    ClaudeAgentOptions, ClaudeSDKError, HookEvent, PermissionMode, SettingSource, Settings, SettingsArg, SettingsHook,
+    SettingsHookMatcher,
+};
+use serde_json::json;
+use std::fs;
+use std::path::Path;
+
+fn write_settings(path: &Path, value: serde_json::Value) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, value.to_string()).unwrap();
+}
+
+#[test]
+fn test_settings_serde_keeps_unknown_keys() {
+    let value = json!({
+        "permissions": {
+            "allow": ["Bash(npm run test:*)"],
+            "deny": ["Read(./.env)"],
+            "ask": ["Bash(git push:*)"],
+            "defaultMode": "acceptEdits",
+            "futureRule": true
+        },
+        "env": {"FOO": "bar"},
+        "hooks": {
+            "PreToolUse": [{"matcher": "Bash", "hooks": [{"type": "command", "command": "./check.sh", "timeout": 30}]}]
+        },
+        "model": "claude-sonnet-4-5",
+        "enabledMcpjsonServers": ["memory"],
+        "spinnerTipsEnabled": false
+    });
+    let settings: Settings = serde_json::from_value(value.clone()).unwrap();
+
+    assert_eq!(settings.permissions.ask, ["Bash(git push:*)"]);
+    assert_eq!(settings.permissions.default_mode, Some(PermissionMode::AcceptEdits));
+    assert_eq!(settings.hooks[&HookEvent::PreToolUse][0].hooks[0].timeout, Some(30));
+    assert_eq!(settings.enabled_mcpjson_servers, ["memory"]);
+    assert_eq!(settings.extra["spinnerTipsEnabled"], false);
+    assert_eq!(serde_json::to_value(&settings).unwrap(), value);
+    assert_eq!(serde_json::to_value(Settings::default()).unwrap(), json!({}));
+}
+
+#[test]
+fn test_merge_layers_settings() {
+    let mut settings = Settings {
+        model: Some("claude-opus-4-1".to_string()),
+        ..Default::default()
+    };
+    settings.permissions.allow = vec!["Read".to_string(), "Grep".to_string()];
+    settings.env.insert("A".to_string(), "1".to_string());
+    settings.env.insert("B".to_string(), "1".to_string());
+
+    let mut local = Settings::default();
+    local.permissions.allow = vec!["Grep".to_string(), "Bash(ls:*)".to_string()];
+    local.env.insert("B".to_string(), "2".to_string());
+    local.hooks.insert(
+        HookEvent::PostToolUse,
+        vec![SettingsHookMatcher {
+            matcher: None,
+            hooks: vec![SettingsHook::command("./log.sh")],
+        }],
+    );
+    settings.merge(local);
+
+    assert_eq!(settings.model.as_deref(), Some("claude-opus-4-1"));
+    assert_eq!(settings.permissions.allow, ["Read", "Grep", "Bash(ls:*)"]);
+    assert_eq!(settings.env["A"], "1");
+    assert_eq!(settings.env["B"], "2");
+    assert_eq!(settings.hooks[&HookEvent::PostToolUse][0].hooks[0].command, "./log.sh");
+}
+
+#[test]
+fn test_effective_settings_merge_sources_in_cli_order() {
+    let config = tempfile::tempdir().unwrap();
+    let project = tempfile::tempdir().unwrap();
+    std::env::set_var("CLAUDE_CONFIG_DIR", config.path());
+    write_settings(
+        &config.path().join("settings.json"),
+        json!({"model": "user-model", "env": {"WHO": "user"}, "permissions": {"deny": ["WebFetch"]}}),
+    );
+    write_settings(
+        &project.path().join(".claude/settings.json"),
+        json!({"model": "project-model", "env": {"WHO": "project"}}),
+    );
+    write_settings(&project.path().join(".claude/settings.local.json"), json!({"env": {"WHO": "local"}}));
+
+    let mut options = ClaudeAgentOptions {
+        cwd: Some(project.path().to_path_buf()),
+        setting_sources: Some(vec![SettingSource::Local, SettingSource::User, SettingSource::Project]),
+        ..Default::default()
+    };
+    let settings = options.effective_settings().unwrap();
+    assert_eq!(settings.model.as_deref(), Some("project-model"));
+    assert_eq!(settings.env["WHO"], "local");
+    assert_eq!(settings.permissions.deny, ["WebFetch"]);
+
+    options.setting_sources = Some(vec![SettingSource::User]);
+    options.settings = Some(
+        Settings {
+            model: Some("inline-model".to_string()),
+            ..Default::default()
+        }
+        .into(),
+    );
+    let settings = options.effective_settings().unwrap();
+    assert_eq!(settings.model.as_deref(), Some("inline-model"));
+    assert_eq!(settings.env["WHO"], "user");
+
+    options.setting_sources = None;
+    options.settings = Some(".claude/settings.json".into());
+    let settings = options.effective_settings().unwrap();
+    assert_eq!(settings.model.as_deref(), Some("project-model"));
+    assert!(settings.permissions.deny.is_empty());
+    std::env::remove_var("CLAUDE_CONFIG_DIR");
+
+    // Without a home directory there are no user settings to read
+    let home = std::env::var_os("HOME");
+    std::env::remove_var("HOME");
+    assert_eq!(SettingSource::User.path(project.path()), None);
+    options.setting_sources = Some(vec![SettingSource::User]);
+    options.settings = None;
+    assert_eq!(options.effective_settings().unwrap(), Settings::default());
+    if let Some(home) = home {
+        std::env::set_var("HOME", home);
+    }
+}
+
+#[test]
+fn test_settings_file_errors() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("settings.json");
+    assert!(Settings::load(&path).unwrap().is_none());
+
+    fs::write(&path, r#"{"permissions": {"allow": "Read"}}"#).unwrap();
+    let error = Settings::load(&path).unwrap_err();
+    assert!(matches!(error, ClaudeSDKError::InvalidConfig(_)));
+    let message = error.to_string();
+    assert!(message.contains("settings.json: permissions.allow: invalid type"), "{}", message);
+
+    let options = ClaudeAgentOptions {
+        settings: Some(dir.path().join("missing.json").into()),
+        ..Default::default()
+    };
+    assert!(options.problems()[0].starts_with("settings file"));
+}
+
+#[test]
+fn test_settings_in_config_files() {
+    let options = ClaudeAgentOptions::from_toml_str(
+        r#"
+        [settings]
+        model = "claude-sonnet-4-5"
+        permissions = { allow = ["Read"] }
+        "#,
+    )
+    .unwrap();
+    let Some(SettingsArg::Inline(ref settings)) = options.settings else {
+        panic!("expected inline settings, got {:?}", options.settings);
+    };
+    assert_eq!(settings.permissions.allow, ["Read"]);
+
+    let options = ClaudeAgentOptions::from_json_str(r#"{"settings": "/etc/claude/settings.json"}"#).unwrap();
+    assert_eq!(options.settings, Some(SettingsArg::File("/etc/claude/settings.json".into())));
+}
@@ -0,0 +1,142 @@
+//! Tests for typed built-in tool inputs and outputs
+
+use claude_agent_sdk::tools::{BashOutput, GrepOutputMode, NotebookEditMode, TodoStatus};
+use claude_agent_sdk::types::{BaseHookInput, PostToolUseHookInput};
+use claude_agent_sdk::{BuiltinToolInput, BuiltinToolOutput, ContentBlock};
+use serde_json::json;
+
+#[test]
+fn test_parse_file_tools() {
+    match BuiltinToolInput::parse("Edit", &json!({"file_path": "/src/lib.rs", "old_string": "a", "new_string": "b"})) {
+        BuiltinToolInput::Edit(edit) => {
+            assert_eq!(edit.file_path, "/src/lib.rs");
+            assert_eq!(edit.old_string, "a");
+            assert_eq!(edit.new_string, "b");
+            assert_eq!(edit.replace_all, None);
+        }
+        other => panic!("Expected Edit, got {:?}", other),
+    }
+
+    let multi = BuiltinToolInput::parse(
+        "MultiEdit",
+        &json!({"file_path": "/a.rs", "edits": [{"old_string": "x", "new_string": "y", "replace_all": true}]}),
+    );
+    assert_eq!(multi.file_path(), Some("/a.rs"));
+    match multi {
+        BuiltinToolInput::MultiEdit(multi) => assert_eq!(multi.edits[0].replace_all, Some(true)),
+        other => panic!("Expected MultiEdit, got {:?}", other),
+    }
+
+    let read = BuiltinToolInput::parse("Read", &json!({"file_path": "/a.rs", "offset": 10, "limit": 5}));
+    assert_eq!(read.file_path(), Some("/a.rs"));
+    assert_eq!(BuiltinToolInput::parse("Bash", &json!({"command": "ls"})).file_path(), None);
+}
+
+#[test]
+fn test_parse_search_and_agent_tools() {
+    match BuiltinToolInput::parse("Grep", &json!({"pattern": "fn main", "output_mode": "content", "-n": true, "-C": 2, "type": "rust"})) {
+        BuiltinToolInput::Grep(grep) => {
+            assert_eq!(grep.output_mode, Some(GrepOutputMode::Content));
+            assert_eq!(grep.line_numbers, Some(true));
+            assert_eq!(grep.context, Some(2));
+            assert_eq!(grep.file_type.as_deref(), Some("rust"));
+        }
+        other => panic!("Expected Grep, got {:?}", other),
+    }
+
+    match BuiltinToolInput::parse(
+        "TodoWrite",
+        &json!({"todos": [{"content": "Write tests", "status": "in_progress", "activeForm": "Writing tests"}]}),
+    ) {
+        BuiltinToolInput::TodoWrite(todo) => {
+            assert_eq!(todo.todos[0].status, TodoStatus::InProgress);
+            assert_eq!(todo.todos[0].active_form, "Writing tests");
+        }
+        other => panic!("Expected TodoWrite, got {:?}", other),
+    }
+
+    match BuiltinToolInput::parse(
+        "NotebookEdit",
+        &json!({"notebook_path": "/n.ipynb", "new_source": "print(1)", "edit_mode": "insert"}),
+    ) {
+        BuiltinToolInput::NotebookEdit(edit) => assert_eq!(edit.edit_mode, Some(NotebookEditMode::Insert)),
+        other => panic!("Expected NotebookEdit, got {:?}", other),
+    }
+
+    assert!(matches!(
+        BuiltinToolInput::parse("Task", &json!({"description": "d", "prompt": "p", "subagent_type": "general-purpose"})),
+        BuiltinToolInput::Task(_)
+    ));
+    assert!(matches!(
+        BuiltinToolInput::parse("WebSearch", &json!({"query": "rust", "allowed_domains": ["rust-lang.org"]})),
+        BuiltinToolInput::WebSearch(_)
+    ));
+    assert!(matches!(
+        BuiltinToolInput::parse("ExitPlanMode", &json!({"plan": "1. Do it"})),
+        BuiltinToolInput::ExitPlanMode(_)
+    ));
+}
+
+#[test]
+fn test_unknown_and_malformed_inputs_keep_raw_input() {
+    let input = json!({"expression": "1 + 1"});
+    assert_eq!(
+        BuiltinToolInput::parse("mcp__calc__eval", &input),
+        BuiltinToolInput::Other {
+            name: "mcp__calc__eval".to_string(),
+            input: input.clone(),
+        }
+    );
+
+    // A built-in tool whose input has changed shape is not mistaken for another tool
+    let input = json!({"cmd": "ls"});
+    let BuiltinToolInput::Malformed { name, input: raw, error } = BuiltinToolInput::parse("Bash", &input) else {
+        panic!("expected Malformed");
+    };
+    assert_eq!(name, "Bash");
+    assert_eq!(raw, input);
+    assert!(error.contains("command"), "{}", error);
+}
+
+#[test]
+fn test_tool_use_view_from_content_block() {
+    let block = ContentBlock::ToolUse {
+        id: "toolu_1".to_string(),
+        name: "Bash".to_string(),
+        input: json!({"command": "cargo test", "timeout": 60000}),
+    };
+    let tool_use = block.as_tool_use().unwrap();
+    assert_eq!(tool_use.id, "toolu_1");
+    match tool_use.typed() {
+        BuiltinToolInput::Bash(bash) => {
+            assert_eq!(bash.command, "cargo test");
+            assert_eq!(bash.timeout, Some(60000));
+        }
+        other => panic!("Expected Bash, got {:?}", other),
+    }
+
+    assert!(ContentBlock::Text { text: "hi".to_string() }.as_tool_use().is_none());
+}
+
+#[test]
+fn test_post_tool_use_typed_response() {
+    let input = PostToolUseHookInput {
+        base: BaseHookInput::default(),
+        tool_name: "Bash".to_string(),
+        tool_input: json!({"command": "echo hi"}),
+        tool_response: json!({"stdout": "hi\n", "stderr": "", "interrupted": false, "isImage": false}),
+    };
+    assert!(matches!(input.typed_input(), BuiltinToolInput::Bash(_)));
+    assert_eq!(
+        input.typed_response(),
+        BuiltinToolOutput::Bash(BashOutput {
+            stdout: "hi\n".to_string(),
+            ..Default::default()
+        })
+    );
+
+    assert!(matches!(
+        BuiltinToolOutput::parse("Read", &json!({"type": "text"})),
+        BuiltinToolOutput::Other { .. }
+    ));
+}
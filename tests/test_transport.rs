@@ -47,7 +47,7 @@ impl Transport for MockTransport {
         Ok(())
     }
 
-    fn read_messages(&mut self) -> Pin<Box<dyn Stream<Item = claude_agent_sdk::errors::Result<Value>> + Send + '_>> {
+    fn read_messages(&mut self) -> Pin<Box<dyn Stream<Item = claude_agent_sdk::errors::Result<Value>> + Send + 'static>> {
         let messages = self.messages.lock().unwrap().clone();
         Box::pin(futures::stream::iter(
             messages.into_iter().map(Ok)
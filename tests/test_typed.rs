@@ -0,0 +1,83 @@
+//! Tests for query_typed against a fake CLI script
+
+#![cfg(unix)]
+
+use claude_agent_sdk::typed::schema_for;
+use claude_agent_sdk::{query_typed, query_typed_with_retries, ClaudeAgentOptions, ClaudeSDKError};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use std::path::PathBuf;
+
+#[derive(Debug, PartialEq, Deserialize, JsonSchema)]
+struct Capital {
+    city: String,
+    population: u64,
+}
+
+fn fake_cli_options(env: &[(&str, &str)]) -> ClaudeAgentOptions {
+    let mut options = ClaudeAgentOptions {
+        cli_path: Some(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/fake_claude.sh")),
+        ..Default::default()
+    };
+    for (key, value) in env {
+        options.env.insert(key.to_string(), value.to_string());
+    }
+    options
+}
+
+#[test]
+fn test_schema_is_derived_from_type() {
+    let schema = schema_for::<Capital>();
+    assert_eq!(schema["type"], "object");
+    assert_eq!(schema["properties"]["population"]["type"], "integer");
+    let required: Vec<&str> = schema["required"].as_array().unwrap().iter().map(|v| v.as_str().unwrap()).collect();
+    assert_eq!(required, ["city", "population"]);
+}
+
+#[tokio::test]
+async fn test_query_typed_deserializes_structured_output() {
+    let options = fake_cli_options(&[("FAKE_CLAUDE_STRUCTURED_OUTPUT", r#"{"city":"Tokyo","population":14000000}"#)]);
+    let answer = query_typed::<Capital>("capital of Japan?", options).await.unwrap();
+
+    assert_eq!(
+        answer.value,
+        Capital {
+            city: "Tokyo".to_string(),
+            population: 14_000_000
+        }
+    );
+    assert_eq!(answer.attempts, 1);
+    assert_eq!(answer.turns.len(), 1);
+    assert_eq!(answer.session_id, answer.turns[0].session_id);
+    assert!((answer.spend.total_cost_usd - 0.001).abs() < 1e-9);
+    assert_eq!(answer.spend.total_tokens, 15);
+}
+
+#[tokio::test]
+async fn test_query_typed_retries_with_validation_error() {
+    let options = fake_cli_options(&[
+        ("FAKE_CLAUDE_STRUCTURED_OUTPUT_1", r#"{"city":"Tokyo","population":"lots"}"#),
+        ("FAKE_CLAUDE_STRUCTURED_OUTPUT_2", r#"{"city":"Tokyo","population":14000000}"#),
+    ]);
+    let answer = query_typed::<Capital>("capital of Japan?", options).await.unwrap();
+
+    assert_eq!(answer.value.population, 14_000_000);
+    assert_eq!(answer.attempts, 2);
+    assert_eq!(answer.turns.len(), 2);
+    assert!((answer.spend.total_cost_usd - 0.002).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn test_query_typed_gives_up_after_retries() {
+    let options = fake_cli_options(&[("FAKE_CLAUDE_STRUCTURED_OUTPUT", r#"{"city":"Tokyo"}"#)]);
+    let error = query_typed_with_retries::<Capital>("capital of Japan?", options, 1).await.unwrap_err();
+
+    match error {
+        ClaudeSDKError::MessageParse { message, data } => {
+            assert!(message.contains("after 2 attempts"), "{}", message);
+            assert!(message.contains("missing field `population`"), "{}", message);
+            assert_eq!(data.unwrap()["city"], "Tokyo");
+        }
+        other => panic!("expected MessageParse, got {:?}", other),
+    }
+}
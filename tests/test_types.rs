@@ -20,14 +20,14 @@ fn test_claude_agent_options_default() {
 fn test_claude_agent_options_builder() {
     let options = ClaudeAgentOptions {
         allowed_tools: vec!["Read".to_string(), "Write".to_string()],
-        permission_mode: Some("acceptEdits".to_string()),
+        permission_mode: Some(PermissionMode::AcceptEdits),
         max_turns: Some(5),
         cwd: Some(PathBuf::from("/test")),
         ..Default::default()
     };
 
     assert_eq!(options.allowed_tools.len(), 2);
-    assert_eq!(options.permission_mode.as_ref().unwrap(), "acceptEdits");
+    assert_eq!(options.permission_mode, Some(PermissionMode::AcceptEdits));
     assert_eq!(options.max_turns, Some(5));
     assert_eq!(options.cwd, Some(PathBuf::from("/test")));
 }
@@ -58,21 +58,68 @@ fn test_claude_agent_options_debug() {
 }
 
 #[test]
-fn test_permission_mode_constants() {
-    assert_eq!(PERMISSION_MODE_DEFAULT, "default");
-    assert_eq!(PERMISSION_MODE_ACCEPT_EDITS, "acceptEdits");
-    assert_eq!(PERMISSION_MODE_PLAN, "plan");
-    assert_eq!(PERMISSION_MODE_BYPASS, "bypassPermissions");
+fn test_permission_mode_serialization() {
+    assert_eq!(PermissionMode::Default.as_str(), "default");
+    assert_eq!(PermissionMode::AcceptEdits.as_str(), "acceptEdits");
+    assert_eq!(PermissionMode::Plan.as_str(), "plan");
+    assert_eq!(PermissionMode::BypassPermissions.as_str(), "bypassPermissions");
+
+    assert_eq!(serde_json::to_value(PermissionMode::AcceptEdits).unwrap(), json!("acceptEdits"));
+    let mode: PermissionMode = serde_json::from_value(json!("plan")).unwrap();
+    assert_eq!(mode, PermissionMode::Plan);
 }
 
 #[test]
-fn test_hook_event_constants() {
-    assert_eq!(HOOK_PRE_TOOL_USE, "PreToolUse");
-    assert_eq!(HOOK_POST_TOOL_USE, "PostToolUse");
-    assert_eq!(HOOK_USER_PROMPT_SUBMIT, "UserPromptSubmit");
-    assert_eq!(HOOK_STOP, "Stop");
-    assert_eq!(HOOK_SUBAGENT_STOP, "SubagentStop");
-    assert_eq!(HOOK_PRE_COMPACT, "PreCompact");
+fn test_string_enums_keep_unknown_values() {
+    let mode: PermissionMode = serde_json::from_value(json!("someFutureMode")).unwrap();
+    assert_eq!(mode, PermissionMode::Other("someFutureMode".to_string()));
+    assert_eq!(serde_json::to_value(&mode).unwrap(), json!("someFutureMode"));
+
+    // Known values always map to their variant
+    assert_eq!(PermissionMode::from("acceptEdits"), PermissionMode::AcceptEdits);
+    assert_eq!(HookEvent::from("Notification").to_string(), "Notification");
+    assert_eq!(ResultSubtype::from("error_max_turns"), ResultSubtype::ErrorMaxTurns);
+    assert_eq!(ResultSubtype::from("error_max_budget"), "error_max_budget");
+}
+
+#[test]
+fn test_hook_event_serialization() {
+    assert_eq!(HookEvent::PreToolUse.as_str(), "PreToolUse");
+    assert_eq!(HookEvent::PostToolUse.as_str(), "PostToolUse");
+    assert_eq!(HookEvent::UserPromptSubmit.as_str(), "UserPromptSubmit");
+    assert_eq!(HookEvent::Stop.as_str(), "Stop");
+    assert_eq!(HookEvent::SubagentStop.as_str(), "SubagentStop");
+    assert_eq!(HookEvent::PreCompact.as_str(), "PreCompact");
+}
+
+#[test]
+fn test_permission_update_serialization() {
+    let update = PermissionUpdate::AddRules {
+        rules: vec![PermissionRuleValue {
+            tool_name: "Bash".to_string(),
+            rule_content: None,
+        }],
+        behavior: PermissionBehavior::Allow,
+        destination: Some(PermissionUpdateDestination::Session),
+    };
+    let value = serde_json::to_value(&update).unwrap();
+    assert_eq!(value["type"], "addRules");
+    assert_eq!(value["behavior"], "allow");
+    assert_eq!(value["destination"], "session");
+
+    let mode: PermissionUpdate = serde_json::from_value(json!({
+        "type": "setMode",
+        "mode": "acceptEdits",
+        "destination": "localSettings"
+    }))
+    .unwrap();
+    match mode {
+        PermissionUpdate::SetMode { mode, destination } => {
+            assert_eq!(mode, PermissionMode::AcceptEdits);
+            assert_eq!(destination, Some(PermissionUpdateDestination::LocalSettings));
+        }
+        other => panic!("unexpected update: {:?}", other),
+    }
 }
 
 #[test]
@@ -226,7 +273,7 @@ fn test_content_block_text() {
 fn test_content_block_thinking() {
     let block = ContentBlock::Thinking {
         thinking: "Analyzing...".to_string(),
-        signature: "sig123".to_string(),
+        signature: Some("sig123".to_string()),
     };
     let json = serde_json::to_value(&block).unwrap();
     assert_eq!(json["type"], "thinking");
@@ -280,10 +327,107 @@ fn test_hook_matcher_clone() {
     assert_eq!(cloned.matcher, matcher.matcher);
 }
 
+#[test]
+fn test_hook_input_deserialization() {
+    let input: HookInput = serde_json::from_value(json!({
+        "hook_event_name": "PreToolUse",
+        "session_id": "s",
+        "transcript_path": "/tmp/t.jsonl",
+        "cwd": "/repo",
+        "permission_mode": "default",
+        "tool_name": "Bash",
+        "tool_input": {"command": "ls"}
+    }))
+    .unwrap();
+    match input {
+        HookInput::PreToolUse(input) => {
+            assert_eq!(input.tool_name, "Bash");
+            assert_eq!(input.tool_input["command"], "ls");
+            assert_eq!(input.base.permission_mode, Some(PermissionMode::Default));
+        }
+        other => panic!("unexpected input: {:?}", other),
+    }
+
+    let input: HookInput = serde_json::from_value(json!({
+        "hook_event_name": "PreCompact",
+        "session_id": "s",
+        "transcript_path": "/tmp/t.jsonl",
+        "cwd": "/repo",
+        "trigger": "auto",
+        "custom_instructions": null
+    }))
+    .unwrap();
+    match input {
+        HookInput::PreCompact(input) => assert_eq!(input.trigger, CompactTrigger::Auto),
+        other => panic!("unexpected input: {:?}", other),
+    }
+}
+
+#[test]
+fn test_hook_output_serialization() {
+    let output = HookJSONOutput {
+        continue_: Some(false),
+        stop_reason: Some("done".to_string()),
+        suppress_output: Some(true),
+        hook_specific_output: Some(HookSpecificOutput::PreToolUse {
+            permission_decision: Some(PermissionBehavior::Deny),
+            permission_decision_reason: Some("not allowed".to_string()),
+        }),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        serde_json::to_value(&output).unwrap(),
+        json!({
+            "continue": false,
+            "stopReason": "done",
+            "suppressOutput": true,
+            "hookSpecificOutput": {
+                "hookEventName": "PreToolUse",
+                "permissionDecision": "deny",
+                "permissionDecisionReason": "not allowed"
+            }
+        })
+    );
+    assert_eq!(serde_json::to_value(HookJSONOutput::default()).unwrap(), json!({}));
+}
+
+#[tokio::test]
+async fn test_add_hook_registers_typed_callback() {
+    let mut options = ClaudeAgentOptions::default();
+    options.add_hook(Some("Bash"), |input: PreToolUseHookInput, _tool_use_id, _ctx| async move {
+        HookJSONOutput {
+            reason: Some(input.tool_name),
+            ..Default::default()
+        }
+    });
+
+    let matchers = &options.hooks[&HookEvent::PreToolUse];
+    assert_eq!(matchers[0].matcher.as_deref(), Some("Bash"));
+
+    let callback = &matchers[0].hooks[0];
+    let input = json!({
+        "hook_event_name": "PreToolUse",
+        "session_id": "s",
+        "transcript_path": "/tmp/t.jsonl",
+        "cwd": "/repo",
+        "tool_name": "Bash",
+        "tool_input": {}
+    });
+    let output = callback(input, None, HookContext {}).await;
+    assert_eq!(output.reason.as_deref(), Some("Bash"));
+
+    // Input for a different event is ignored rather than misinterpreted
+    let output = callback(json!({"hook_event_name": "Stop"}), None, HookContext {}).await;
+    assert_eq!(output, HookJSONOutput::default());
+}
+
 #[test]
 fn test_tool_permission_context() {
     let context = ToolPermissionContext {
         suggestions: Vec::new(),
+        blocked_path: None,
+        tool_use_id: None,
     };
 
     assert!(context.suggestions.is_empty());
@@ -318,3 +462,40 @@ fn test_sdk_control_response_serialization() {
     let json = serde_json::to_value(&response).unwrap();
     assert_eq!(json["type"], "control_response");
 }
+
+#[test]
+fn test_recovery_policy_backoff() {
+    let policy = RecoveryPolicy {
+        max_retries: 5,
+        initial_backoff: std::time::Duration::from_millis(100),
+        max_backoff: std::time::Duration::from_millis(350),
+    };
+
+    assert_eq!(policy.backoff_for(1).as_millis(), 100);
+    assert_eq!(policy.backoff_for(2).as_millis(), 200);
+    assert_eq!(policy.backoff_for(3).as_millis(), 350);
+    assert_eq!(policy.backoff_for(40).as_millis(), 350);
+}
+
+#[test]
+fn test_system_prompt_file_variants_serde() {
+    let prompt = SystemPrompt::File(PathBuf::from("/etc/prompts/review.md"));
+    let json = serde_json::to_value(&prompt).unwrap();
+    assert_eq!(json, json!({"type": "file", "path": "/etc/prompts/review.md"}));
+    assert_eq!(serde_json::from_value::<SystemPrompt>(json).unwrap(), prompt);
+
+    let prompt: SystemPrompt = serde_json::from_value(json!({"type": "append_file", "path": "extra.md"})).unwrap();
+    assert_eq!(prompt, SystemPrompt::AppendFile(PathBuf::from("extra.md")));
+
+    let prompt: SystemPrompt = serde_json::from_value(json!("Be brief.")).unwrap();
+    assert_eq!(prompt, SystemPrompt::Text("Be brief.".to_string()));
+}
+
+#[test]
+fn test_render_template() {
+    let vars = HashMap::from([("name".to_string(), "Ada".to_string())]);
+    assert_eq!(render_template("Hi {name}!", &vars).unwrap(), "Hi Ada!");
+    assert_eq!(render_template("{{name}} is {name}", &vars).unwrap(), "{name} is Ada");
+    assert_eq!(render_template(r#"{"a": 1} { name } {1x}"#, &vars).unwrap(), r#"{"a": 1} { name } {1x}"#);
+    assert!(render_template("{missing}", &vars).is_err());
+}